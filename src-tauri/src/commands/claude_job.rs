@@ -0,0 +1,80 @@
+use tauri::State;
+
+use crate::models::claude_job::{ClaudeJob, ClaudeJobQueue};
+use crate::services::claude_job_service::ClaudeJobService;
+use crate::services::pty_service::ClaudeLaunchOptions;
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn start_claude_job(
+    project_path: String,
+    prompt: String,
+    claude_options: Option<ClaudeLaunchOptions>,
+    job_service: State<'_, ClaudeJobService>,
+) -> Result<ClaudeJob, String> {
+    job_service.start(&project_path, &prompt, claude_options.unwrap_or_default())
+}
+
+/// `max_retries` defaults to the `claude.job_max_retries` setting.
+#[tauri::command(rename_all = "snake_case")]
+pub fn enqueue_claude_job(
+    project_path: String,
+    prompt: String,
+    claude_options: Option<ClaudeLaunchOptions>,
+    max_retries: Option<u32>,
+    job_service: State<'_, ClaudeJobService>,
+) -> Result<ClaudeJob, String> {
+    job_service.enqueue(
+        &project_path,
+        &prompt,
+        claude_options.unwrap_or_default(),
+        max_retries,
+    )
+}
+
+/// Moves the given pending jobs to the front of the queue, in order.
+#[tauri::command(rename_all = "snake_case")]
+pub fn reorder_claude_jobs(
+    job_ids: Vec<String>,
+    job_service: State<'_, ClaudeJobService>,
+) -> Result<ClaudeJobQueue, String> {
+    job_service.reorder(&job_ids)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_claude_job_queue_paused(
+    paused: bool,
+    job_service: State<'_, ClaudeJobService>,
+) -> Result<ClaudeJobQueue, String> {
+    job_service.set_paused(paused)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_claude_job_queue(
+    job_service: State<'_, ClaudeJobService>,
+) -> Result<ClaudeJobQueue, String> {
+    job_service.queue()
+}
+
+/// Also removes pending jobs from the queue. Returns false when the job already finished.
+#[tauri::command(rename_all = "snake_case")]
+pub fn cancel_claude_job(
+    job_id: String,
+    job_service: State<'_, ClaudeJobService>,
+) -> Result<bool, String> {
+    job_service.cancel(&job_id)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn list_claude_jobs(
+    job_service: State<'_, ClaudeJobService>,
+) -> Result<Vec<ClaudeJob>, String> {
+    job_service.list()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_claude_job(
+    job_id: String,
+    job_service: State<'_, ClaudeJobService>,
+) -> Result<ClaudeJob, String> {
+    job_service.get(&job_id)
+}
@@ -1,5 +1,16 @@
+use crate::models::claude_session::{
+    ClaudeProjectMigration, ClaudeSearchHit, ClaudeTranscriptCursor, ClaudeTranscriptPage,
+    ClaudeTrashEntry,
+};
+use crate::models::claude_usage::ClaudeUsageReport;
 use crate::models::ClaudeSession;
-use crate::services::ClaudeSessionService;
+use crate::services::claude_export_service::ClaudeExportFormat;
+use crate::services::{
+    ClaudeExportService, ClaudeMigrationService, ClaudeSearchService, ClaudeSessionService,
+    ClaudeTrashService, ClaudeUsageService,
+};
+use crate::AppState;
+use tauri::State;
 
 #[tauri::command(rename_all = "snake_case")]
 pub fn list_claude_sessions(
@@ -23,7 +34,103 @@ pub fn rename_claude_session(
     ClaudeSessionService::rename_claude_session(&project_path, &session_id, &session_name)
 }
 
+/// Moves the session into the trash; it can be restored until it expires.
 #[tauri::command(rename_all = "snake_case")]
-pub fn delete_claude_session(project_path: String, session_id: String) -> Result<(), String> {
-    ClaudeSessionService::delete_claude_session(&project_path, &session_id)
+pub fn delete_claude_session(
+    project_path: String,
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    purge_expired_trash(&state);
+    ClaudeTrashService::trash_session(&project_path, &session_id).map(|_| ())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn list_claude_trash(state: State<'_, AppState>) -> Result<Vec<ClaudeTrashEntry>, String> {
+    purge_expired_trash(&state);
+    ClaudeTrashService::list_trash()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn restore_claude_session(trash_id: String) -> Result<ClaudeTrashEntry, String> {
+    ClaudeTrashService::restore_session(&trash_id)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn purge_claude_trash(trash_id: Option<String>) -> Result<u32, String> {
+    ClaudeTrashService::purge(trash_id.as_deref())
+}
+
+fn purge_expired_trash(state: &AppState) {
+    let retention_days = match state.settings_service.get_settings() {
+        Ok(settings) => settings.claude.trash_retention_days,
+        Err(error) => {
+            log::warn!("Skipping trash expiry, settings unavailable: {}", error);
+            return;
+        }
+    };
+
+    if let Err(error) = ClaudeTrashService::purge_expired(retention_days) {
+        log::warn!("Failed to purge expired Claude trash: {}", error);
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn fork_claude_session(
+    project_path: String,
+    session_id: String,
+    message_index: u32,
+) -> Result<ClaudeSession, String> {
+    ClaudeSessionService::fork_claude_session(&project_path, &session_id, message_index)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_claude_session_transcript(
+    project_path: String,
+    session_id: String,
+    cursor: Option<ClaudeTranscriptCursor>,
+    limit: Option<usize>,
+) -> Result<ClaudeTranscriptPage, String> {
+    ClaudeSessionService::read_transcript(&project_path, &session_id, cursor, limit)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn search_claude_sessions(
+    query: String,
+    project_path: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ClaudeSearchHit>, String> {
+    ClaudeSearchService::search(&query, project_path.as_deref(), limit)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn export_claude_session(
+    project_path: String,
+    session_id: String,
+    format: ClaudeExportFormat,
+    output_path: String,
+) -> Result<String, String> {
+    ClaudeExportService::export_session(&project_path, &session_id, format, &output_path)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_claude_usage(
+    project_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ClaudeUsageReport, String> {
+    let settings = state.settings_service.get_settings()?;
+    ClaudeUsageService::usage_report(project_path.as_deref(), &settings.claude.model_prices)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn migrate_claude_project(
+    old_project_path: String,
+    new_project_path: String,
+    dry_run: Option<bool>,
+) -> Result<ClaudeProjectMigration, String> {
+    ClaudeMigrationService::migrate_project(
+        &old_project_path,
+        &new_project_path,
+        dry_run.unwrap_or(false),
+    )
 }
@@ -1,3 +1,4 @@
+pub mod claude_job;
 pub mod claude_session;
 pub mod project;
 pub mod pty;
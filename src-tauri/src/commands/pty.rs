@@ -1,6 +1,22 @@
-use tauri::{Emitter, State};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
 
-use crate::services::pty_service::{PtyLaunchConfig, PtyManager};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use tauri::ipc::{Channel, Response};
+use tauri::{Emitter, Manager, State};
+
+use crate::models::pty_recording::PtyRecording;
+use crate::services::pty_env_service::PtyEnvService;
+use crate::services::pty_process_service::PtyProcessReport;
+use crate::services::pty_recording_service::PtyRecordingService;
+use crate::services::pty_service::{
+    ClaudeLaunchOptions, PtyAttachment, PtyByteSubscription, PtyClaudeSession, PtyLaunchConfig,
+    PtyLaunchMode, PtyManager, TauriPtyEvents,
+};
+use crate::services::pty_shutdown::{PtyCloseOptions, PtyCloseOutcome, PtyCloseSignal};
+use crate::AppState;
 
 #[derive(serde::Serialize, Clone)]
 struct PtyCreateFailedPayload {
@@ -8,36 +24,81 @@ struct PtyCreateFailedPayload {
     error: String,
 }
 
+/// `launch_mode` takes precedence over the older `enable_claude_resume` flag.
 #[allow(clippy::too_many_arguments)]
 #[tauri::command(rename_all = "snake_case")]
 pub fn create_pty(
     session_id: String,
     working_dir: String,
     enable_claude_resume: Option<bool>,
+    launch_mode: Option<PtyLaunchMode>,
     resume_session_id: Option<String>,
+    prompt: Option<String>,
+    claude_session_id: Option<String>,
+    claude_options: Option<ClaudeLaunchOptions>,
     claude_args: Option<Vec<String>>,
     allow_dangerously_skip_permissions: Option<bool>,
+    shell: Option<String>,
+    shell_args: Option<Vec<String>>,
+    record: Option<bool>,
+    app_session_id: Option<String>,
+    project_id: Option<String>,
+    load_dotenv: Option<bool>,
     app_handle: tauri::AppHandle,
+    app_state: State<'_, AppState>,
     state: State<'_, PtyManager>,
 ) -> Result<bool, String> {
-    let launch_config = if enable_claude_resume.unwrap_or(false) {
-        let resume_target = resume_session_id.unwrap_or_else(|| session_id.clone());
-        let mut resolved_claude_args = claude_args.unwrap_or_default();
-
-        if allow_dangerously_skip_permissions.unwrap_or(false)
-            && !resolved_claude_args
-                .iter()
-                .any(|arg| arg == "--dangerously-skip-permissions")
-        {
-            resolved_claude_args.push("--dangerously-skip-permissions".to_string());
-        }
-
-        PtyLaunchConfig::claude_resume(resume_target, resolved_claude_args)
+    let launch_mode = launch_mode.unwrap_or(if enable_claude_resume.unwrap_or(false) {
+        PtyLaunchMode::ClaudeResume
     } else {
-        PtyLaunchConfig::plain()
-    };
+        PtyLaunchMode::Plain
+    });
+    let mut resolved_claude_args = claude_args.unwrap_or_default();
+    if allow_dangerously_skip_permissions.unwrap_or(false)
+        && !resolved_claude_args
+            .iter()
+            .any(|arg| arg == "--dangerously-skip-permissions")
+    {
+        resolved_claude_args.push("--dangerously-skip-permissions".to_string());
+    }
 
-    match state.create(&session_id, &working_dir, launch_config, app_handle.clone()) {
+    let launch_config = match launch_mode {
+        PtyLaunchMode::Plain => PtyLaunchConfig::plain(),
+        PtyLaunchMode::ClaudeResume => PtyLaunchConfig::claude_resume(
+            resume_session_id.unwrap_or_else(|| session_id.clone()),
+            resolved_claude_args,
+        ),
+        PtyLaunchMode::ClaudeNew => PtyLaunchConfig::claude_new(resolved_claude_args),
+        PtyLaunchMode::ClaudeContinue => PtyLaunchConfig::claude_continue(resolved_claude_args),
+        PtyLaunchMode::ClaudePrompt => {
+            PtyLaunchConfig::claude_prompt(prompt.unwrap_or_default(), resolved_claude_args)
+        }
+    }
+    .with_claude_session_id(claude_session_id)
+    .with_claude_options(claude_options.unwrap_or_default());
+    let record = record.unwrap_or_else(|| {
+        app_state
+            .settings_service
+            .get_settings()
+            .map(|settings| settings.terminal.record_sessions)
+            .unwrap_or(false)
+    });
+    let launch_result = resolve_session_launch(
+        &app_state,
+        app_session_id.as_deref(),
+        project_id.as_deref(),
+        &working_dir,
+        load_dotenv,
+    )
+    .and_then(|(session_shell, env)| {
+        let launch_config = launch_config
+            .with_recording(record)
+            .with_shell(shell.or(session_shell), shell_args.unwrap_or_default())
+            .with_env(env);
+        state.create(&session_id, &working_dir, launch_config)
+    });
+
+    match launch_result {
         Ok(created) => Ok(created),
         Err(error) => {
             log::warn!("create_pty failed for {}: {}", session_id, error);
@@ -60,6 +121,48 @@ pub fn create_pty(
     }
 }
 
+/// Shell and environment for a PTY tied to a stored session and/or project.
+/// `.env` is read from the project root, or the working dir when no project is
+/// given, if `load_dotenv` (default: the `terminal.load_dotenv` setting) is on.
+fn resolve_session_launch(
+    app_state: &AppState,
+    app_session_id: Option<&str>,
+    project_id: Option<&str>,
+    working_dir: &str,
+    load_dotenv: Option<bool>,
+) -> Result<(Option<String>, BTreeMap<String, String>), String> {
+    let session = match app_session_id.map(str::trim).filter(|id| !id.is_empty()) {
+        Some(id) => Some(app_state.session_service.get_session(id)?),
+        None => None,
+    };
+
+    let project_id = project_id
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .or_else(|| session.as_ref().map(|session| session.project_id.as_str()));
+    let project_root = match project_id {
+        Some(id) => app_state.project_service.get_project(id)?.path,
+        None => working_dir.trim().to_string(),
+    };
+
+    let load_dotenv = load_dotenv.unwrap_or_else(|| {
+        app_state
+            .settings_service
+            .get_settings()
+            .map(|settings| settings.terminal.load_dotenv)
+            .unwrap_or(false)
+    });
+    let dotenv_dir = (load_dotenv && !project_root.is_empty()).then(|| Path::new(&project_root));
+
+    let (session_shell, session_variables) = match session {
+        Some(session) => (Some(session.shell), session.environment_variables),
+        None => (None, HashMap::new()),
+    };
+    let env = PtyEnvService::resolve(dotenv_dir, &session_variables)?;
+
+    Ok((session_shell, env))
+}
+
 #[tauri::command(rename_all = "snake_case")]
 pub fn write_pty(
     session_id: String,
@@ -72,6 +175,22 @@ pub fn write_pty(
     })
 }
 
+/// Binary-safe input: `data` is base64 and is written to the PTY unchanged.
+#[tauri::command(rename_all = "snake_case")]
+pub fn write_pty_bytes(
+    session_id: String,
+    data: String,
+    state: State<'_, PtyManager>,
+) -> Result<(), String> {
+    let bytes = BASE64
+        .decode(data.as_bytes())
+        .map_err(|e| format!("Invalid base64 PTY input: {}", e))?;
+    state.write_bytes(&session_id, &bytes).map_err(|error| {
+        log::debug!("write_pty_bytes failed for {}: {}", session_id, error);
+        error
+    })
+}
+
 #[tauri::command(rename_all = "snake_case")]
 pub fn resize_pty(
     session_id: String,
@@ -85,18 +204,171 @@ pub fn resize_pty(
     })
 }
 
+/// Binary-safe output: the scrollback and then every raw chunk arrive on `on_output`
+/// as `ArrayBuffer`s. `pty-output` text events keep flowing alongside.
 #[tauri::command(rename_all = "snake_case")]
-pub fn close_pty(session_id: String, state: State<'_, PtyManager>) -> Result<(), String> {
-    state.close(&session_id).map_err(|error| {
-        log::warn!("close_pty failed for {}: {}", session_id, error);
+pub fn subscribe_pty_bytes(
+    session_id: String,
+    on_output: Channel<Response>,
+    state: State<'_, PtyManager>,
+) -> Result<PtyByteSubscription, String> {
+    let sink = Box::new(move |data: &[u8]| on_output.send(Response::new(data.to_vec())).is_ok());
+    state.subscribe_bytes(&session_id, sink).map_err(|error| {
+        log::debug!("subscribe_pty_bytes failed for {}: {}", session_id, error);
         error
     })
 }
 
 #[tauri::command(rename_all = "snake_case")]
-pub fn close_all_ptys(state: State<'_, PtyManager>) -> Result<(), String> {
-    state.close_all().map_err(|error| {
-        log::warn!("close_all_ptys failed: {}", error);
+pub fn unsubscribe_pty_bytes(
+    session_id: String,
+    subscription_id: u64,
+    state: State<'_, PtyManager>,
+) -> Result<bool, String> {
+    state.unsubscribe_bytes(&session_id, subscription_id)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn attach_pty(
+    session_id: String,
+    state: State<'_, PtyManager>,
+) -> Result<PtyAttachment, String> {
+    state.attach(&session_id).map_err(|error| {
+        log::debug!("attach_pty failed for {}: {}", session_id, error);
         error
     })
 }
+
+/// Terminal close settings, with per-call overrides.
+fn close_options(
+    app_state: &AppState,
+    signal: Option<String>,
+    grace_period_ms: Option<u64>,
+) -> Result<PtyCloseOptions, String> {
+    let mut options = app_state
+        .settings_service
+        .get_settings()
+        .map(|settings| PtyCloseOptions::from_settings(&settings.terminal))
+        .unwrap_or_default();
+
+    if let Some(signal) = signal {
+        options.signal = PtyCloseSignal::parse(&signal)
+            .ok_or_else(|| format!("Unsupported close signal: {}", signal))?;
+    }
+    if let Some(grace_period_ms) = grace_period_ms {
+        options.grace_period_ms = grace_period_ms;
+    }
+
+    Ok(options)
+}
+
+/// Runs blocking `PtyManager` work on the blocking thread pool. Async commands share
+/// the async runtime, which a grace period or a process scan would otherwise stall.
+async fn run_blocking<T, F>(app_handle: tauri::AppHandle, work: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&PtyManager) -> Result<T, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(move || work(&app_handle.state::<PtyManager>()))
+        .await
+        .map_err(|error| format!("PTY task failed: {}", error))?
+}
+
+/// Async so the grace period does not block the main thread.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn close_pty(
+    session_id: String,
+    signal: Option<String>,
+    grace_period_ms: Option<u64>,
+    app_handle: tauri::AppHandle,
+    app_state: State<'_, AppState>,
+) -> Result<PtyCloseOutcome, String> {
+    let options = close_options(&app_state, signal, grace_period_ms)?;
+    run_blocking(app_handle, move |manager| {
+        manager.close(&session_id, &options).map_err(|error| {
+            log::warn!("close_pty failed for {}: {}", session_id, error);
+            error
+        })
+    })
+    .await
+}
+
+/// Closes every PTY and waits for all children to exit.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn close_all_ptys(
+    signal: Option<String>,
+    grace_period_ms: Option<u64>,
+    app_handle: tauri::AppHandle,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<PtyCloseOutcome>, String> {
+    let options = close_options(&app_state, signal, grace_period_ms)?;
+    run_blocking(app_handle, move |manager| {
+        manager.close_all(&options).map_err(|error| {
+            log::warn!("close_all_ptys failed: {}", error);
+            error
+        })
+    })
+    .await
+}
+
+/// Claude sessions started by live PTYs, keyed by the PTY's session id.
+#[tauri::command(rename_all = "snake_case")]
+pub fn list_pty_claude_sessions(
+    state: State<'_, PtyManager>,
+) -> Result<Vec<PtyClaudeSession>, String> {
+    state.claude_sessions()
+}
+
+/// Processes and CPU / memory use of the given PTYs, or of all of them.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_pty_processes(
+    session_ids: Option<Vec<String>>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<PtyProcessReport>, String> {
+    run_blocking(app_handle, move |manager| {
+        manager
+            .processes(&session_ids.unwrap_or_default())
+            .map_err(|error| {
+                log::warn!("get_pty_processes failed: {}", error);
+                error
+            })
+    })
+    .await
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn list_pty_recordings() -> Result<Vec<PtyRecording>, String> {
+    PtyRecordingService::list_recordings()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn export_pty_recording(recording_id: String, output_path: String) -> Result<String, String> {
+    PtyRecordingService::export_recording(&recording_id, &output_path)
+}
+
+/// Replays a recording as `pty-output` and `pty-replay-resize` events for `session_id`,
+/// which must not be a live PTY, followed by `pty-replay-finished`.
+#[tauri::command(rename_all = "snake_case")]
+pub fn replay_pty_recording(
+    recording_id: String,
+    session_id: String,
+    speed: Option<f64>,
+    idle_time_limit: Option<f64>,
+    app_handle: tauri::AppHandle,
+    state: State<'_, PtyManager>,
+) -> Result<(), String> {
+    if state.list()?.iter().any(|id| id == session_id.trim()) {
+        return Err(format!(
+            "Cannot replay into live PTY session: {}",
+            session_id.trim()
+        ));
+    }
+
+    PtyRecordingService::replay_recording(
+        &recording_id,
+        &session_id,
+        speed,
+        idle_time_limit,
+        Arc::new(TauriPtyEvents::new(app_handle)),
+    )
+}
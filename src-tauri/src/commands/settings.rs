@@ -1,6 +1,7 @@
 use tauri::State;
 
 use crate::models::app_settings::AppSettings;
+use crate::services::claude_job_service::{ClaudeJobQueueOptions, ClaudeJobService};
 use crate::AppState;
 
 #[tauri::command(rename_all = "snake_case")]
@@ -15,12 +16,20 @@ pub fn get_app_settings(state: State<'_, AppState>) -> Result<AppSettings, Strin
 pub fn set_app_settings(
     settings: AppSettings,
     state: State<'_, AppState>,
+    job_service: State<'_, ClaudeJobService>,
 ) -> Result<AppSettings, String> {
-    state
+    let settings = state
         .settings_service
         .set_settings(settings)
         .map_err(|error| {
             log::warn!("set_app_settings failed: {}", error);
             error
-        })
+        })?;
+
+    if let Err(error) =
+        job_service.set_options(ClaudeJobQueueOptions::from_settings(&settings.claude))
+    {
+        log::warn!("Failed to apply Claude job queue settings: {}", error);
+    }
+    Ok(settings)
 }
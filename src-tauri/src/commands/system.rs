@@ -2,6 +2,9 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::process::Command;
 
+use crate::models::claude_diagnostics::ClaudeDiagnostics;
+use crate::services::ClaudeDiagnosticsService;
+
 fn is_allowed_url_scheme(url: &str) -> bool {
     url.starts_with("https://") || url.starts_with("http://") || url.starts_with("mailto:")
 }
@@ -219,3 +222,9 @@ pub fn open_project_in_editor(
     #[allow(unreachable_code)]
     Err("Unsupported platform for opening editor".to_string())
 }
+
+/// Where `claude` resolves, its version and common setup problems.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_claude_diagnostics() -> Result<ClaudeDiagnostics, String> {
+    Ok(ClaudeDiagnosticsService::diagnose())
+}
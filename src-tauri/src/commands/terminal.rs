@@ -8,6 +8,7 @@ use tauri::State;
 use crate::models::TerminalApp;
 use crate::utils::{
     detect_available_terminals, open_terminal_with_command, open_terminal_with_path,
+    resolve_shell_path,
 };
 use crate::AppState;
 
@@ -133,33 +134,6 @@ pub fn run_session_command(
     })
 }
 
-fn resolve_shell_path(shell: &str) -> Result<String, String> {
-    let normalized = shell.trim();
-
-    if normalized.is_empty() {
-        return Err("Shell cannot be empty".to_string());
-    }
-
-    if normalized.contains('/') {
-        if Path::new(normalized).exists() {
-            return Ok(normalized.to_string());
-        }
-
-        return Err(format!("Shell executable not found: {}", normalized));
-    }
-
-    let bundled_path = format!("/bin/{}", normalized);
-    if Path::new(&bundled_path).exists() {
-        return Ok(bundled_path);
-    }
-
-    if command_exists(normalized) {
-        return Ok(normalized.to_string());
-    }
-
-    Err(format!("Unsupported or missing shell: {}", normalized))
-}
-
 fn normalize_existing_directory_path(path: &str) -> Result<String, String> {
     let normalized = path.trim();
     if normalized.is_empty() {
@@ -177,16 +151,3 @@ fn normalize_existing_directory_path(path: &str) -> Result<String, String> {
 
     Ok(normalized.to_string())
 }
-
-fn command_exists(command: &str) -> bool {
-    let normalized = command.trim();
-    if normalized.is_empty() || normalized.contains(char::is_whitespace) {
-        return false;
-    }
-
-    Command::new("which")
-        .arg(normalized)
-        .status()
-        .map(|status| status.success())
-        .unwrap_or(false)
-}
@@ -6,8 +6,17 @@ mod models;
 mod services;
 mod utils;
 
-use services::pty_service::PtyManager;
-use services::{ProjectService, SessionService, SettingsService};
+use services::claude_job_service::{ClaudeJobQueueOptions, ClaudeJobService, TauriClaudeJobEvents};
+use services::claude_watch_service::{
+    ClaudeWatchService, DEFAULT_WATCH_DEBOUNCE, EVENT_CLAUDE_SESSIONS_CHANGED,
+};
+#[cfg(unix)]
+use services::pty_daemon::{PtyDaemon, PtyDaemonClient, DEFAULT_DAEMON_IDLE_TIMEOUT};
+use services::pty_service::{PtyEventSink, PtyManager, TauriPtyEvents};
+use services::pty_shutdown::PtyCloseOptions;
+use services::{ClaudeSessionService, ProjectService, SessionService, SettingsService};
+use std::sync::Arc;
+use tauri::{Emitter, Manager};
 
 #[cfg(target_os = "macos")]
 use services::StorageService;
@@ -16,8 +25,6 @@ use std::process::Command;
 
 #[cfg(target_os = "macos")]
 use tauri::menu::{Menu, MenuItem, MenuItemKind, PredefinedMenuItem};
-#[cfg(target_os = "macos")]
-use tauri::{Emitter, Manager};
 
 #[cfg(target_os = "macos")]
 const MENU_OPEN_SETTINGS_ID: &str = "open_settings";
@@ -122,11 +129,102 @@ fn build_app_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result
     Ok(menu)
 }
 
+fn start_claude_session_watcher<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(projects_dir) = ClaudeSessionService::claude_projects_dir() else {
+        log::warn!("Cannot determine home directory; Claude session watcher disabled");
+        return;
+    };
+
+    let emitter = app.clone();
+    let watcher = ClaudeWatchService::start(projects_dir, DEFAULT_WATCH_DEBOUNCE, move |change| {
+        if let Err(error) = emitter.emit(EVENT_CLAUDE_SESSIONS_CHANGED, change) {
+            log::debug!("Failed to emit Claude session change: {}", error);
+        }
+    });
+
+    match watcher {
+        Ok(watcher) => {
+            app.manage(watcher);
+        }
+        Err(error) => log::warn!("Claude session watcher disabled: {}", error),
+    }
+}
+
+fn create_pty_manager(app: &tauri::AppHandle) -> PtyManager {
+    let events: Arc<dyn PtyEventSink> = Arc::new(TauriPtyEvents::new(app.clone()));
+
+    #[cfg(unix)]
+    {
+        let use_daemon = app
+            .state::<AppState>()
+            .settings_service
+            .get_settings()
+            .map(|settings| settings.terminal.use_pty_daemon)
+            .unwrap_or(false);
+
+        if use_daemon {
+            let socket_path = PtyDaemon::socket_file();
+            match PtyDaemonClient::connect_or_spawn(&socket_path, Arc::clone(&events)) {
+                Ok(daemon) => return PtyManager::with_daemon(events, daemon),
+                Err(error) => log::warn!(
+                    "PTY daemon unavailable, running terminals in-process: {}",
+                    error
+                ),
+            }
+        }
+    }
+
+    PtyManager::new(events)
+}
+
+/// Resumes queued Claude jobs left over from the last run.
+fn start_claude_job_service(app: &tauri::AppHandle) {
+    let options = app
+        .state::<AppState>()
+        .settings_service
+        .get_settings()
+        .map(|settings| ClaudeJobQueueOptions::from_settings(&settings.claude))
+        .unwrap_or_default();
+    let events = Arc::new(TauriClaudeJobEvents::new(app.clone()));
+    app.manage(ClaudeJobService::new(events, options));
+}
+
+/// Gives embedded Claude sessions a chance to flush their transcripts before the app exits.
+fn shutdown_ptys(app: &tauri::AppHandle) {
+    let Some(pty_manager) = app.try_state::<PtyManager>() else {
+        return;
+    };
+    let options = app
+        .state::<AppState>()
+        .settings_service
+        .get_settings()
+        .map(|settings| PtyCloseOptions::from_settings(&settings.terminal))
+        .unwrap_or_default();
+
+    for outcome in pty_manager.shutdown(&options) {
+        log::info!(
+            "PTY closed on exit sid={} stage={:?} elapsed_ms={}",
+            outcome.session_id,
+            outcome.stage,
+            outcome.elapsed_ms
+        );
+    }
+}
+
 fn main() {
     let _ = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
         .format_timestamp_millis()
         .try_init();
 
+    #[cfg(unix)]
+    if let Some(socket_path) = PtyDaemon::socket_arg() {
+        if let Err(error) = PtyDaemon::serve(&socket_path, DEFAULT_DAEMON_IDLE_TIMEOUT) {
+            log::error!("{}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let builder = tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
@@ -136,7 +234,12 @@ fn main() {
             session_service: SessionService::new(),
             settings_service: SettingsService::new(),
         })
-        .manage(PtyManager::new());
+        .setup(|app| {
+            app.manage(create_pty_manager(app.handle()));
+            start_claude_job_service(app.handle());
+            start_claude_session_watcher(app.handle());
+            Ok(())
+        });
 
     #[cfg(target_os = "macos")]
     let builder =
@@ -179,21 +282,56 @@ fn main() {
             commands::terminal::run_session_command,
             commands::pty::create_pty,
             commands::pty::write_pty,
+            commands::pty::write_pty_bytes,
             commands::pty::resize_pty,
+            commands::pty::attach_pty,
+            commands::pty::subscribe_pty_bytes,
+            commands::pty::unsubscribe_pty_bytes,
             commands::pty::close_pty,
             commands::pty::close_all_ptys,
+            commands::pty::get_pty_processes,
+            commands::pty::list_pty_claude_sessions,
+            commands::pty::list_pty_recordings,
+            commands::pty::export_pty_recording,
+            commands::pty::replay_pty_recording,
             commands::claude_session::list_claude_sessions,
             commands::claude_session::list_claude_projects,
             commands::claude_session::rename_claude_session,
             commands::claude_session::delete_claude_session,
+            commands::claude_session::list_claude_trash,
+            commands::claude_session::restore_claude_session,
+            commands::claude_session::purge_claude_trash,
+            commands::claude_session::fork_claude_session,
+            commands::claude_session::get_claude_session_transcript,
+            commands::claude_session::search_claude_sessions,
+            commands::claude_session::export_claude_session,
+            commands::claude_session::get_claude_usage,
+            commands::claude_session::migrate_claude_project,
+            commands::claude_job::start_claude_job,
+            commands::claude_job::enqueue_claude_job,
+            commands::claude_job::reorder_claude_jobs,
+            commands::claude_job::set_claude_job_queue_paused,
+            commands::claude_job::get_claude_job_queue,
+            commands::claude_job::cancel_claude_job,
+            commands::claude_job::list_claude_jobs,
+            commands::claude_job::get_claude_job,
             commands::settings::get_app_settings,
             commands::settings::set_app_settings,
             commands::system::open_external_url,
             commands::system::get_available_editors,
             commands::system::open_project_in_editor,
+            commands::system::get_claude_diagnostics,
             commands::update::check_for_updates,
             commands::update::download_and_open_update,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                if let Some(job_service) = app.try_state::<ClaudeJobService>() {
+                    job_service.shutdown();
+                }
+                shutdown_ptys(app);
+            }
+        });
 }
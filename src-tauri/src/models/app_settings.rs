@@ -22,6 +22,31 @@ fn default_custom_startup_args() -> String {
     "--dangerously-skip-permissions".to_string()
 }
 
+fn default_trash_retention_days() -> u32 {
+    30
+}
+
+fn default_job_concurrency() -> u32 {
+    2
+}
+
+fn default_job_max_retries() -> u32 {
+    1
+}
+
+fn default_model_prices() -> Vec<ModelPrice> {
+    vec![
+        ModelPrice::new("claude-opus-4-5", 5.0, 25.0, 6.25, 0.5),
+        ModelPrice::new("claude-opus-4", 15.0, 75.0, 18.75, 1.5),
+        ModelPrice::new("claude-sonnet-4", 3.0, 15.0, 3.75, 0.3),
+        ModelPrice::new("claude-haiku-4-5", 1.0, 5.0, 1.25, 0.1),
+        ModelPrice::new("claude-3-7-sonnet", 3.0, 15.0, 3.75, 0.3),
+        ModelPrice::new("claude-3-5-sonnet", 3.0, 15.0, 3.75, 0.3),
+        ModelPrice::new("claude-3-5-haiku", 0.8, 4.0, 1.0, 0.08),
+        ModelPrice::new("claude-3-opus", 15.0, 75.0, 18.75, 1.5),
+    ]
+}
+
 fn default_sidebar_width() -> u32 {
     300
 }
@@ -55,6 +80,14 @@ fn default_terminal_scrollbar_width() -> u32 {
     6
 }
 
+fn default_terminal_close_signal() -> String {
+    "hangup".to_string()
+}
+
+fn default_terminal_close_grace_period_ms() -> u64 {
+    3000
+}
+
 fn default_external_terminal() -> String {
     "Terminal".to_string()
 }
@@ -73,6 +106,7 @@ pub struct AppSettings {
     pub integrations: IntegrationSettings,
     pub ui: UiSettings,
     pub sessions: SessionSettings,
+    pub terminal: TerminalSettings,
 }
 
 impl Default for AppSettings {
@@ -84,6 +118,7 @@ impl Default for AppSettings {
             integrations: IntegrationSettings::default(),
             ui: UiSettings::default(),
             sessions: SessionSettings::default(),
+            terminal: TerminalSettings::default(),
         }
     }
 }
@@ -234,6 +269,17 @@ pub struct ClaudeSettings {
     pub use_custom_startup_args: bool,
     #[serde(default = "default_custom_startup_args")]
     pub custom_startup_args: String,
+    #[serde(default = "default_model_prices")]
+    pub model_prices: Vec<ModelPrice>,
+    /// Days a deleted Claude session stays in the trash before it is purged
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    /// Queued Claude jobs that may run at once; jobs in the same project never overlap
+    #[serde(default = "default_job_concurrency")]
+    pub job_concurrency: u32,
+    /// Retries given to a queued job that fails
+    #[serde(default = "default_job_max_retries")]
+    pub job_max_retries: u32,
 }
 
 impl Default for ClaudeSettings {
@@ -241,6 +287,40 @@ impl Default for ClaudeSettings {
         Self {
             use_custom_startup_args: false,
             custom_startup_args: default_custom_startup_args(),
+            model_prices: default_model_prices(),
+            trash_retention_days: default_trash_retention_days(),
+            job_concurrency: default_job_concurrency(),
+            job_max_retries: default_job_max_retries(),
+        }
+    }
+}
+
+/// USD price per million tokens for models whose id contains `model_pattern`.
+/// The longest matching pattern wins, so specific versions can override a family.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ModelPrice {
+    pub model_pattern: String,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    pub cache_write_per_mtok: f64,
+    pub cache_read_per_mtok: f64,
+}
+
+impl ModelPrice {
+    fn new(
+        model_pattern: &str,
+        input_per_mtok: f64,
+        output_per_mtok: f64,
+        cache_write_per_mtok: f64,
+        cache_read_per_mtok: f64,
+    ) -> Self {
+        Self {
+            model_pattern: model_pattern.to_string(),
+            input_per_mtok,
+            output_per_mtok,
+            cache_write_per_mtok,
+            cache_read_per_mtok,
         }
     }
 }
@@ -323,6 +403,39 @@ pub struct ProjectTreeSettings {
     pub project_order: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalSettings {
+    /// Run embedded terminals in a background daemon so they survive app restarts.
+    /// Takes effect on the next launch.
+    #[serde(default)]
+    pub use_pty_daemon: bool,
+    /// Record embedded terminal sessions to asciicast files under the app data dir
+    #[serde(default)]
+    pub record_sessions: bool,
+    /// Load `<project root>/.env` into embedded terminals
+    #[serde(default)]
+    pub load_dotenv: bool,
+    /// First signal sent when a terminal is closed: "hangup" or "interrupt"
+    #[serde(default = "default_terminal_close_signal")]
+    pub close_signal: String,
+    /// Wait before escalating to SIGTERM, then SIGKILL
+    #[serde(default = "default_terminal_close_grace_period_ms")]
+    pub close_grace_period_ms: u64,
+}
+
+impl Default for TerminalSettings {
+    fn default() -> Self {
+        Self {
+            use_pty_daemon: false,
+            record_sessions: false,
+            load_dotenv: false,
+            close_signal: default_terminal_close_signal(),
+            close_grace_period_ms: default_terminal_close_grace_period_ms(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct LastOpenedSession {
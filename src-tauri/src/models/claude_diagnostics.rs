@@ -0,0 +1,57 @@
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaudeDiagnosticSeverity {
+    Warning,
+    Error,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaudeDiagnosticIssue {
+    /// Stable identifier such as `claude_not_found` or `nested_claudecode`
+    pub code: String,
+    pub severity: ClaudeDiagnosticSeverity,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaudeDirStatus {
+    pub path: String,
+    pub exists: bool,
+    pub writable: bool,
+}
+
+/// Result of running `command -v claude` in the login shell used by embedded terminals
+#[derive(Serialize, Clone, Debug)]
+pub struct LoginShellStatus {
+    pub shell: String,
+    pub exists: bool,
+    /// Started and exited successfully within the timeout
+    pub ok: bool,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub elapsed_ms: u64,
+    /// `claude` as resolved by the shell after its profile ran
+    pub claude_path: Option<String>,
+}
+
+/// Health of the Claude CLI installation as seen by the app
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaudeDiagnostics {
+    /// False when any issue is an error
+    pub ok: bool,
+    pub claude_path: Option<String>,
+    /// `claude_path` with symlinks resolved
+    pub claude_real_path: Option<String>,
+    pub claude_version: Option<String>,
+    /// The CLI is a Node script rather than a native binary
+    pub claude_requires_node: bool,
+    pub node_path: Option<String>,
+    pub node_version: Option<String>,
+    /// PATH given to embedded terminals and jobs, in lookup order
+    pub effective_path: Vec<String>,
+    pub claude_dir: ClaudeDirStatus,
+    pub login_shell: LoginShellStatus,
+    pub issues: Vec<ClaudeDiagnosticIssue>,
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::services::pty_service::ClaudeLaunchOptions;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaudeJobStatus {
+    /// Waiting in the queue
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl ClaudeJobStatus {
+    pub fn is_finished(self) -> bool {
+        !matches!(self, Self::Pending | Self::Running)
+    }
+}
+
+/// Token counts and cost reported in the final `result` event
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ClaudeJobUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+    pub total_cost_usd: Option<f64>,
+}
+
+/// A non-interactive `claude -p` run in a project directory. Queued jobs keep their
+/// place in the job list until they run.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClaudeJob {
+    pub id: String,
+    pub project_path: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub options: ClaudeLaunchOptions,
+    pub status: ClaudeJobStatus,
+    /// Runs started so far, including retries
+    #[serde(default)]
+    pub attempts: u32,
+    /// Failed runs are queued again until `attempts` exceeds this
+    #[serde(default)]
+    pub max_retries: u32,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// Transcript id reported by Claude once the run starts
+    pub claude_session_id: Option<String>,
+    /// Final answer text
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub usage: Option<ClaudeJobUsage>,
+    pub num_turns: Option<u32>,
+    pub duration_ms: Option<u64>,
+}
+
+impl ClaudeJob {
+    pub fn new(
+        project_path: String,
+        prompt: String,
+        options: ClaudeLaunchOptions,
+        max_retries: u32,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_path,
+            prompt,
+            options,
+            status: ClaudeJobStatus::Pending,
+            attempts: 0,
+            max_retries,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            started_at: None,
+            finished_at: None,
+            claude_session_id: None,
+            result: None,
+            error: None,
+            usage: None,
+            num_turns: None,
+            duration_ms: None,
+        }
+    }
+}
+
+/// Pending and running jobs in queue order
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaudeJobQueue {
+    pub paused: bool,
+    pub max_concurrent: usize,
+    pub jobs: Vec<ClaudeJob>,
+}
+
+/// Final `result` event of a stream-json run
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ClaudeJobOutcome {
+    pub is_error: bool,
+    /// `success`, `error_max_turns`, `error_during_execution`, ...
+    pub subtype: String,
+    pub result: Option<String>,
+    pub claude_session_id: Option<String>,
+    pub usage: ClaudeJobUsage,
+    pub num_turns: Option<u32>,
+    pub duration_ms: Option<u64>,
+}
+
+/// Typed progress parsed from `claude --output-format stream-json`
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClaudeJobProgress {
+    Started {
+        claude_session_id: String,
+        model: Option<String>,
+    },
+    AssistantText {
+        text: String,
+    },
+    ToolUse {
+        tool_use_id: String,
+        tool: String,
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        is_error: bool,
+        /// Text of the result, truncated for display
+        content: String,
+    },
+    Completed(ClaudeJobOutcome),
+}
+
+/// Payload of the `claude-job-progress` event
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaudeJobProgressEvent<'a> {
+    pub job_id: &'a str,
+    #[serde(flatten)]
+    pub progress: &'a ClaudeJobProgress,
+}
@@ -8,6 +8,12 @@ pub struct ClaudeSession {
     pub summary: String,
     pub first_prompt: String,
     pub message_count: u32,
+    #[serde(default)]
+    pub user_message_count: u32,
+    #[serde(default)]
+    pub assistant_message_count: u32,
+    #[serde(default)]
+    pub tool_message_count: u32,
     pub created: String,
     pub modified: String,
     pub git_branch: String,
@@ -55,6 +61,7 @@ pub struct ClaudeSessionsIndexEntry {
 pub struct JsonlEntry {
     #[serde(rename = "type")]
     pub entry_type: Option<String>,
+    pub uuid: Option<String>,
     #[serde(rename = "sessionId")]
     #[allow(dead_code)]
     pub session_id: Option<String>,
@@ -68,7 +75,132 @@ pub struct JsonlEntry {
 
 #[derive(Deserialize, Debug)]
 pub struct JsonlMessage {
+    pub id: Option<String>,
     #[allow(dead_code)]
     pub role: Option<String>,
+    pub model: Option<String>,
     pub content: Option<serde_json::Value>,
+    pub usage: Option<JsonlUsage>,
+}
+
+/// Token usage reported on assistant messages
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+pub struct JsonlUsage {
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    pub cache_creation_input_tokens: Option<u64>,
+    pub cache_read_input_tokens: Option<u64>,
+}
+
+/// A tool invocation requested by the assistant
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClaudeToolUse {
+    pub id: String,
+    pub name: String,
+    pub input: serde_json::Value,
+}
+
+/// The output of a tool invocation, reported back in a user turn
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClaudeToolResult {
+    pub tool_use_id: String,
+    pub content: String,
+    pub is_error: bool,
+}
+
+/// One user or assistant message of a session transcript
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClaudeTranscriptTurn {
+    pub message_index: u32,
+    pub uuid: String,
+    pub role: String,
+    pub timestamp: String,
+    pub is_sidechain: bool,
+    /// Git branch the session was on when the message was written
+    pub git_branch: String,
+    pub text: String,
+    pub tool_uses: Vec<ClaudeToolUse>,
+    pub tool_results: Vec<ClaudeToolResult>,
+}
+
+/// Resume position inside a transcript JSONL file
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClaudeTranscriptCursor {
+    pub byte_offset: u64,
+    pub message_index: u32,
+}
+
+/// A page of transcript turns; `next_cursor` is `None` once the file is exhausted
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaudeTranscriptPage {
+    pub session_id: String,
+    pub turns: Vec<ClaudeTranscriptTurn>,
+    pub next_cursor: Option<ClaudeTranscriptCursor>,
+}
+
+/// A single transcript message matching a full-text search query
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaudeSearchHit {
+    pub session_id: String,
+    pub project_path: String,
+    pub message_index: u32,
+    pub role: String,
+    pub timestamp: String,
+    pub snippet: String,
+    pub score: u32,
+}
+
+/// Outcome (or dry-run preview) of moving Claude history to a new project path
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaudeProjectMigration {
+    pub old_project_path: String,
+    pub new_project_path: String,
+    pub source_dir: String,
+    pub target_dir: String,
+    /// True when the target dir already held sessions and the two are merged
+    pub merged: bool,
+    pub dry_run: bool,
+    pub session_ids: Vec<String>,
+    /// Files present in both dirs; a migration with conflicts is refused
+    pub conflicts: Vec<String>,
+    pub rewritten_cwd_count: u32,
+}
+
+/// A deleted session kept in the app's trash folder until restored or purged
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClaudeTrashEntry {
+    pub trash_id: String,
+    pub session_id: String,
+    pub project_path: String,
+    pub original_file_path: String,
+    pub deleted_at: String,
+    pub summary: String,
+    pub first_prompt: String,
+    pub message_count: u32,
+    /// The sessions-index.json entry removed on delete, re-added on restore
+    #[serde(default)]
+    pub index_entry: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaudeSessionChangeKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClaudeSessionFileChange {
+    pub session_id: String,
+    pub kind: ClaudeSessionChangeKind,
+}
+
+/// Debounced batch of filesystem changes inside one Claude project dir
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClaudeProjectChange {
+    pub project_dir_name: String,
+    pub project_path: String,
+    pub sessions: Vec<ClaudeSessionFileChange>,
+    pub index_changed: bool,
 }
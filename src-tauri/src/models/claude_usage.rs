@@ -0,0 +1,43 @@
+use serde::Serialize;
+
+/// Token counts and estimated cost for a group of assistant requests
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ClaudeUsageTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+    pub request_count: u32,
+    pub estimated_cost_usd: f64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaudeSessionUsage {
+    pub session_id: String,
+    pub project_path: String,
+    pub totals: ClaudeUsageTotals,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaudeProjectUsage {
+    pub project_path: String,
+    pub totals: ClaudeUsageTotals,
+}
+
+/// Usage bucketed by UTC calendar day (`YYYY-MM-DD`)
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaudeDailyUsage {
+    pub date: String,
+    pub totals: ClaudeUsageTotals,
+}
+
+/// Aggregated token usage across Claude Code sessions
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ClaudeUsageReport {
+    pub total: ClaudeUsageTotals,
+    pub sessions: Vec<ClaudeSessionUsage>,
+    pub projects: Vec<ClaudeProjectUsage>,
+    pub days: Vec<ClaudeDailyUsage>,
+    /// Models seen in the transcripts that have no entry in the price table
+    pub unpriced_models: Vec<String>,
+}
@@ -1,6 +1,10 @@
 pub mod app_settings;
+pub mod claude_diagnostics;
+pub mod claude_job;
 pub mod claude_session;
+pub mod claude_usage;
 pub mod project;
+pub mod pty_recording;
 pub mod session;
 pub mod shell;
 pub mod terminal;
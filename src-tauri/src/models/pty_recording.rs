@@ -0,0 +1,15 @@
+use serde::Serialize;
+
+/// A recorded PTY session stored as an asciicast v2 `.cast` file
+#[derive(Serialize, Clone, Debug)]
+pub struct PtyRecording {
+    /// File stem, used to address the recording in commands
+    pub id: String,
+    pub session_id: String,
+    pub file_path: String,
+    pub started_at: String,
+    pub duration_secs: f64,
+    pub width: u16,
+    pub height: u16,
+    pub size_bytes: u64,
+}
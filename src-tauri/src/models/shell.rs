@@ -34,6 +34,12 @@ impl ShellType {
             _ => None,
         }
     }
+
+    /// Shell type of an executable path such as `/usr/local/bin/fish` or a login `-zsh`.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let name = std::path::Path::new(path.trim()).file_name()?.to_str()?;
+        Self::from_str(name.trim_start_matches('-'))
+    }
 }
 
 impl fmt::Display for ShellType {
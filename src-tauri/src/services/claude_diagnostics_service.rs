@@ -0,0 +1,525 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::models::claude_diagnostics::{
+    ClaudeDiagnosticIssue, ClaudeDiagnosticSeverity, ClaudeDiagnostics, ClaudeDirStatus,
+    LoginShellStatus,
+};
+use crate::models::shell::ShellType;
+use crate::services::pty_service::{
+    build_runtime_path, default_shell, find_in_path, shell_launch_args,
+};
+
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+/// Profiles that run `nvm` or `conda` hooks can take a few seconds.
+const LOGIN_SHELL_TIMEOUT: Duration = Duration::from_secs(8);
+
+pub struct ClaudeDiagnosticsService;
+
+/// The parts of the process environment the checks depend on.
+struct DiagnosticsEnv {
+    /// Already extended with `build_runtime_path`
+    path: String,
+    home: Option<PathBuf>,
+    shell: String,
+    claudecode: Option<String>,
+    claude_config_dir: Option<String>,
+}
+
+impl DiagnosticsEnv {
+    fn current() -> Self {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        Self {
+            path: build_runtime_path(&std::env::var("PATH").unwrap_or_default()),
+            home: dirs::home_dir(),
+            shell: default_shell(),
+            claudecode: var("CLAUDECODE"),
+            claude_config_dir: var("CLAUDE_CONFIG_DIR"),
+        }
+    }
+}
+
+struct CommandOutput {
+    status: ExitStatus,
+    stdout: String,
+    stderr: String,
+}
+
+impl ClaudeDiagnosticsService {
+    /// Resolves `claude` the way embedded terminals and jobs do and checks what commonly
+    /// keeps it from starting. Spawns processes, so it can take several seconds.
+    pub fn diagnose() -> ClaudeDiagnostics {
+        Self::diagnose_env(&DiagnosticsEnv::current())
+    }
+
+    fn diagnose_env(env: &DiagnosticsEnv) -> ClaudeDiagnostics {
+        let mut issues = Vec::new();
+        let mut issue = |code: &str, severity, message: String| {
+            issues.push(ClaudeDiagnosticIssue {
+                code: code.to_string(),
+                severity,
+                message,
+            })
+        };
+
+        let claude_path = find_in_path("claude", &env.path);
+        let claude_real_path = claude_path
+            .as_deref()
+            .and_then(|path| fs::canonicalize(path).ok());
+        let claude_requires_node = claude_real_path
+            .as_deref()
+            .or(claude_path.as_deref())
+            .is_some_and(is_node_script);
+        let node_path = find_in_path("node", &env.path);
+
+        let claude_version = match &claude_path {
+            None => {
+                issue(
+                    "claude_not_found",
+                    ClaudeDiagnosticSeverity::Error,
+                    "`claude` was not found on the PATH or in the usual install locations"
+                        .to_string(),
+                );
+                None
+            }
+            Some(path) => match probe_version(path, env) {
+                Ok(version) => Some(version),
+                Err(error) => {
+                    issue(
+                        "claude_version_failed",
+                        ClaudeDiagnosticSeverity::Error,
+                        format!("`{} --version` failed: {}", path.display(), error),
+                    );
+                    None
+                }
+            },
+        };
+        let node_version = node_path
+            .as_deref()
+            .and_then(|path| probe_version(path, env).ok());
+
+        if claude_requires_node && node_path.is_none() {
+            issue(
+                "node_missing",
+                ClaudeDiagnosticSeverity::Error,
+                "`claude` is a Node.js script but `node` was not found on the PATH".to_string(),
+            );
+        }
+
+        if let Some(value) = env.claudecode.as_deref().filter(|value| *value != "0") {
+            issue(
+                "nested_claudecode",
+                ClaudeDiagnosticSeverity::Warning,
+                format!(
+                    "The app was started from inside Claude Code (CLAUDECODE={}). Embedded \
+                     terminals and jobs reset it, but external terminals opened by the app \
+                     inherit it and Claude refuses to start nested sessions",
+                    value
+                ),
+            );
+        }
+
+        let default_claude_dir = env.home.as_ref().map(|home| home.join(".claude"));
+        if let Some(config_dir) = env.claude_config_dir.as_deref() {
+            if default_claude_dir.as_deref() != Some(Path::new(config_dir)) {
+                issue(
+                    "custom_claude_config_dir",
+                    ClaudeDiagnosticSeverity::Warning,
+                    format!(
+                        "CLAUDE_CONFIG_DIR points to {}; the app only reads sessions from ~/.claude",
+                        config_dir
+                    ),
+                );
+            }
+        }
+
+        let claude_dir = claude_dir_status(default_claude_dir);
+        if !claude_dir.exists {
+            issue(
+                "claude_dir_missing",
+                ClaudeDiagnosticSeverity::Warning,
+                format!(
+                    "{} does not exist; run `claude` once to create it",
+                    claude_dir.path
+                ),
+            );
+        } else if !claude_dir.writable {
+            issue(
+                "claude_dir_not_writable",
+                ClaudeDiagnosticSeverity::Error,
+                format!(
+                    "{} is not writable; Claude cannot save sessions",
+                    claude_dir.path
+                ),
+            );
+        }
+
+        let login_shell = probe_login_shell(env);
+        if !login_shell.exists {
+            issue(
+                "login_shell_missing",
+                ClaudeDiagnosticSeverity::Error,
+                format!("Login shell {} does not exist", login_shell.shell),
+            );
+        } else if !login_shell.ok {
+            issue(
+                "login_shell_failed",
+                ClaudeDiagnosticSeverity::Warning,
+                if login_shell.timed_out {
+                    format!(
+                        "{} did not finish starting within {}s; check your shell profile",
+                        login_shell.shell,
+                        LOGIN_SHELL_TIMEOUT.as_secs()
+                    )
+                } else {
+                    format!(
+                        "{} exited with code {:?} while loading your shell profile",
+                        login_shell.shell, login_shell.exit_code
+                    )
+                },
+            );
+        } else {
+            match (&login_shell.claude_path, &claude_path) {
+                (None, Some(_)) => issue(
+                    "claude_missing_in_login_shell",
+                    ClaudeDiagnosticSeverity::Warning,
+                    format!(
+                        "`claude` does not resolve in {}; embedded terminals fail to start it \
+                         if your shell profile replaces PATH",
+                        login_shell.shell
+                    ),
+                ),
+                (Some(shell_claude), Some(app_claude))
+                    if fs::canonicalize(shell_claude).ok().as_deref()
+                        != claude_real_path.as_deref() =>
+                {
+                    issue(
+                        "claude_path_mismatch",
+                        ClaudeDiagnosticSeverity::Warning,
+                        format!(
+                            "The login shell runs {} but jobs run {}; more than one Claude \
+                             install is on the PATH",
+                            shell_claude,
+                            app_claude.display()
+                        ),
+                    )
+                }
+                _ => {}
+            }
+        }
+
+        ClaudeDiagnostics {
+            ok: !issues
+                .iter()
+                .any(|issue| issue.severity == ClaudeDiagnosticSeverity::Error),
+            claude_path: claude_path.map(|path| path.display().to_string()),
+            claude_real_path: claude_real_path.map(|path| path.display().to_string()),
+            claude_version,
+            claude_requires_node,
+            node_path: node_path.map(|path| path.display().to_string()),
+            node_version,
+            effective_path: env
+                .path
+                .split(':')
+                .filter(|dir| !dir.is_empty())
+                .map(str::to_string)
+                .collect(),
+            claude_dir,
+            login_shell,
+            issues,
+        }
+    }
+}
+
+/// A `#!` script run by node, as npm installs the CLI; the native installer ships a binary.
+fn is_node_script(path: &Path) -> bool {
+    let mut head = [0u8; 128];
+    let Ok(read) = fs::File::open(path).and_then(|mut file| file.read(&mut head)) else {
+        return false;
+    };
+    let head = String::from_utf8_lossy(&head[..read]);
+    head.lines()
+        .next()
+        .is_some_and(|line| line.starts_with("#!") && line.contains("node"))
+}
+
+/// First line of `<program> --version`.
+fn probe_version(program: &Path, env: &DiagnosticsEnv) -> Result<String, String> {
+    let mut command = Command::new(program);
+    command.arg("--version").env("PATH", &env.path);
+    if let Some(home) = &env.home {
+        command.current_dir(home);
+    }
+
+    let output = run_with_timeout(command, VERSION_TIMEOUT)?
+        .ok_or_else(|| format!("no answer within {}s", VERSION_TIMEOUT.as_secs()))?;
+    let first_line = |text: &str| {
+        text.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(str::to_string)
+    };
+
+    if !output.status.success() {
+        return Err(first_line(&output.stderr).unwrap_or_else(|| output.status.to_string()));
+    }
+    first_line(&output.stdout).ok_or_else(|| "no version printed".to_string())
+}
+
+fn claude_dir_status(path: Option<PathBuf>) -> ClaudeDirStatus {
+    let Some(path) = path else {
+        return ClaudeDirStatus {
+            path: "~/.claude".to_string(),
+            exists: false,
+            writable: false,
+        };
+    };
+
+    let exists = path.is_dir();
+    // Permission bits do not account for ACLs or read-only mounts, so try a write.
+    let writable = exists && {
+        let probe = path.join(format!(".ccsm-write-test-{}", uuid::Uuid::new_v4()));
+        let created = fs::write(&probe, b"").is_ok();
+        let _ = fs::remove_file(&probe);
+        created
+    };
+
+    ClaudeDirStatus {
+        path: path.display().to_string(),
+        exists,
+        writable,
+    }
+}
+
+/// Starts the shell the way embedded terminals do and asks it where `claude` is.
+fn probe_login_shell(env: &DiagnosticsEnv) -> LoginShellStatus {
+    let mut status = LoginShellStatus {
+        shell: env.shell.clone(),
+        exists: Path::new(&env.shell).is_file()
+            || (!env.shell.contains('/') && find_in_path(&env.shell, &env.path).is_some()),
+        ok: false,
+        exit_code: None,
+        timed_out: false,
+        elapsed_ms: 0,
+        claude_path: None,
+    };
+    if !status.exists {
+        return status;
+    }
+
+    let shell_type = ShellType::from_path(&env.shell).unwrap_or(ShellType::Sh);
+    let script = match shell_type {
+        ShellType::Tcsh => "which claude",
+        _ => "command -v claude",
+    };
+    let mut command = Command::new(&env.shell);
+    command
+        .args(shell_launch_args(
+            &shell_type,
+            &[],
+            Some(script.to_string()),
+        ))
+        .env("PATH", &env.path)
+        .env("CLAUDECODE", "0");
+    if let Some(home) = &env.home {
+        command.env("HOME", home).current_dir(home);
+    }
+
+    let started = Instant::now();
+    let output = run_with_timeout(command, LOGIN_SHELL_TIMEOUT);
+    status.elapsed_ms = started.elapsed().as_millis() as u64;
+
+    match output {
+        Ok(Some(output)) => {
+            status.exit_code = output.status.code();
+            // Profiles may print banners; the answer is the last absolute path printed.
+            status.claude_path = output
+                .stdout
+                .lines()
+                .map(str::trim)
+                .rev()
+                .find(|line| line.starts_with('/'))
+                .map(str::to_string);
+            // `command -v` exits with 1 when claude is missing; the shell itself still worked.
+            status.ok = output.status.success()
+                || (output.status.code() == Some(1) && status.claude_path.is_none());
+            if !output.status.success() && !output.stderr.trim().is_empty() {
+                log::debug!("Login shell probe stderr: {}", output.stderr.trim());
+            }
+        }
+        Ok(None) => status.timed_out = true,
+        Err(error) => log::warn!("Failed to start login shell {}: {}", env.shell, error),
+    }
+
+    status
+}
+
+/// Runs `command` with captured output. `Ok(None)` means it was killed after `timeout`.
+fn run_with_timeout(
+    mut command: Command,
+    timeout: Duration,
+) -> Result<Option<CommandOutput>, String> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // Its own process group, so a timeout also stops whatever a shell profile spawned.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let child = command.spawn().map_err(|e| e.to_string())?;
+    let pid = child.id();
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(child.wait_with_output());
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(output) => {
+            let output = output.map_err(|e| e.to_string())?;
+            Ok(Some(CommandOutput {
+                status: output.status,
+                stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            }))
+        }
+        Err(_) => {
+            #[cfg(unix)]
+            if let Ok(pgid) = i32::try_from(pid) {
+                // SAFETY: plain syscall; the waiting thread reaps the child.
+                unsafe {
+                    libc::killpg(pgid, libc::SIGKILL);
+                }
+            }
+            #[cfg(not(unix))]
+            let _ = pid;
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{ClaudeDiagnosticsService, DiagnosticsEnv};
+    use crate::services::storage_service::unique_test_data_dir;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    fn write_executable(path: &Path, content: &str) {
+        fs::write(path, content).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    fn issue_codes(env: &DiagnosticsEnv) -> (super::ClaudeDiagnostics, Vec<String>) {
+        let report = ClaudeDiagnosticsService::diagnose_env(env);
+        let codes = report
+            .issues
+            .iter()
+            .map(|issue| issue.code.clone())
+            .collect();
+        (report, codes)
+    }
+
+    #[test]
+    fn healthy_install_reports_version_and_login_shell_path() {
+        let dir = unique_test_data_dir("diagnostics-healthy");
+        let bin = dir.join("bin");
+        let home = dir.join("home");
+        fs::create_dir_all(&bin).unwrap();
+        fs::create_dir_all(home.join(".claude")).unwrap();
+        write_executable(
+            &bin.join("claude"),
+            "#!/bin/sh\necho\necho '2.0.14 (Claude Code)'\n",
+        );
+        // A login sh rebuilds PATH from /etc/profile, then reads ~/.profile.
+        fs::write(
+            home.join(".profile"),
+            format!("PATH={}:$PATH\n", bin.display()),
+        )
+        .unwrap();
+
+        let env = DiagnosticsEnv {
+            path: format!("{}:/usr/bin:/bin", bin.display()),
+            home: Some(home.clone()),
+            shell: "/bin/sh".to_string(),
+            claudecode: Some("1".to_string()),
+            claude_config_dir: None,
+        };
+        let (report, codes) = issue_codes(&env);
+
+        assert!(report.ok, "{:?}", report.issues);
+        assert_eq!(codes, ["nested_claudecode"]);
+        assert_eq!(
+            report.claude_version.as_deref(),
+            Some("2.0.14 (Claude Code)")
+        );
+        assert!(!report.claude_requires_node);
+        assert_eq!(report.effective_path[0], bin.display().to_string());
+        assert!(report.claude_dir.exists && report.claude_dir.writable);
+        assert_eq!(fs::read_dir(home.join(".claude")).unwrap().count(), 0);
+        assert!(report.login_shell.ok);
+        assert_eq!(
+            report.login_shell.claude_path,
+            Some(bin.join("claude").display().to_string())
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn misconfigurations_are_reported_as_issues() {
+        let dir = unique_test_data_dir("diagnostics-broken");
+        let bin = dir.join("bin");
+        let home = dir.join("home");
+        fs::create_dir_all(&bin).unwrap();
+        fs::create_dir_all(&home).unwrap();
+        // An npm install whose node is not on the PATH.
+        write_executable(
+            &bin.join("claude"),
+            "#!/usr/bin/env node\nconsole.log('2.0.14')\n",
+        );
+
+        let env = DiagnosticsEnv {
+            path: bin.display().to_string(),
+            home: Some(home.clone()),
+            shell: dir.join("missing-shell").display().to_string(),
+            claudecode: Some("0".to_string()),
+            claude_config_dir: Some(dir.join("config").display().to_string()),
+        };
+        let (report, codes) = issue_codes(&env);
+
+        assert!(!report.ok);
+        assert!(report.claude_requires_node);
+        assert_eq!(
+            codes,
+            [
+                "claude_version_failed",
+                "node_missing",
+                "custom_claude_config_dir",
+                "claude_dir_missing",
+                "login_shell_missing",
+            ]
+        );
+
+        fs::remove_file(bin.join("claude")).unwrap();
+        let (report, codes) = issue_codes(&env);
+        assert_eq!(report.claude_path, None);
+        assert_eq!(codes[0], "claude_not_found");
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
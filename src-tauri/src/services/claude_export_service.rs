@@ -0,0 +1,453 @@
+use crate::models::claude_session::{ClaudeTranscriptCursor, ClaudeTranscriptTurn};
+use crate::services::ClaudeSessionService;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const EXPORT_PAGE_SIZE: usize = 1000;
+const MAX_TITLE_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaudeExportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+struct ClaudeSessionExport<'a> {
+    title: &'a str,
+    session_id: &'a str,
+    project_path: &'a str,
+    exported_at: &'a str,
+    turns: &'a [ClaudeTranscriptTurn],
+}
+
+pub struct ClaudeExportService;
+
+impl ClaudeExportService {
+    /// Render a Claude session transcript and write it to `output_path`.
+    /// Returns the path of the written file.
+    pub fn export_session(
+        project_path: &str,
+        session_id: &str,
+        format: ClaudeExportFormat,
+        output_path: &str,
+    ) -> Result<String, String> {
+        let normalized_output_path = output_path.trim();
+        if normalized_output_path.is_empty() {
+            return Err("Export path cannot be empty".to_string());
+        }
+
+        let output_path = PathBuf::from(normalized_output_path);
+        if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            if !parent.is_dir() {
+                return Err(format!(
+                    "Export directory does not exist: {}",
+                    parent.display()
+                ));
+            }
+        }
+
+        let jsonl_path = ClaudeSessionService::session_file_path(project_path, session_id)?;
+        let turns = Self::read_all_turns(&jsonl_path, session_id.trim())?;
+
+        let rendered = Self::render(
+            format,
+            project_path.trim(),
+            session_id.trim(),
+            &turns,
+            &chrono::Utc::now().to_rfc3339(),
+        )?;
+
+        fs::write(&output_path, rendered)
+            .map_err(|e| format!("Failed to write export file: {}", e))?;
+
+        Ok(output_path.display().to_string())
+    }
+
+    fn read_all_turns(
+        jsonl_path: &Path,
+        session_id: &str,
+    ) -> Result<Vec<ClaudeTranscriptTurn>, String> {
+        let mut turns = Vec::new();
+        let mut cursor = ClaudeTranscriptCursor::default();
+
+        loop {
+            let page = ClaudeSessionService::read_transcript_page(
+                jsonl_path,
+                session_id,
+                cursor,
+                Some(EXPORT_PAGE_SIZE),
+            )?;
+            turns.extend(page.turns);
+
+            match page.next_cursor {
+                Some(next_cursor) => cursor = next_cursor,
+                None => break,
+            }
+        }
+
+        Ok(turns)
+    }
+
+    fn render(
+        format: ClaudeExportFormat,
+        project_path: &str,
+        session_id: &str,
+        turns: &[ClaudeTranscriptTurn],
+        exported_at: &str,
+    ) -> Result<String, String> {
+        let title = Self::session_title(session_id, turns);
+
+        match format {
+            ClaudeExportFormat::Markdown => Ok(Self::render_markdown(
+                &title,
+                project_path,
+                session_id,
+                turns,
+                exported_at,
+            )),
+            ClaudeExportFormat::Html => Ok(Self::render_html(
+                &title,
+                project_path,
+                session_id,
+                turns,
+                exported_at,
+            )),
+            ClaudeExportFormat::Json => serde_json::to_string_pretty(&ClaudeSessionExport {
+                title: &title,
+                session_id,
+                project_path,
+                exported_at,
+                turns,
+            })
+            .map_err(|e| format!("Failed to serialize session export: {}", e)),
+        }
+    }
+
+    fn session_title(session_id: &str, turns: &[ClaudeTranscriptTurn]) -> String {
+        turns
+            .iter()
+            .filter(|turn| turn.role == "user" && !turn.is_sidechain)
+            .map(|turn| ClaudeSessionService::sanitize_session_label(&turn.text))
+            .find(|label| !label.is_empty())
+            .map(|label| label.chars().take(MAX_TITLE_CHARS).collect())
+            .unwrap_or_else(|| format!("Claude session {}", session_id))
+    }
+
+    fn role_heading(turn: &ClaudeTranscriptTurn) -> String {
+        let role = if turn.role == "assistant" {
+            "Assistant"
+        } else {
+            "User"
+        };
+
+        if turn.is_sidechain {
+            format!("{} (sidechain)", role)
+        } else {
+            role.to_string()
+        }
+    }
+
+    fn pretty_json(value: &serde_json::Value) -> String {
+        serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+    }
+
+    /// A backtick fence longer than any backtick run inside `content`.
+    fn markdown_fence(content: &str) -> String {
+        let mut longest_run = 0;
+        let mut current_run = 0;
+        for ch in content.chars() {
+            if ch == '`' {
+                current_run += 1;
+                longest_run = longest_run.max(current_run);
+            } else {
+                current_run = 0;
+            }
+        }
+
+        "`".repeat((longest_run + 1).max(3))
+    }
+
+    fn render_markdown(
+        title: &str,
+        project_path: &str,
+        session_id: &str,
+        turns: &[ClaudeTranscriptTurn],
+        exported_at: &str,
+    ) -> String {
+        let mut output = format!(
+            "# {}\n\n- Session: `{}`\n- Project: `{}`\n- Exported: {}\n",
+            title, session_id, project_path, exported_at
+        );
+
+        for turn in turns {
+            output.push_str(&format!("\n---\n\n## {}", Self::role_heading(turn)));
+            if !turn.timestamp.is_empty() {
+                output.push_str(&format!(" · {}", turn.timestamp));
+            }
+            output.push_str("\n\n");
+
+            if !turn.text.trim().is_empty() {
+                output.push_str(turn.text.trim_end());
+                output.push_str("\n\n");
+            }
+
+            for tool_use in &turn.tool_uses {
+                let input = Self::pretty_json(&tool_use.input);
+                let fence = Self::markdown_fence(&input);
+                output.push_str(&format!(
+                    "<details>\n<summary>Tool call: {}</summary>\n\n{}json\n{}\n{}\n\n</details>\n\n",
+                    tool_use.name, fence, input, fence
+                ));
+            }
+
+            for tool_result in &turn.tool_results {
+                let fence = Self::markdown_fence(&tool_result.content);
+                let label = if tool_result.is_error {
+                    "Tool error"
+                } else {
+                    "Tool result"
+                };
+                output.push_str(&format!(
+                    "<details>\n<summary>{}</summary>\n\n{}\n{}\n{}\n\n</details>\n\n",
+                    label,
+                    fence,
+                    tool_result.content.trim_end(),
+                    fence
+                ));
+            }
+        }
+
+        output
+    }
+
+    fn escape_html(raw: &str) -> String {
+        raw.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&#39;")
+    }
+
+    /// Escape message text, turning fenced code blocks into `<pre><code>`.
+    fn render_html_text(text: &str) -> String {
+        let mut output = String::new();
+        let mut paragraph = Vec::new();
+        let mut code_block: Option<Vec<&str>> = None;
+
+        for line in text.lines() {
+            match code_block.as_mut() {
+                Some(code_lines) => {
+                    if line.trim_start().starts_with("```") {
+                        output.push_str(&format!(
+                            "<pre><code>{}</code></pre>\n",
+                            Self::escape_html(&code_lines.join("\n"))
+                        ));
+                        code_block = None;
+                    } else {
+                        code_lines.push(line);
+                    }
+                }
+                None => {
+                    if line.trim_start().starts_with("```") {
+                        Self::flush_html_paragraph(&mut output, &mut paragraph);
+                        code_block = Some(Vec::new());
+                    } else {
+                        paragraph.push(line);
+                    }
+                }
+            }
+        }
+
+        if let Some(code_lines) = code_block {
+            output.push_str(&format!(
+                "<pre><code>{}</code></pre>\n",
+                Self::escape_html(&code_lines.join("\n"))
+            ));
+        }
+        Self::flush_html_paragraph(&mut output, &mut paragraph);
+
+        output
+    }
+
+    fn flush_html_paragraph(output: &mut String, paragraph: &mut Vec<&str>) {
+        let joined = paragraph.join("\n");
+        if !joined.trim().is_empty() {
+            output.push_str(&format!(
+                "<p>{}</p>\n",
+                Self::escape_html(joined.trim_matches('\n'))
+            ));
+        }
+        paragraph.clear();
+    }
+
+    fn render_html(
+        title: &str,
+        project_path: &str,
+        session_id: &str,
+        turns: &[ClaudeTranscriptTurn],
+        exported_at: &str,
+    ) -> String {
+        let mut body = String::new();
+
+        for turn in turns {
+            body.push_str(&format!(
+                "<section class=\"turn {}\">\n<h2>{}<span class=\"timestamp\">{}</span></h2>\n",
+                Self::escape_html(&turn.role),
+                Self::escape_html(&Self::role_heading(turn)),
+                Self::escape_html(&turn.timestamp)
+            ));
+            body.push_str(&Self::render_html_text(&turn.text));
+
+            for tool_use in &turn.tool_uses {
+                body.push_str(&format!(
+                    "<details><summary>Tool call: {}</summary><pre><code>{}</code></pre></details>\n",
+                    Self::escape_html(&tool_use.name),
+                    Self::escape_html(&Self::pretty_json(&tool_use.input))
+                ));
+            }
+
+            for tool_result in &turn.tool_results {
+                body.push_str(&format!(
+                    "<details><summary>{}</summary><pre><code>{}</code></pre></details>\n",
+                    if tool_result.is_error {
+                        "Tool error"
+                    } else {
+                        "Tool result"
+                    },
+                    Self::escape_html(&tool_result.content)
+                ));
+            }
+
+            body.push_str("</section>\n");
+        }
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: -apple-system, "Segoe UI", sans-serif; max-width: 860px; margin: 2rem auto; padding: 0 1rem; color: #24292f; }}
+header p {{ color: #57606a; margin: 0.2rem 0; }}
+.turn {{ border-top: 1px solid #d0d7de; padding: 0.8rem 0; }}
+.turn.assistant h2 {{ color: #6b87d6; }}
+h2 {{ font-size: 1rem; }}
+.timestamp {{ color: #8c959f; font-weight: normal; margin-left: 0.6rem; }}
+p {{ white-space: pre-wrap; }}
+pre {{ background: #f6f8fa; padding: 0.8rem; overflow-x: auto; border-radius: 6px; }}
+details {{ margin: 0.4rem 0; }}
+summary {{ cursor: pointer; color: #57606a; }}
+</style>
+</head>
+<body>
+<header>
+<h1>{title}</h1>
+<p>Session: <code>{session_id}</code></p>
+<p>Project: <code>{project_path}</code></p>
+<p>Exported: {exported_at}</p>
+</header>
+{body}</body>
+</html>
+"#,
+            title = Self::escape_html(title),
+            session_id = Self::escape_html(session_id),
+            project_path = Self::escape_html(project_path),
+            exported_at = Self::escape_html(exported_at),
+            body = body
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClaudeExportFormat, ClaudeExportService};
+    use crate::models::claude_session::{ClaudeToolUse, ClaudeTranscriptTurn};
+
+    fn turn(role: &str, text: &str, tool_uses: Vec<ClaudeToolUse>) -> ClaudeTranscriptTurn {
+        ClaudeTranscriptTurn {
+            message_index: 0,
+            uuid: String::new(),
+            role: role.to_string(),
+            timestamp: "2026-04-07T01:00:00Z".to_string(),
+            is_sidechain: false,
+            git_branch: String::new(),
+            text: text.to_string(),
+            tool_uses,
+            tool_results: Vec::new(),
+        }
+    }
+
+    fn sample_turns() -> Vec<ClaudeTranscriptTurn> {
+        vec![
+            turn(
+                "user",
+                "<conversation_history>\nold\n</conversation_history>\n\nFix the <Login> form",
+                Vec::new(),
+            ),
+            turn(
+                "assistant",
+                "Patched it:\n```rust\nfn main() {}\n```",
+                vec![ClaudeToolUse {
+                    id: "toolu_1".to_string(),
+                    name: "Edit".to_string(),
+                    input: serde_json::json!({ "file_path": "src/login.tsx" }),
+                }],
+            ),
+        ]
+    }
+
+    #[test]
+    fn render_markdown_keeps_code_blocks_and_collapses_tool_calls() {
+        let rendered = ClaudeExportService::render(
+            ClaudeExportFormat::Markdown,
+            "/tmp/demo",
+            "session-1",
+            &sample_turns(),
+            "2026-04-08T00:00:00Z",
+        )
+        .unwrap();
+
+        assert!(rendered.starts_with("# Fix the <Login> form\n"));
+        assert!(rendered.contains("```rust\nfn main() {}\n```"));
+        assert!(rendered.contains("<summary>Tool call: Edit</summary>"));
+        assert!(rendered.contains("\"file_path\": \"src/login.tsx\""));
+    }
+
+    #[test]
+    fn render_html_escapes_text_and_renders_code_blocks() {
+        let rendered = ClaudeExportService::render(
+            ClaudeExportFormat::Html,
+            "/tmp/demo",
+            "session-1",
+            &sample_turns(),
+            "2026-04-08T00:00:00Z",
+        )
+        .unwrap();
+
+        assert!(rendered.contains("<title>Fix the &lt;Login&gt; form</title>"));
+        assert!(rendered.contains("<pre><code>fn main() {}</code></pre>"));
+        assert!(!rendered.contains("<Login>"));
+    }
+
+    #[test]
+    fn render_json_produces_normalized_document() {
+        let rendered = ClaudeExportService::render(
+            ClaudeExportFormat::Json,
+            "/tmp/demo",
+            "session-1",
+            &sample_turns(),
+            "2026-04-08T00:00:00Z",
+        )
+        .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+
+        assert_eq!(parsed["title"], "Fix the <Login> form");
+        assert_eq!(parsed["turns"].as_array().unwrap().len(), 2);
+        assert_eq!(parsed["turns"][1]["tool_uses"][0]["name"], "Edit");
+    }
+}
@@ -0,0 +1,1391 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::app_settings::ClaudeSettings;
+use crate::models::claude_job::{
+    ClaudeJob, ClaudeJobOutcome, ClaudeJobProgress, ClaudeJobProgressEvent, ClaudeJobQueue,
+    ClaudeJobStatus, ClaudeJobUsage,
+};
+use crate::services::pty_service::{build_runtime_path, find_in_path, ClaudeLaunchOptions};
+use crate::services::storage_service::StorageService;
+
+pub const EVENT_CLAUDE_JOB_PROGRESS: &str = "claude-job-progress";
+pub const EVENT_CLAUDE_JOB_UPDATED: &str = "claude-job-updated";
+
+/// Time between SIGTERM and SIGKILL when a job is cancelled.
+const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(3);
+/// Finished jobs kept in the history file.
+const MAX_FINISHED_JOBS: usize = 200;
+const MAX_TOOL_RESULT_CHARS: usize = 2000;
+const STDERR_TAIL_BYTES: usize = 4096;
+
+/// Receives job progress; `TauriClaudeJobEvents` forwards it to the webview.
+pub trait ClaudeJobEvents: Send + Sync {
+    fn progress(&self, job_id: &str, progress: &ClaudeJobProgress);
+    /// The job was started or finished.
+    fn updated(&self, job: &ClaudeJob);
+}
+
+pub struct TauriClaudeJobEvents {
+    app_handle: tauri::AppHandle,
+}
+
+impl TauriClaudeJobEvents {
+    pub fn new(app_handle: tauri::AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+impl ClaudeJobEvents for TauriClaudeJobEvents {
+    fn progress(&self, job_id: &str, progress: &ClaudeJobProgress) {
+        use tauri::Emitter;
+
+        let payload = ClaudeJobProgressEvent { job_id, progress };
+        if let Err(error) = self.app_handle.emit(EVENT_CLAUDE_JOB_PROGRESS, payload) {
+            log::debug!("Failed emitting job progress for {}: {}", job_id, error);
+        }
+    }
+
+    fn updated(&self, job: &ClaudeJob) {
+        use tauri::Emitter;
+
+        if let Err(error) = self.app_handle.emit(EVENT_CLAUDE_JOB_UPDATED, job) {
+            log::debug!("Failed emitting job update for {}: {}", job.id, error);
+        }
+    }
+}
+
+/// Queue settings taken from `AppSettings.claude`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClaudeJobQueueOptions {
+    pub max_concurrent: usize,
+    /// Retries for jobs enqueued without their own limit
+    pub max_retries: u32,
+}
+
+impl Default for ClaudeJobQueueOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 2,
+            max_retries: 1,
+        }
+    }
+}
+
+impl ClaudeJobQueueOptions {
+    pub fn from_settings(settings: &ClaudeSettings) -> Self {
+        Self {
+            max_concurrent: settings.job_concurrency.max(1) as usize,
+            max_retries: settings.job_max_retries,
+        }
+    }
+}
+
+/// Contents of `claude-jobs.json`: the queue in order, followed by finished jobs
+/// interleaved where they were enqueued.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct ClaudeJobQueueFile {
+    paused: bool,
+    jobs: Vec<ClaudeJob>,
+}
+
+struct QueueState {
+    paused: bool,
+    jobs: Vec<ClaudeJob>,
+    options: ClaudeJobQueueOptions,
+    /// Set on app exit; nothing new is started and runs that end are not recorded.
+    shutting_down: bool,
+}
+
+impl QueueState {
+    fn find_mut(&mut self, job_id: &str) -> Option<&mut ClaudeJob> {
+        self.jobs.iter_mut().find(|job| job.id == job_id)
+    }
+
+    fn snapshot(&self) -> ClaudeJobQueue {
+        ClaudeJobQueue {
+            paused: self.paused,
+            max_concurrent: self.options.max_concurrent,
+            jobs: self
+                .jobs
+                .iter()
+                .filter(|job| !job.status.is_finished())
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Drops the oldest finished jobs beyond `MAX_FINISHED_JOBS`.
+    fn prune(&mut self) {
+        let finished = self
+            .jobs
+            .iter()
+            .filter(|job| job.status.is_finished())
+            .count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+        if excess == 0 {
+            return;
+        }
+
+        self.jobs.retain(|job| {
+            if excess > 0 && job.status.is_finished() {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+    }
+}
+
+struct RunningJob {
+    pid: u32,
+    cancelled: Arc<AtomicBool>,
+    finished: Arc<AtomicBool>,
+}
+
+/// State shared with the runner threads. Lock order: `state`, then `running`.
+struct JobQueue {
+    state: Mutex<QueueState>,
+    running: Mutex<HashMap<String, RunningJob>>,
+    file: PathBuf,
+    events: Arc<dyn ClaudeJobEvents>,
+    program: String,
+}
+
+impl JobQueue {
+    fn load(
+        file: PathBuf,
+        program: &str,
+        events: Arc<dyn ClaudeJobEvents>,
+        options: ClaudeJobQueueOptions,
+    ) -> Self {
+        let stored = StorageService::read::<ClaudeJobQueueFile>(&file).unwrap_or_default();
+        let mut state = QueueState {
+            paused: stored.paused,
+            jobs: stored.jobs,
+            options,
+            shutting_down: false,
+        };
+
+        // Left over from a crash; `shutdown` records runs it stops itself.
+        let mut interrupted = false;
+        for job in state
+            .jobs
+            .iter_mut()
+            .filter(|job| job.status == ClaudeJobStatus::Running)
+        {
+            mark_interrupted(job);
+            interrupted = true;
+        }
+
+        let queue = Self {
+            state: Mutex::new(state),
+            running: Mutex::new(HashMap::new()),
+            file,
+            events,
+            program: program.to_string(),
+        };
+        if interrupted {
+            if let Ok(state) = queue.state.lock() {
+                queue.persist(&state);
+            }
+        }
+        queue
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, QueueState>, String> {
+        self.state.lock().map_err(|e| e.to_string())
+    }
+
+    fn persist(&self, state: &QueueState) {
+        let stored = ClaudeJobQueueFile {
+            paused: state.paused,
+            jobs: state.jobs.clone(),
+        };
+        if let Err(error) = StorageService::write(&self.file, &stored) {
+            log::warn!("Failed to save Claude jobs: {}", error);
+        }
+    }
+
+    /// Starts pending jobs in queue order while there is capacity, skipping projects
+    /// that already have a job running.
+    fn schedule(self: &Arc<Self>) {
+        let mut updated = Vec::new();
+        {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            if state.paused || state.shutting_down {
+                return;
+            }
+
+            let mut busy_projects = state
+                .jobs
+                .iter()
+                .filter(|job| job.status == ClaudeJobStatus::Running)
+                .map(|job| job.project_path.clone())
+                .collect::<Vec<_>>();
+            let mut running = busy_projects.len();
+
+            for index in 0..state.jobs.len() {
+                if running >= state.options.max_concurrent {
+                    break;
+                }
+                let job = &state.jobs[index];
+                if job.status != ClaudeJobStatus::Pending
+                    || busy_projects.contains(&job.project_path)
+                {
+                    continue;
+                }
+
+                busy_projects.push(job.project_path.clone());
+                if let Err(error) = self.launch(&mut state, index) {
+                    // Not worth retrying: the CLI or the project directory is missing.
+                    log::warn!(
+                        "Claude job {} failed to start: {}",
+                        state.jobs[index].id,
+                        error
+                    );
+                    let job = &mut state.jobs[index];
+                    job.status = ClaudeJobStatus::Failed;
+                    job.error = Some(error);
+                    job.finished_at = Some(chrono::Utc::now().to_rfc3339());
+                } else {
+                    running += 1;
+                }
+                updated.push(state.jobs[index].clone());
+            }
+
+            if !updated.is_empty() {
+                self.persist(&state);
+            }
+        }
+
+        for job in &updated {
+            self.events.updated(job);
+        }
+    }
+
+    /// Spawns the job at `index` and marks it running. The caller persists and emits.
+    fn launch(self: &Arc<Self>, state: &mut QueueState, index: usize) -> Result<(), String> {
+        let job = &mut state.jobs[index];
+        job.attempts += 1;
+        let mut child = spawn_claude(&self.program, job)?;
+
+        job.status = ClaudeJobStatus::Running;
+        job.started_at = Some(chrono::Utc::now().to_rfc3339());
+        job.finished_at = None;
+        job.claude_session_id = None;
+        job.result = None;
+        job.error = None;
+        job.usage = None;
+        job.num_turns = None;
+        job.duration_ms = None;
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let finished = Arc::new(AtomicBool::new(false));
+        self.running.lock().map_err(|e| e.to_string())?.insert(
+            job.id.clone(),
+            RunningJob {
+                pid: child.id(),
+                cancelled: Arc::clone(&cancelled),
+                finished: Arc::clone(&finished),
+            },
+        );
+        log::info!(
+            "Claude job started id={} attempt={} pid={} cwd={}",
+            job.id,
+            job.attempts,
+            child.id(),
+            job.project_path
+        );
+
+        let job_id = job.id.clone();
+        let queue = Arc::clone(self);
+        thread::spawn(move || {
+            let stderr = child.stderr.take();
+            let stderr_tail = thread::spawn(move || read_tail(stderr, STDERR_TAIL_BYTES));
+            let outcome = child
+                .stdout
+                .take()
+                .and_then(|stdout| follow_stream(&job_id, stdout, &queue));
+            let exit = child.wait();
+            finished.store(true, Ordering::SeqCst);
+            let stderr_tail = stderr_tail.join().unwrap_or_default();
+
+            let cancelled = cancelled.load(Ordering::SeqCst);
+            queue.finish(&job_id, cancelled, &exit, outcome.as_ref(), &stderr_tail);
+        });
+
+        Ok(())
+    }
+
+    fn finish(
+        self: &Arc<Self>,
+        job_id: &str,
+        cancelled: bool,
+        exit: &std::io::Result<std::process::ExitStatus>,
+        outcome: Option<&ClaudeJobOutcome>,
+        stderr_tail: &str,
+    ) {
+        let job = {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            // Removed under the state lock so a retry cannot be started in between.
+            if let Ok(mut running) = self.running.lock() {
+                running.remove(job_id);
+            }
+            if state.shutting_down {
+                return;
+            }
+            let Some(job) = state.find_mut(job_id) else {
+                return;
+            };
+
+            job.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            job.status = if cancelled {
+                ClaudeJobStatus::Cancelled
+            } else if matches!((exit, outcome), (Ok(status), Some(outcome)) if status.success() && !outcome.is_error)
+            {
+                ClaudeJobStatus::Succeeded
+            } else {
+                ClaudeJobStatus::Failed
+            };
+
+            if let Some(outcome) = outcome {
+                job.claude_session_id = outcome
+                    .claude_session_id
+                    .clone()
+                    .or(job.claude_session_id.take());
+                job.result = outcome.result.clone();
+                job.usage = Some(outcome.usage.clone());
+                job.num_turns = outcome.num_turns;
+                job.duration_ms = outcome.duration_ms;
+            }
+            if job.status == ClaudeJobStatus::Failed {
+                job.error = Some(failure_message(exit, outcome, stderr_tail));
+                if job.attempts <= job.max_retries {
+                    log::info!(
+                        "Retrying Claude job id={} after attempt {}",
+                        job.id,
+                        job.attempts
+                    );
+                    job.status = ClaudeJobStatus::Pending;
+                    job.finished_at = None;
+                }
+            }
+
+            let job = job.clone();
+            self.persist(&state);
+            job
+        };
+
+        log::info!("Claude job finished id={} status={:?}", job.id, job.status);
+        self.events.updated(&job);
+        self.schedule();
+    }
+}
+
+/// Runs `claude -p --output-format stream-json` in project directories without a
+/// terminal, either right away or through a persistent queue, and reports progress.
+pub struct ClaudeJobService {
+    queue: Arc<JobQueue>,
+}
+
+impl ClaudeJobService {
+    /// Loads the stored queue and resumes its pending jobs unless it is paused.
+    pub fn new(events: Arc<dyn ClaudeJobEvents>, options: ClaudeJobQueueOptions) -> Self {
+        Self::with_config(
+            StorageService::claude_jobs_file(),
+            "claude",
+            events,
+            options,
+        )
+    }
+
+    fn with_config(
+        file: PathBuf,
+        program: &str,
+        events: Arc<dyn ClaudeJobEvents>,
+        options: ClaudeJobQueueOptions,
+    ) -> Self {
+        let queue = Arc::new(JobQueue::load(file, program, events, options));
+        queue.schedule();
+        Self { queue }
+    }
+
+    /// Applies changed settings; a higher limit starts waiting jobs right away.
+    pub fn set_options(&self, options: ClaudeJobQueueOptions) -> Result<(), String> {
+        self.queue.lock()?.options = options;
+        self.queue.schedule();
+        Ok(())
+    }
+
+    /// Starts a job right away, outside the queue's limits, and returns it in the
+    /// `running` state. Failed runs are not retried.
+    pub fn start(
+        &self,
+        project_path: &str,
+        prompt: &str,
+        options: ClaudeLaunchOptions,
+    ) -> Result<ClaudeJob, String> {
+        let job = new_job(project_path, prompt, options, 0)?;
+
+        let job = {
+            let mut state = self.queue.lock()?;
+            state.jobs.push(job);
+            let index = state.jobs.len() - 1;
+            if let Err(error) = self.queue.launch(&mut state, index) {
+                state.jobs.pop();
+                return Err(error);
+            }
+            let job = state.jobs[index].clone();
+            state.prune();
+            self.queue.persist(&state);
+            job
+        };
+
+        self.queue.events.updated(&job);
+        Ok(job)
+    }
+
+    /// Adds a job to the end of the queue. `max_retries` defaults to the setting.
+    pub fn enqueue(
+        &self,
+        project_path: &str,
+        prompt: &str,
+        options: ClaudeLaunchOptions,
+        max_retries: Option<u32>,
+    ) -> Result<ClaudeJob, String> {
+        let job = {
+            let mut state = self.queue.lock()?;
+            let max_retries = max_retries.unwrap_or(state.options.max_retries);
+            let job = new_job(project_path, prompt, options, max_retries)?;
+            state.jobs.push(job.clone());
+            state.prune();
+            self.queue.persist(&state);
+            job
+        };
+        log::info!("Claude job queued id={} cwd={}", job.id, job.project_path);
+
+        self.queue.events.updated(&job);
+        self.queue.schedule();
+        self.get(&job.id)
+    }
+
+    /// Moves the listed pending jobs to the front of the queue in the given order.
+    /// Pending jobs not listed keep their relative order behind them.
+    pub fn reorder(&self, job_ids: &[String]) -> Result<ClaudeJobQueue, String> {
+        let mut state = self.queue.lock()?;
+
+        let slots = state
+            .jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| job.status == ClaudeJobStatus::Pending)
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let mut pending = slots
+            .iter()
+            .map(|index| state.jobs[*index].clone())
+            .collect::<Vec<_>>();
+
+        let mut ordered = Vec::with_capacity(pending.len());
+        for job_id in job_ids {
+            if let Some(position) = pending.iter().position(|job| job.id == job_id.trim()) {
+                ordered.push(pending.remove(position));
+            }
+        }
+        ordered.append(&mut pending);
+
+        for (index, job) in slots.into_iter().zip(ordered) {
+            state.jobs[index] = job;
+        }
+        self.queue.persist(&state);
+        Ok(state.snapshot())
+    }
+
+    /// A paused queue starts no new jobs; running ones are left alone.
+    pub fn set_paused(&self, paused: bool) -> Result<ClaudeJobQueue, String> {
+        {
+            let mut state = self.queue.lock()?;
+            state.paused = paused;
+            self.queue.persist(&state);
+        }
+        log::info!("Claude job queue paused={}", paused);
+
+        if !paused {
+            self.queue.schedule();
+        }
+        self.queue()
+    }
+
+    pub fn queue(&self) -> Result<ClaudeJobQueue, String> {
+        Ok(self.queue.lock()?.snapshot())
+    }
+
+    /// Removes a pending job from the queue, or stops a running one: SIGTERM to its
+    /// process group, SIGKILL after a grace period. Returns false when the job has
+    /// already finished.
+    pub fn cancel(&self, job_id: &str) -> Result<bool, String> {
+        let job_id = job_id.trim();
+        let mut state = self.queue.lock()?;
+        let Some(job) = state.find_mut(job_id) else {
+            return Ok(false);
+        };
+
+        match job.status {
+            ClaudeJobStatus::Pending => {
+                job.status = ClaudeJobStatus::Cancelled;
+                job.finished_at = Some(chrono::Utc::now().to_rfc3339());
+                let job = job.clone();
+                self.queue.persist(&state);
+                drop(state);
+
+                log::info!("Cancelled queued Claude job id={}", job_id);
+                self.queue.events.updated(&job);
+                Ok(true)
+            }
+            ClaudeJobStatus::Running => {
+                let running = self.queue.running.lock().map_err(|e| e.to_string())?;
+                let Some(job) = running.get(job_id) else {
+                    return Ok(false);
+                };
+
+                log::info!("Cancelling Claude job id={} pid={}", job_id, job.pid);
+                job.cancelled.store(true, Ordering::SeqCst);
+                signal_job(job.pid, false);
+
+                let (pid, finished) = (job.pid, Arc::clone(&job.finished));
+                thread::spawn(move || {
+                    thread::sleep(CANCEL_GRACE_PERIOD);
+                    if !finished.load(Ordering::SeqCst) {
+                        signal_job(pid, true);
+                    }
+                });
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Stops running jobs when the app exits, so they cannot outlive it and run again
+    /// next to their retry. SIGTERM first, SIGKILL for what is left after the grace period.
+    /// Stopped jobs are queued again if they have retries left.
+    pub fn shutdown(&self) {
+        let stopping = {
+            let Ok(mut state) = self.queue.state.lock() else {
+                return;
+            };
+            state.shutting_down = true;
+            let Ok(running) = self.queue.running.lock() else {
+                return;
+            };
+
+            let mut stopping = Vec::new();
+            for job in state
+                .jobs
+                .iter_mut()
+                .filter(|job| job.status == ClaudeJobStatus::Running)
+            {
+                mark_interrupted(job);
+                if let Some(run) = running.get(&job.id) {
+                    log::info!("Stopping Claude job on exit id={} pid={}", job.id, run.pid);
+                    signal_job(run.pid, false);
+                    stopping.push((run.pid, Arc::clone(&run.finished)));
+                }
+            }
+            self.queue.persist(&state);
+            stopping
+        };
+
+        let deadline = std::time::Instant::now() + CANCEL_GRACE_PERIOD;
+        while std::time::Instant::now() < deadline
+            && stopping
+                .iter()
+                .any(|(_, finished)| !finished.load(Ordering::SeqCst))
+        {
+            thread::sleep(Duration::from_millis(25));
+        }
+        for (pid, finished) in &stopping {
+            if !finished.load(Ordering::SeqCst) {
+                signal_job(*pid, true);
+            }
+        }
+    }
+
+    /// Newest first.
+    pub fn list(&self) -> Result<Vec<ClaudeJob>, String> {
+        let state = self.queue.lock()?;
+        let mut jobs = state.jobs.clone();
+        jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(jobs)
+    }
+
+    pub fn get(&self, job_id: &str) -> Result<ClaudeJob, String> {
+        let state = self.queue.lock()?;
+        state
+            .jobs
+            .iter()
+            .find(|job| job.id == job_id.trim())
+            .cloned()
+            .ok_or_else(|| format!("Claude job not found: {}", job_id.trim()))
+    }
+}
+
+/// Queues a run that was cut off again if it has retries left, otherwise fails it.
+fn mark_interrupted(job: &mut ClaudeJob) {
+    job.error = Some("Interrupted: the app exited while the job was running".to_string());
+    job.status = if job.attempts <= job.max_retries {
+        ClaudeJobStatus::Pending
+    } else {
+        job.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        ClaudeJobStatus::Failed
+    };
+}
+
+fn new_job(
+    project_path: &str,
+    prompt: &str,
+    options: ClaudeLaunchOptions,
+    max_retries: u32,
+) -> Result<ClaudeJob, String> {
+    let project_path = project_path.trim();
+    if !Path::new(project_path).is_dir() {
+        return Err(format!("Project directory not found: {}", project_path));
+    }
+    if prompt.trim().is_empty() {
+        return Err("Job prompt cannot be empty".to_string());
+    }
+
+    Ok(ClaudeJob::new(
+        project_path.to_string(),
+        prompt.to_string(),
+        options,
+        max_retries,
+    ))
+}
+
+fn spawn_claude(program: &str, job: &ClaudeJob) -> Result<Child, String> {
+    if !Path::new(&job.project_path).is_dir() {
+        return Err(format!("Project directory not found: {}", job.project_path));
+    }
+    let runtime_path = build_runtime_path(&std::env::var("PATH").unwrap_or_default());
+    let program = find_in_path(program, &runtime_path)
+        .ok_or_else(|| format!("Claude CLI not found: {}", program))?;
+
+    let mut command = Command::new(program);
+    // The prompt goes right after `-p` so no variadic option can take it.
+    command
+        .arg("-p")
+        .arg(&job.prompt)
+        .args(["--output-format", "stream-json", "--verbose"])
+        .args(job.options.to_args())
+        .current_dir(&job.project_path)
+        .env("PATH", &runtime_path)
+        // Claude refuses to start when it looks nested in another Claude session.
+        .env("CLAUDECODE", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // Its own process group, so cancelling also stops the tools Claude spawned.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    command
+        .spawn()
+        .map_err(|e| format!("Failed to start Claude job: {}", e))
+}
+
+/// Emits progress for each stream-json line and returns the final `result`, if any.
+fn follow_stream(job_id: &str, stdout: impl Read, queue: &JobQueue) -> Option<ClaudeJobOutcome> {
+    let mut outcome = None;
+
+    for line in BufReader::new(stdout).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        for progress in parse_stream_line(&line) {
+            match &progress {
+                ClaudeJobProgress::Started {
+                    claude_session_id, ..
+                } => {
+                    if let Ok(mut state) = queue.state.lock() {
+                        if let Some(job) = state.find_mut(job_id) {
+                            job.claude_session_id = Some(claude_session_id.clone());
+                        }
+                    }
+                }
+                ClaudeJobProgress::Completed(completed) => outcome = Some(completed.clone()),
+                _ => {}
+            }
+            queue.events.progress(job_id, &progress);
+        }
+    }
+
+    outcome
+}
+
+/// Parses one line of `--output-format stream-json`. Lines that are not JSON or carry
+/// nothing to show (e.g. thinking blocks) yield no progress.
+pub(crate) fn parse_stream_line(line: &str) -> Vec<ClaudeJobProgress> {
+    let Ok(event) = serde_json::from_str::<Value>(line.trim()) else {
+        if !line.trim().is_empty() {
+            log::debug!("Ignoring non-JSON job output: {}", line);
+        }
+        return Vec::new();
+    };
+    let text =
+        |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+    let content = |event: &Value| {
+        event
+            .pointer("/message/content")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    match event.get("type").and_then(Value::as_str) {
+        Some("system") if event.get("subtype").and_then(Value::as_str) == Some("init") => {
+            text(&event, "session_id")
+                .map(|claude_session_id| ClaudeJobProgress::Started {
+                    claude_session_id,
+                    model: text(&event, "model"),
+                })
+                .into_iter()
+                .collect()
+        }
+        Some("assistant") => content(&event)
+            .iter()
+            .filter_map(|block| match block.get("type").and_then(Value::as_str) {
+                Some("text") => text(block, "text")
+                    .filter(|text| !text.trim().is_empty())
+                    .map(|text| ClaudeJobProgress::AssistantText { text }),
+                Some("tool_use") => Some(ClaudeJobProgress::ToolUse {
+                    tool_use_id: text(block, "id").unwrap_or_default(),
+                    tool: text(block, "name").unwrap_or_default(),
+                    input: block.get("input").cloned().unwrap_or(Value::Null),
+                }),
+                _ => None,
+            })
+            .collect(),
+        Some("user") => content(&event)
+            .iter()
+            .filter(|block| block.get("type").and_then(Value::as_str) == Some("tool_result"))
+            .map(|block| ClaudeJobProgress::ToolResult {
+                tool_use_id: text(block, "tool_use_id").unwrap_or_default(),
+                is_error: block
+                    .get("is_error")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                content: tool_result_text(block.get("content")),
+            })
+            .collect(),
+        Some("result") => {
+            let count = |key: &str| {
+                event
+                    .pointer(&format!("/usage/{}", key))
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0)
+            };
+            vec![ClaudeJobProgress::Completed(ClaudeJobOutcome {
+                is_error: event
+                    .get("is_error")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                subtype: text(&event, "subtype").unwrap_or_default(),
+                result: text(&event, "result"),
+                claude_session_id: text(&event, "session_id"),
+                usage: ClaudeJobUsage {
+                    input_tokens: count("input_tokens"),
+                    output_tokens: count("output_tokens"),
+                    cache_creation_input_tokens: count("cache_creation_input_tokens"),
+                    cache_read_input_tokens: count("cache_read_input_tokens"),
+                    total_cost_usd: event.get("total_cost_usd").and_then(Value::as_f64),
+                },
+                num_turns: event
+                    .get("num_turns")
+                    .and_then(Value::as_u64)
+                    .and_then(|turns| u32::try_from(turns).ok()),
+                duration_ms: event.get("duration_ms").and_then(Value::as_u64),
+            })]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Tool results are a string or a list of content blocks.
+fn tool_result_text(content: Option<&Value>) -> String {
+    let text = match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    };
+
+    match text.char_indices().nth(MAX_TOOL_RESULT_CHARS) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text,
+    }
+}
+
+fn failure_message(
+    exit: &std::io::Result<std::process::ExitStatus>,
+    outcome: Option<&ClaudeJobOutcome>,
+    stderr_tail: &str,
+) -> String {
+    if let Some(outcome) = outcome.filter(|outcome| outcome.is_error) {
+        return match outcome.result.as_deref().filter(|text| !text.is_empty()) {
+            Some(result) => format!("{}: {}", outcome.subtype, result),
+            None => outcome.subtype.clone(),
+        };
+    }
+
+    let status = match exit {
+        Ok(status) => format!("Claude exited with {}", status),
+        Err(error) => format!("Failed waiting for Claude: {}", error),
+    };
+    match stderr_tail.trim() {
+        "" => status,
+        stderr => format!("{}: {}", status, stderr),
+    }
+}
+
+/// The last `limit` bytes of a stream, decoded lossily.
+fn read_tail(reader: Option<impl Read>, limit: usize) -> String {
+    let Some(mut reader) = reader else {
+        return String::new();
+    };
+
+    let mut tail = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                tail.extend_from_slice(&buf[..n]);
+                if tail.len() > limit {
+                    tail.drain(..tail.len() - limit);
+                }
+            }
+        }
+    }
+    String::from_utf8_lossy(&tail).into_owned()
+}
+
+#[cfg(unix)]
+fn signal_job(pid: u32, kill: bool) {
+    let Ok(pgid) = i32::try_from(pid) else {
+        return;
+    };
+    let signal = if kill { libc::SIGKILL } else { libc::SIGTERM };
+    // SAFETY: plain syscall; a group that is already gone just returns ESRCH.
+    if unsafe { libc::killpg(pgid, signal) } != 0 {
+        log::debug!(
+            "killpg({}, {}) failed: {}",
+            pgid,
+            signal,
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn signal_job(pid: u32, _kill: bool) {
+    let result = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    if let Err(error) = result {
+        log::debug!("taskkill for {} failed: {}", pid, error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_stream_line, ClaudeJobEvents, ClaudeJobQueueFile, ClaudeJobQueueOptions,
+        ClaudeJobService,
+    };
+    use crate::models::claude_job::{ClaudeJob, ClaudeJobProgress, ClaudeJobStatus};
+    use crate::services::pty_service::ClaudeLaunchOptions;
+    use crate::services::storage_service::{unique_test_data_dir, StorageService};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+
+    enum JobEvent {
+        Progress(ClaudeJobProgress),
+        Updated(Box<ClaudeJob>),
+    }
+
+    struct ChannelEvents(Mutex<mpsc::Sender<JobEvent>>);
+
+    impl ClaudeJobEvents for ChannelEvents {
+        fn progress(&self, _job_id: &str, progress: &ClaudeJobProgress) {
+            let _ = self
+                .0
+                .lock()
+                .unwrap()
+                .send(JobEvent::Progress(progress.clone()));
+        }
+
+        fn updated(&self, job: &ClaudeJob) {
+            let _ = self
+                .0
+                .lock()
+                .unwrap()
+                .send(JobEvent::Updated(Box::new(job.clone())));
+        }
+    }
+
+    #[cfg(unix)]
+    fn fake_claude(dir: &Path, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("claude");
+        fs::write(&path, format!("#!/bin/sh\n{}", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn queue_service(
+        dir: &Path,
+        program: &Path,
+        options: ClaudeJobQueueOptions,
+    ) -> (ClaudeJobService, mpsc::Receiver<JobEvent>) {
+        let (sender, receiver) = mpsc::channel();
+        let service = ClaudeJobService::with_config(
+            dir.join("claude-jobs.json"),
+            program.to_str().unwrap(),
+            Arc::new(ChannelEvents(Mutex::new(sender))),
+            options,
+        );
+        (service, receiver)
+    }
+
+    fn job_service(dir: &Path, program: &Path) -> (ClaudeJobService, mpsc::Receiver<JobEvent>) {
+        queue_service(dir, program, ClaudeJobQueueOptions::default())
+    }
+
+    /// Final state of each job in `job_ids`, in that order.
+    fn wait_all_finished(receiver: &mpsc::Receiver<JobEvent>, job_ids: &[&str]) -> Vec<ClaudeJob> {
+        let mut finished = HashMap::new();
+        while finished.len() < job_ids.len() {
+            if let JobEvent::Updated(job) = receiver.recv_timeout(Duration::from_secs(15)).unwrap()
+            {
+                if job.status.is_finished() && job_ids.contains(&job.id.as_str()) {
+                    finished.insert(job.id.clone(), *job);
+                }
+            }
+        }
+        job_ids
+            .iter()
+            .map(|id| finished.remove(*id).unwrap())
+            .collect()
+    }
+
+    fn timestamp(value: &Option<String>) -> chrono::DateTime<chrono::FixedOffset> {
+        chrono::DateTime::parse_from_rfc3339(value.as_deref().unwrap()).unwrap()
+    }
+
+    #[cfg(unix)]
+    const SUCCESS_LINE: &str = r#"{"type":"result","subtype":"success","is_error":false,"result":"done","session_id":"s"}"#;
+
+    fn wait_finished(receiver: &mpsc::Receiver<JobEvent>) -> (Vec<ClaudeJobProgress>, ClaudeJob) {
+        let mut progress = Vec::new();
+        loop {
+            match receiver.recv_timeout(Duration::from_secs(15)).unwrap() {
+                JobEvent::Progress(event) => progress.push(event),
+                JobEvent::Updated(job) if job.status.is_finished() => return (progress, *job),
+                JobEvent::Updated(_) => {}
+            }
+        }
+    }
+
+    #[test]
+    fn parse_stream_line_maps_events_to_progress() {
+        let assistant = json!({
+            "type": "assistant",
+            "message": {"content": [
+                {"type": "thinking", "thinking": "hmm"},
+                {"type": "text", "text": "Running tests"},
+                {"type": "tool_use", "id": "tu_1", "name": "Bash", "input": {"command": "npm test"}}
+            ]}
+        });
+        assert_eq!(
+            parse_stream_line(&assistant.to_string()),
+            vec![
+                ClaudeJobProgress::AssistantText {
+                    text: "Running tests".to_string()
+                },
+                ClaudeJobProgress::ToolUse {
+                    tool_use_id: "tu_1".to_string(),
+                    tool: "Bash".to_string(),
+                    input: json!({"command": "npm test"}),
+                },
+            ]
+        );
+
+        let tool_result = json!({
+            "type": "user",
+            "message": {"content": [{
+                "type": "tool_result", "tool_use_id": "tu_1", "is_error": true,
+                "content": [{"type": "text", "text": "1 failing"}]
+            }]}
+        });
+        assert_eq!(
+            parse_stream_line(&tool_result.to_string()),
+            vec![ClaudeJobProgress::ToolResult {
+                tool_use_id: "tu_1".to_string(),
+                is_error: true,
+                content: "1 failing".to_string(),
+            }]
+        );
+
+        assert!(parse_stream_line("not json").is_empty());
+        assert!(parse_stream_line(r#"{"type":"system","subtype":"hook"}"#).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn job_streams_progress_and_persists_result_and_usage() {
+        let dir = unique_test_data_dir("claude-job-run");
+        fs::create_dir_all(&dir).unwrap();
+        let program = fake_claude(
+            &dir,
+            r#"[ "$1" = -p ] || exit 2
+echo '{"type":"system","subtype":"init","session_id":"s-1","model":"claude-sonnet"}'
+echo 'npm WARN something'
+printf '{"type":"assistant","message":{"content":[{"type":"text","text":"prompt was: %s in %s"}]}}\n' "$2" "$(basename "$PWD")"
+echo '{"type":"result","subtype":"success","is_error":false,"result":"All green","session_id":"s-1","num_turns":3,"duration_ms":1200,"total_cost_usd":0.25,"usage":{"input_tokens":10,"output_tokens":20,"cache_read_input_tokens":5}}'
+"#,
+        );
+        let project = dir.join("project");
+        fs::create_dir_all(&project).unwrap();
+
+        let (service, receiver) = job_service(&dir, &program);
+        let job = service
+            .start(
+                project.to_str().unwrap(),
+                "fix lint",
+                ClaudeLaunchOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(job.status, ClaudeJobStatus::Running);
+
+        let (progress, finished) = wait_finished(&receiver);
+        assert_eq!(
+            progress[0],
+            ClaudeJobProgress::Started {
+                claude_session_id: "s-1".to_string(),
+                model: Some("claude-sonnet".to_string()),
+            }
+        );
+        assert_eq!(
+            progress[1],
+            ClaudeJobProgress::AssistantText {
+                text: "prompt was: fix lint in project".to_string()
+            }
+        );
+        assert!(matches!(progress[2], ClaudeJobProgress::Completed(_)));
+
+        assert_eq!(finished.status, ClaudeJobStatus::Succeeded);
+        assert_eq!(finished.result.as_deref(), Some("All green"));
+        assert_eq!(finished.claude_session_id.as_deref(), Some("s-1"));
+        let usage = finished.usage.clone().unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (10, 20));
+        assert_eq!(usage.cache_read_input_tokens, 5);
+        assert_eq!(usage.total_cost_usd, Some(0.25));
+        assert_eq!(finished.num_turns, Some(3));
+
+        // A fresh service reads the persisted history.
+        let (reloaded, _) = job_service(&dir, &program);
+        let stored = reloaded.get(&job.id).unwrap();
+        assert_eq!(stored.status, ClaudeJobStatus::Succeeded);
+        assert_eq!(stored.usage, finished.usage);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn job_failure_and_cancel_are_reported() {
+        let dir = unique_test_data_dir("claude-job-cancel");
+        fs::create_dir_all(&dir).unwrap();
+
+        let program = fake_claude(&dir, "echo 'API key missing' >&2\nexit 1\n");
+        let (service, receiver) = job_service(&dir, &program);
+        service
+            .start(dir.to_str().unwrap(), "x", ClaudeLaunchOptions::default())
+            .unwrap();
+        let (_, failed) = wait_finished(&receiver);
+        assert_eq!(failed.status, ClaudeJobStatus::Failed);
+        assert!(
+            failed.error.as_deref().unwrap().contains("API key missing"),
+            "{:?}",
+            failed.error
+        );
+
+        // Ignores SIGTERM, like a tool stuck in a syscall; the child sleep keeps it busy.
+        let program = fake_claude(
+            &dir,
+            "trap '' TERM\necho '{\"type\":\"system\",\"subtype\":\"init\",\"session_id\":\"s-2\"}'\nsleep 30 &\nwait\nsleep 30\n",
+        );
+        let (service, receiver) = job_service(&dir, &program);
+        let job = service
+            .start(dir.to_str().unwrap(), "x", ClaudeLaunchOptions::default())
+            .unwrap();
+        // The reader thread can report the start before `start` emits the running update.
+        while !matches!(
+            receiver.recv_timeout(Duration::from_secs(10)).unwrap(),
+            JobEvent::Progress(ClaudeJobProgress::Started { .. })
+        ) {}
+        assert!(service.cancel(&job.id).unwrap());
+
+        let (_, cancelled) = wait_finished(&receiver);
+        assert_eq!(cancelled.status, ClaudeJobStatus::Cancelled);
+        assert_eq!(cancelled.claude_session_id.as_deref(), Some("s-2"));
+        assert!(!service.cancel(&job.id).unwrap());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn queue_limits_concurrency_and_serializes_projects() {
+        let dir = unique_test_data_dir("claude-job-queue");
+        let program = {
+            fs::create_dir_all(&dir).unwrap();
+            fake_claude(&dir, &format!("sleep 0.3\necho '{}'\n", SUCCESS_LINE))
+        };
+        let project = |name: &str| {
+            let path = dir.join(name);
+            fs::create_dir_all(&path).unwrap();
+            path.to_string_lossy().into_owned()
+        };
+        let (a, b, c) = (project("a"), project("b"), project("c"));
+
+        let options = ClaudeJobQueueOptions {
+            max_concurrent: 2,
+            max_retries: 0,
+        };
+        let (service, receiver) = queue_service(&dir, &program, options);
+        assert!(service.set_paused(true).unwrap().paused);
+
+        let enqueue = |path: &str, prompt: &str| {
+            let job = service
+                .enqueue(path, prompt, ClaudeLaunchOptions::default(), None)
+                .unwrap();
+            assert_eq!(job.status, ClaudeJobStatus::Pending);
+            job.id
+        };
+        let ids = [
+            enqueue(&a, "a1"),
+            enqueue(&a, "a2"),
+            enqueue(&b, "b1"),
+            enqueue(&c, "c1"),
+        ];
+
+        let queue = service.reorder(&[ids[3].clone()]).unwrap();
+        let prompts = queue
+            .jobs
+            .iter()
+            .map(|job| job.prompt.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(prompts, ["c1", "a1", "a2", "b1"]);
+
+        let queue = service.set_paused(false).unwrap();
+        let running = queue
+            .jobs
+            .iter()
+            .filter(|job| job.status == ClaudeJobStatus::Running)
+            .map(|job| job.prompt.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(running, ["c1", "a1"]);
+
+        let ids = ids.iter().map(String::as_str).collect::<Vec<_>>();
+        let jobs = wait_all_finished(&receiver, &ids);
+        assert!(jobs
+            .iter()
+            .all(|job| job.status == ClaudeJobStatus::Succeeded && job.attempts == 1));
+
+        for job in &jobs {
+            let started = timestamp(&job.started_at);
+            let overlapping = jobs
+                .iter()
+                .filter(|other| other.id != job.id)
+                .filter(|other| {
+                    timestamp(&other.started_at) <= started
+                        && started < timestamp(&other.finished_at)
+                })
+                .collect::<Vec<_>>();
+            assert!(
+                overlapping.len() < 2,
+                "{} started over the limit",
+                job.prompt
+            );
+            assert!(
+                overlapping
+                    .iter()
+                    .all(|other| other.project_path != job.project_path),
+                "{} overlapped a job in the same project",
+                job.prompt
+            );
+        }
+        assert!(service.queue().unwrap().jobs.is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_jobs_are_retried_and_interrupted_queue_resumes() {
+        let dir = unique_test_data_dir("claude-job-retry");
+        fs::create_dir_all(&dir).unwrap();
+        // Fails until it has run `$2` times in the project directory.
+        let program = fake_claude(
+            &dir,
+            &format!(
+                r#"echo run >> runs
+[ "$(wc -l < runs)" -ge "$2" ] && echo '{}' && exit 0
+echo '{{"type":"result","subtype":"error_during_execution","is_error":true}}'
+exit 1
+"#,
+                SUCCESS_LINE
+            ),
+        );
+
+        let job_in = |name: &str, prompt: &str, status, attempts, max_retries| {
+            let path = dir.join(name);
+            fs::create_dir_all(&path).unwrap();
+            let mut job = ClaudeJob::new(
+                path.to_string_lossy().into_owned(),
+                prompt.to_string(),
+                ClaudeLaunchOptions::default(),
+                max_retries,
+            );
+            job.status = status;
+            job.attempts = attempts;
+            job
+        };
+        // As left behind by an app that quit mid-run.
+        let jobs = vec![
+            job_in("a", "3", ClaudeJobStatus::Running, 1, 2),
+            job_in("b", "2", ClaudeJobStatus::Pending, 0, 1),
+            job_in("c", "1", ClaudeJobStatus::Running, 1, 0),
+        ];
+        fs::write(dir.join("a").join("runs"), "run\n").unwrap();
+        let ids = jobs.iter().map(|job| job.id.clone()).collect::<Vec<_>>();
+        StorageService::write(
+            &dir.join("claude-jobs.json"),
+            &ClaudeJobQueueFile {
+                paused: false,
+                jobs,
+            },
+        )
+        .unwrap();
+
+        let (service, receiver) = job_service(&dir, &program);
+        let interrupted = service.get(&ids[2]).unwrap();
+        assert_eq!(interrupted.status, ClaudeJobStatus::Failed);
+        assert!(interrupted.error.unwrap().starts_with("Interrupted"));
+
+        let finished = wait_all_finished(&receiver, &[&ids[0], &ids[1]]);
+        assert_eq!(finished[0].status, ClaudeJobStatus::Succeeded);
+        assert_eq!(finished[0].attempts, 3);
+        assert_eq!(finished[0].error, None);
+        assert_eq!(finished[1].status, ClaudeJobStatus::Succeeded);
+        assert_eq!(finished[1].attempts, 2);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shutdown_stops_running_jobs_and_records_them_interrupted() {
+        let dir = unique_test_data_dir("claude-job-shutdown");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("b")).unwrap();
+        let program = fake_claude(
+            &dir,
+            "echo '{\"type\":\"system\",\"subtype\":\"init\",\"session_id\":\"s\"}'\nsleep 30\n",
+        );
+
+        let (service, receiver) = job_service(&dir, &program);
+        let queued = service
+            .enqueue(
+                dir.join("a").to_str().unwrap(),
+                "x",
+                ClaudeLaunchOptions::default(),
+                Some(1),
+            )
+            .unwrap();
+        let started = service
+            .start(
+                dir.join("b").to_str().unwrap(),
+                "x",
+                ClaudeLaunchOptions::default(),
+            )
+            .unwrap();
+        let mut init_lines = 0;
+        while init_lines < 2 {
+            if let JobEvent::Progress(ClaudeJobProgress::Started { .. }) =
+                receiver.recv_timeout(Duration::from_secs(10)).unwrap()
+            {
+                init_lines += 1;
+            }
+        }
+
+        let begun = std::time::Instant::now();
+        service.shutdown();
+        assert!(begun.elapsed() < Duration::from_secs(2));
+        // Runners that see their process end must not overwrite what shutdown recorded.
+        std::thread::sleep(Duration::from_millis(200));
+
+        let stored: ClaudeJobQueueFile =
+            StorageService::read(&dir.join("claude-jobs.json")).unwrap();
+        let status = |id: &str| {
+            let job = stored.jobs.iter().find(|job| job.id == id).unwrap();
+            assert!(job.error.as_deref().unwrap().starts_with("Interrupted"));
+            job.status
+        };
+        assert_eq!(status(&queued.id), ClaudeJobStatus::Pending);
+        assert_eq!(status(&started.id), ClaudeJobStatus::Failed);
+        assert_eq!(
+            service
+                .enqueue(
+                    dir.join("a").to_str().unwrap(),
+                    "y",
+                    ClaudeLaunchOptions::default(),
+                    None
+                )
+                .unwrap()
+                .status,
+            ClaudeJobStatus::Pending
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,552 @@
+use crate::models::claude_session::ClaudeProjectMigration;
+use crate::models::ClaudeSession;
+use crate::services::claude_session_cache::ClaudeSessionCache;
+use crate::services::ClaudeSessionService;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SESSIONS_INDEX_FILE: &str = "sessions-index.json";
+
+pub struct ClaudeMigrationService;
+
+impl ClaudeMigrationService {
+    /// Move the Claude history of `old_project_path` into the directory for `new_project_path`,
+    /// merging with any history already recorded there.
+    ///
+    /// `cwd` fields in the JSONL files and the paths in sessions-index.json are rewritten.
+    /// With `dry_run` nothing is touched and the returned report is a preview.
+    pub fn migrate_project(
+        old_project_path: &str,
+        new_project_path: &str,
+        dry_run: bool,
+    ) -> Result<ClaudeProjectMigration, String> {
+        let old_path = Self::normalize_project_path(old_project_path, "Old project path")?;
+        let new_path = Self::normalize_project_path(new_project_path, "New project path")?;
+        if old_path == new_path {
+            return Err("Old and new project paths are the same".to_string());
+        }
+
+        let projects_dir = ClaudeSessionService::claude_projects_dir()
+            .ok_or_else(|| "Cannot determine home directory".to_string())?;
+        let mut cache = ClaudeSessionCache::load();
+        let resolved =
+            ClaudeSessionService::resolve_project_dir(&mut cache, &old_path).and_then(|source| {
+                ClaudeSessionService::resolve_project_dir(&mut cache, &new_path)
+                    .map(|target| (source, target))
+            });
+        cache.save_if_dirty();
+
+        let (source_dir, target_dir) = resolved?;
+        let source_dir =
+            source_dir.ok_or_else(|| format!("No Claude history found for {}", old_path))?;
+        let target_dir = target_dir.unwrap_or_else(|| {
+            projects_dir.join(ClaudeSessionService::encode_project_path(&new_path))
+        });
+
+        if source_dir == target_dir {
+            return Err(format!(
+                "Claude history for both paths is already stored in {}",
+                source_dir.display()
+            ));
+        }
+
+        let mut entries_to_move = Vec::new();
+        let mut session_ids = Vec::new();
+        let mut conflicts = Vec::new();
+
+        let entries = fs::read_dir(&source_dir)
+            .map_err(|e| format!("Failed to read Claude project directory: {}", e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(str::to_string)
+            else {
+                continue;
+            };
+
+            if name == SESSIONS_INDEX_FILE {
+                continue;
+            }
+
+            if Self::is_jsonl_file(&path) {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    session_ids.push(stem.to_string());
+                }
+            }
+
+            if target_dir.join(&name).exists() {
+                conflicts.push(name.clone());
+            }
+
+            entries_to_move.push((path, name));
+        }
+        session_ids.sort();
+        conflicts.sort();
+
+        let mut jsonl_files = Vec::new();
+        Self::collect_jsonl_files(&source_dir, &mut jsonl_files);
+
+        let apply = !dry_run;
+        if apply && !conflicts.is_empty() {
+            return Err(format!(
+                "Cannot migrate, these entries already exist in {}: {}",
+                target_dir.display(),
+                conflicts.join(", ")
+            ));
+        }
+
+        let mut rewritten_cwd_count: u32 = 0;
+        for jsonl_path in &jsonl_files {
+            rewritten_cwd_count +=
+                Self::rewrite_cwd_in_file(jsonl_path, &old_path, &new_path, apply)?;
+        }
+
+        let report = ClaudeProjectMigration {
+            old_project_path: old_path.clone(),
+            new_project_path: new_path.clone(),
+            source_dir: source_dir.to_string_lossy().to_string(),
+            target_dir: target_dir.to_string_lossy().to_string(),
+            merged: target_dir.exists(),
+            dry_run,
+            session_ids,
+            conflicts,
+            rewritten_cwd_count,
+        };
+
+        if !apply {
+            return Ok(report);
+        }
+
+        // Without an index the target's sessions are only found by scanning; register them
+        // before a merged index would hide them.
+        let unindexed_target_sessions = if report.merged
+            && !target_dir.join(SESSIONS_INDEX_FILE).exists()
+            && source_dir.join(SESSIONS_INDEX_FILE).exists()
+        {
+            ClaudeSessionService::list_sessions_for_project(&new_path, None)?
+        } else {
+            Vec::new()
+        };
+
+        fs::create_dir_all(&target_dir)
+            .map_err(|e| format!("Failed to create Claude project directory: {}", e))?;
+
+        for (path, name) in entries_to_move {
+            fs::rename(&path, target_dir.join(&name))
+                .map_err(|e| format!("Failed to move {}: {}", name, e))?;
+        }
+
+        Self::merge_sessions_index(
+            &source_dir,
+            &target_dir,
+            &old_path,
+            &new_path,
+            &report.session_ids,
+            unindexed_target_sessions,
+        )?;
+
+        if let Err(error) = fs::remove_dir(&source_dir) {
+            log::warn!(
+                "Left Claude project directory {} in place: {}",
+                source_dir.display(),
+                error
+            );
+        }
+
+        Ok(report)
+    }
+
+    fn normalize_project_path(value: &str, field: &str) -> Result<String, String> {
+        let normalized = ClaudeSessionService::normalize_non_empty(value, field)?;
+        let trimmed = normalized.trim_end_matches('/');
+
+        Ok(if trimmed.is_empty() {
+            "/".to_string()
+        } else {
+            trimmed.to_string()
+        })
+    }
+
+    fn is_jsonl_file(path: &Path) -> bool {
+        path.is_file() && path.extension().and_then(|e| e.to_str()) == Some("jsonl")
+    }
+
+    /// Session JSONL files, including subagent transcripts in per-session subdirectories.
+    fn collect_jsonl_files(dir: &Path, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                Self::collect_jsonl_files(&path, files);
+            } else if Self::is_jsonl_file(&path) {
+                files.push(path);
+            }
+        }
+    }
+
+    /// Maps `path` from under `old_path` to the same place under `new_path`.
+    fn rehome_path(path: &str, old_path: &str, new_path: &str) -> Option<String> {
+        if path == old_path {
+            return Some(new_path.to_string());
+        }
+
+        path.strip_prefix(old_path)
+            .filter(|rest| rest.starts_with('/'))
+            .map(|rest| format!("{}{}", new_path, rest))
+    }
+
+    /// Counts the lines whose `cwd` points into `old_path`, rewriting them when `apply` is set.
+    /// Lines that don't change are written back byte for byte.
+    fn rewrite_cwd_in_file(
+        path: &Path,
+        old_path: &str,
+        new_path: &str,
+        apply: bool,
+    ) -> Result<u32, String> {
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read JSONL file: {}", e))?;
+
+        let mut rewritten = String::with_capacity(content.len());
+        let mut count: u32 = 0;
+
+        for line in content.split_inclusive('\n') {
+            let updated = line
+                .contains("\"cwd\"")
+                .then(|| serde_json::from_str::<serde_json::Value>(line).ok())
+                .flatten()
+                .and_then(|mut value| {
+                    let cwd = value.get("cwd").and_then(|cwd| cwd.as_str())?;
+                    let rehomed = Self::rehome_path(cwd, old_path, new_path)?;
+                    value["cwd"] = serde_json::Value::String(rehomed);
+                    Some(value)
+                });
+
+            match updated {
+                Some(value) => {
+                    count += 1;
+                    rewritten.push_str(&value.to_string());
+                    if line.ends_with('\n') {
+                        rewritten.push('\n');
+                    }
+                }
+                None => rewritten.push_str(line),
+            }
+        }
+
+        if apply && count > 0 {
+            ClaudeSessionService::write_file_atomically(path, &rewritten)?;
+        }
+
+        Ok(count)
+    }
+
+    /// Moves the source sessions-index.json entries into the target index,
+    /// pointing `originalPath`, `projectPath` and `fullPath` at the new location.
+    /// Moved sessions the source index did not list are indexed from their JSONL, so an
+    /// existing target index does not hide them.
+    fn merge_sessions_index(
+        source_dir: &Path,
+        target_dir: &Path,
+        old_path: &str,
+        new_path: &str,
+        moved_session_ids: &[String],
+        unindexed_target_sessions: Vec<ClaudeSession>,
+    ) -> Result<(), String> {
+        let source_index_path = source_dir.join(SESSIONS_INDEX_FILE);
+        let target_index_path = target_dir.join(SESSIONS_INDEX_FILE);
+
+        // Without any index the sessions are found by scanning the directory.
+        if !source_index_path.exists() && !target_index_path.exists() {
+            return Ok(());
+        }
+
+        let source_entries = if source_index_path.exists() {
+            Self::read_index(&source_index_path)?
+                .get("entries")
+                .and_then(|entries| entries.as_array())
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        let mut target_index = if target_index_path.exists() {
+            Self::read_index(&target_index_path)?
+        } else {
+            serde_json::json!({ "version": 1, "entries": [] })
+        };
+
+        target_index["originalPath"] = serde_json::Value::String(new_path.to_string());
+        let target_entries = target_index
+            .get_mut("entries")
+            .and_then(|entries| entries.as_array_mut())
+            .ok_or_else(|| "Invalid sessions-index.json format: entries missing".to_string())?;
+
+        for session in unindexed_target_sessions {
+            let file_path = target_dir.join(format!("{}.jsonl", session.session_id));
+            target_entries.push(ClaudeSessionService::index_entry_json(&session, &file_path));
+        }
+
+        for mut entry in source_entries {
+            let session_id = entry
+                .get("sessionId")
+                .and_then(|value| value.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let already_indexed = target_entries.iter().any(|existing| {
+                existing.get("sessionId").and_then(|value| value.as_str())
+                    == Some(session_id.as_str())
+            });
+            if session_id.is_empty() || already_indexed {
+                continue;
+            }
+
+            if let Some(project_path) = entry.get("projectPath").and_then(|value| value.as_str()) {
+                let rehomed = Self::rehome_path(project_path, old_path, new_path)
+                    .unwrap_or_else(|| project_path.to_string());
+                entry["projectPath"] = serde_json::Value::String(rehomed);
+            }
+
+            if let Some(file_name) = entry
+                .get("fullPath")
+                .and_then(|value| value.as_str())
+                .and_then(|full_path| Path::new(full_path).file_name())
+                .map(|name| name.to_os_string())
+            {
+                entry["fullPath"] = serde_json::Value::String(
+                    target_dir.join(file_name).to_string_lossy().to_string(),
+                );
+            }
+
+            target_entries.push(entry);
+        }
+
+        for session_id in moved_session_ids {
+            let already_indexed = target_entries.iter().any(|existing| {
+                existing.get("sessionId").and_then(|value| value.as_str())
+                    == Some(session_id.as_str())
+            });
+            if already_indexed {
+                continue;
+            }
+
+            let file_path = target_dir.join(format!("{}.jsonl", session_id));
+            match ClaudeSessionService::parse_jsonl_file(&file_path, session_id, new_path) {
+                Ok(session) => {
+                    target_entries
+                        .push(ClaudeSessionService::index_entry_json(&session, &file_path));
+                }
+                Err(error) => log::warn!(
+                    "Could not index migrated session {}: {}",
+                    file_path.display(),
+                    error
+                ),
+            }
+        }
+
+        let serialized = serde_json::to_string_pretty(&target_index)
+            .map_err(|e| format!("Failed to serialize sessions index: {}", e))?;
+        ClaudeSessionService::write_file_atomically(&target_index_path, &serialized)?;
+
+        if source_index_path.exists() {
+            fs::remove_file(&source_index_path)
+                .map_err(|e| format!("Failed to remove old sessions index: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    fn read_index(index_path: &Path) -> Result<serde_json::Value, String> {
+        let content = fs::read_to_string(index_path)
+            .map_err(|e| format!("Failed to read sessions index: {}", e))?;
+
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse sessions index: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClaudeMigrationService;
+    use crate::services::storage_service::{storage_test_env_lock, unique_test_data_dir};
+    use crate::services::ClaudeSessionService;
+    use std::fs;
+    use std::path::Path;
+
+    fn write_session(dir: &Path, session_id: &str, cwd: &str) {
+        fs::write(
+            dir.join(format!("{session_id}.jsonl")),
+            format!(
+                r#"{{"type":"user","sessionId":"{session_id}","cwd":"{cwd}","timestamp":"2026-04-07T01:00:00Z","message":{{"role":"user","content":"Hello"}}}}
+{{"type":"assistant","sessionId":"{session_id}","cwd":"{cwd}/src","timestamp":"2026-04-07T01:01:00Z","message":{{"role":"assistant","content":[{{"type":"text","text":"Hi"}}]}}}}
+"#
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn migrate_project_previews_then_merges_into_new_path() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_test_data_dir("claude-migration-merge");
+        let old_dir = temp_home.join(".claude/projects/-work-foo");
+        let new_dir = temp_home.join(".claude/projects/-src-foo");
+        fs::create_dir_all(&old_dir).unwrap();
+        fs::create_dir_all(&new_dir).unwrap();
+
+        write_session(&old_dir, "old-session", "/work/foo");
+        write_session(&new_dir, "new-session", "/src/foo");
+        fs::write(
+            old_dir.join("sessions-index.json"),
+            r#"{
+  "version": 1,
+  "originalPath": "/work/foo",
+  "entries": [
+    {
+      "sessionId": "old-session",
+      "fullPath": "/home/me/.claude/projects/-work-foo/old-session.jsonl",
+      "summary": "Old work",
+      "projectPath": "/work/foo",
+      "isSidechain": false
+    }
+  ]
+}"#,
+        )
+        .unwrap();
+
+        std::env::set_var("HOME", &temp_home);
+
+        let preview = ClaudeMigrationService::migrate_project("/work/foo", "/src/foo/", true);
+        let untouched = fs::read_to_string(old_dir.join("old-session.jsonl")).unwrap();
+        let report = ClaudeMigrationService::migrate_project("/work/foo", "/src/foo", false);
+        let sessions = ClaudeSessionService::list_sessions_for_project("/src/foo", None);
+
+        std::env::remove_var("HOME");
+
+        let preview = preview.unwrap();
+        assert!(preview.dry_run);
+        assert!(preview.merged);
+        assert_eq!(preview.session_ids, vec!["old-session".to_string()]);
+        assert_eq!(preview.rewritten_cwd_count, 2);
+        assert!(untouched.contains("\"cwd\":\"/work/foo\""));
+
+        let report = report.unwrap();
+        assert!(!report.dry_run);
+        assert!(!old_dir.exists());
+
+        let moved = fs::read_to_string(new_dir.join("old-session.jsonl")).unwrap();
+        assert!(moved.contains("\"cwd\":\"/src/foo\""));
+        assert!(moved.contains("\"cwd\":\"/src/foo/src\""));
+        assert!(!moved.contains("/work/foo"));
+
+        let index = fs::read_to_string(new_dir.join("sessions-index.json")).unwrap();
+        assert!(index.contains("\"originalPath\": \"/src/foo\""));
+        assert!(index.contains("\"projectPath\": \"/src/foo\""));
+        assert!(index.contains(&format!(
+            "\"fullPath\": \"{}\"",
+            new_dir.join("old-session.jsonl").display()
+        )));
+
+        let session_ids: Vec<String> = sessions
+            .unwrap()
+            .into_iter()
+            .map(|session| session.session_id)
+            .collect();
+        assert_eq!(session_ids.len(), 2);
+        assert!(session_ids.contains(&"old-session".to_string()));
+        assert!(session_ids.contains(&"new-session".to_string()));
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn migrate_project_refuses_conflicting_session_files() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_test_data_dir("claude-migration-conflict");
+        let old_dir = temp_home.join(".claude/projects/-work-bar");
+        let new_dir = temp_home.join(".claude/projects/-src-bar");
+        fs::create_dir_all(&old_dir).unwrap();
+        fs::create_dir_all(&new_dir).unwrap();
+
+        write_session(&old_dir, "shared-session", "/work/bar");
+        write_session(&new_dir, "shared-session", "/src/bar");
+
+        std::env::set_var("HOME", &temp_home);
+
+        let preview = ClaudeMigrationService::migrate_project("/work/bar", "/src/bar", true);
+        let result = ClaudeMigrationService::migrate_project("/work/bar", "/src/bar", false);
+
+        std::env::remove_var("HOME");
+
+        assert_eq!(
+            preview.unwrap().conflicts,
+            vec!["shared-session.jsonl".to_string()]
+        );
+        assert!(result.unwrap_err().contains("shared-session.jsonl"));
+        assert!(old_dir.join("shared-session.jsonl").exists());
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn migrate_project_indexes_moved_sessions_into_existing_target_index() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_test_data_dir("claude-migration-target-index");
+        let old_dir = temp_home.join(".claude/projects/-work-baz");
+        let new_dir = temp_home.join(".claude/projects/-src-baz");
+        fs::create_dir_all(&old_dir).unwrap();
+        fs::create_dir_all(&new_dir).unwrap();
+
+        write_session(&old_dir, "old-session", "/work/baz");
+        write_session(&new_dir, "new-session", "/src/baz");
+        fs::write(
+            new_dir.join("sessions-index.json"),
+            format!(
+                r#"{{
+  "version": 1,
+  "originalPath": "/src/baz",
+  "entries": [
+    {{
+      "sessionId": "new-session",
+      "fullPath": "{}",
+      "summary": "New work",
+      "projectPath": "/src/baz",
+      "isSidechain": false
+    }}
+  ]
+}}"#,
+                new_dir.join("new-session.jsonl").display()
+            ),
+        )
+        .unwrap();
+
+        std::env::set_var("HOME", &temp_home);
+
+        let report = ClaudeMigrationService::migrate_project("/work/baz", "/src/baz", false);
+        let sessions = ClaudeSessionService::list_sessions_for_project("/src/baz", None);
+
+        std::env::remove_var("HOME");
+
+        assert!(report.unwrap().merged);
+        let index = fs::read_to_string(new_dir.join("sessions-index.json")).unwrap();
+        assert!(index.contains("\"summary\": \"New work\""));
+        assert!(index.contains(&format!(
+            "\"fullPath\": \"{}\"",
+            new_dir.join("old-session.jsonl").display()
+        )));
+
+        let mut session_ids: Vec<String> = sessions
+            .unwrap()
+            .into_iter()
+            .map(|session| session.session_id)
+            .collect();
+        session_ids.sort();
+        assert_eq!(session_ids, vec!["new-session", "old-session"]);
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+}
@@ -0,0 +1,290 @@
+use crate::models::claude_session::{ClaudeSearchHit, JsonlEntry};
+use crate::services::ClaudeSessionService;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+const SNIPPET_CONTEXT_CHARS: usize = 80;
+const PHRASE_MATCH_BONUS: u32 = 5;
+
+pub struct ClaudeSearchService;
+
+impl ClaudeSearchService {
+    /// Full-text search over user and assistant messages of every Claude Code session.
+    /// Every query term must appear in a message for it to count as a hit.
+    /// Hits are ranked by term frequency, with a bonus when the whole query appears verbatim.
+    pub fn search(
+        query: &str,
+        project_path: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<ClaudeSearchHit>, String> {
+        let normalized_query = query.trim().to_lowercase();
+        let terms = Self::tokenize_query(&normalized_query);
+        if terms.is_empty() {
+            return Err("Search query cannot be empty".to_string());
+        }
+
+        let project_dirs = ClaudeSessionService::collect_project_dirs(project_path)?;
+
+        let mut hits = Vec::new();
+        for (project_dir, original_path) in project_dirs {
+            let entries = match fs::read_dir(&project_dir) {
+                Ok(entries) => entries,
+                Err(error) => {
+                    log::debug!(
+                        "Skipping unreadable Claude project dir {}: {}",
+                        project_dir.display(),
+                        error
+                    );
+                    continue;
+                }
+            };
+
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                    continue;
+                }
+
+                let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+
+                // Skip agent/subagent files
+                if session_id.starts_with("agent-") {
+                    continue;
+                }
+
+                if let Err(error) = Self::search_jsonl_file(
+                    &path,
+                    session_id,
+                    &original_path,
+                    &normalized_query,
+                    &terms,
+                    &mut hits,
+                ) {
+                    log::debug!("Skipping Claude session {}: {}", path.display(), error);
+                }
+            }
+        }
+
+        hits.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| b.timestamp.cmp(&a.timestamp))
+        });
+        hits.truncate(limit.unwrap_or(DEFAULT_SEARCH_LIMIT));
+
+        Ok(hits)
+    }
+
+    fn search_jsonl_file(
+        path: &Path,
+        session_id: &str,
+        project_path: &str,
+        normalized_query: &str,
+        terms: &[String],
+        hits: &mut Vec<ClaudeSearchHit>,
+    ) -> Result<(), String> {
+        let file = fs::File::open(path).map_err(|e| format!("Failed to open JSONL file: {}", e))?;
+        let reader = BufReader::new(file);
+
+        let mut message_index: u32 = 0;
+
+        for line in reader.lines() {
+            let line = match line {
+                Ok(l) => l,
+                Err(_) => continue,
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let entry: JsonlEntry = match serde_json::from_str(&line) {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+
+            let role = match entry.entry_type.as_deref() {
+                Some(role @ ("user" | "assistant")) => role.to_string(),
+                _ => continue,
+            };
+
+            let current_index = message_index;
+            message_index += 1;
+
+            let Some(content) = entry.message.as_ref().and_then(|msg| msg.content.as_ref()) else {
+                continue;
+            };
+
+            let text = ClaudeSessionService::extract_text_from_json_value(content);
+            let Some((score, first_match)) = Self::score_text(&text, normalized_query, terms)
+            else {
+                continue;
+            };
+
+            hits.push(ClaudeSearchHit {
+                session_id: session_id.to_string(),
+                project_path: project_path.to_string(),
+                message_index: current_index,
+                role,
+                timestamp: entry.timestamp.unwrap_or_default(),
+                snippet: Self::build_snippet(&text, first_match),
+                score,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn tokenize_query(normalized_query: &str) -> Vec<String> {
+        let mut terms: Vec<String> = Vec::new();
+        for term in normalized_query.split_whitespace() {
+            if !terms.iter().any(|existing| existing == term) {
+                terms.push(term.to_string());
+            }
+        }
+        terms
+    }
+
+    /// Returns the match score and the char offset of the earliest match in `text`,
+    /// or `None` when any query term is missing from the text.
+    fn score_text(text: &str, normalized_query: &str, terms: &[String]) -> Option<(u32, usize)> {
+        // Lowercasing can turn one char into several ("İ" -> "i̇"), so remember which
+        // char of `text` each lowercase char came from.
+        let mut haystack = String::with_capacity(text.len());
+        let mut source_chars = Vec::with_capacity(text.len());
+        for (index, c) in text.chars().enumerate() {
+            for lower in c.to_lowercase() {
+                haystack.push(lower);
+                source_chars.push(index);
+            }
+        }
+        let mut score: u32 = 0;
+        let mut first_match_byte = usize::MAX;
+
+        for term in terms {
+            let mut occurrences: u32 = 0;
+            for (position, _) in haystack.match_indices(term.as_str()) {
+                occurrences += 1;
+                first_match_byte = first_match_byte.min(position);
+            }
+
+            if occurrences == 0 {
+                return None;
+            }
+
+            score += occurrences;
+        }
+
+        if terms.len() > 1 && haystack.contains(normalized_query) {
+            score += PHRASE_MATCH_BONUS;
+        }
+
+        let first_match = source_chars[haystack[..first_match_byte].chars().count()];
+        Some((score, first_match))
+    }
+
+    fn build_snippet(text: &str, first_match: usize) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let anchor = first_match.min(chars.len());
+        let start = anchor.saturating_sub(SNIPPET_CONTEXT_CHARS);
+        let end = (anchor + SNIPPET_CONTEXT_CHARS).min(chars.len());
+
+        let body = chars[start..end]
+            .iter()
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let prefix = if start > 0 { "…" } else { "" };
+        let suffix = if end < chars.len() { "…" } else { "" };
+
+        format!("{}{}{}", prefix, body, suffix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClaudeSearchService;
+    use crate::services::storage_service::{storage_test_env_lock, unique_test_data_dir};
+    use std::fs;
+
+    #[test]
+    fn search_ranks_hits_across_projects_by_term_frequency() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_test_data_dir("claude-search-ranked");
+        let alpha_dir = temp_home.join(".claude/projects/-Users-mannix-Project-Alpha");
+        let beta_dir = temp_home.join(".claude/projects/-Users-mannix-Project-Beta");
+        fs::create_dir_all(&alpha_dir).unwrap();
+        fs::create_dir_all(&beta_dir).unwrap();
+
+        fs::write(
+            alpha_dir.join("alpha-session.jsonl"),
+            r#"{"type":"user","timestamp":"2026-04-07T01:00:00Z","message":{"role":"user","content":"Why does the migration fail on startup?"}}
+{"type":"assistant","timestamp":"2026-04-07T01:01:00Z","message":{"role":"assistant","content":[{"type":"text","text":"The migration fails because the migration table is locked."}]}}
+"#,
+        )
+        .unwrap();
+        fs::write(
+            beta_dir.join("beta-session.jsonl"),
+            r#"{"type":"user","timestamp":"2026-04-08T01:00:00Z","message":{"role":"user","content":"Unrelated question about CSS"}}
+{"type":"assistant","timestamp":"2026-04-08T01:01:00Z","message":{"role":"assistant","content":[{"type":"text","text":"Run the migration again after fixing the selector."}]}}
+"#,
+        )
+        .unwrap();
+
+        std::env::set_var("HOME", &temp_home);
+
+        let hits = ClaudeSearchService::search("Migration", None, None).unwrap();
+        let scoped =
+            ClaudeSearchService::search("migration", Some("/Users/mannix/Project/Beta"), None)
+                .unwrap();
+        let missing = ClaudeSearchService::search("migration selector locked", None, None).unwrap();
+
+        std::env::remove_var("HOME");
+
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].session_id, "alpha-session");
+        assert_eq!(hits[0].message_index, 1);
+        assert_eq!(hits[0].role, "assistant");
+        assert_eq!(hits[0].project_path, "/Users/mannix/Project/Alpha");
+        assert!(hits[0].snippet.contains("migration table is locked"));
+
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(scoped[0].session_id, "beta-session");
+        assert!(missing.is_empty());
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn build_snippet_trims_long_text_around_first_match() {
+        let text = format!("{} needle {}", "a ".repeat(200), "b ".repeat(200));
+        let (_, first_match) =
+            ClaudeSearchService::score_text(&text, "needle", &["needle".to_string()]).unwrap();
+
+        let snippet = ClaudeSearchService::build_snippet(&text, first_match);
+
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.contains("needle"));
+    }
+
+    #[test]
+    fn score_text_reports_offsets_in_the_original_text() {
+        // "İ" lowercases to two chars, which must not shift the match position.
+        let text = format!("{} needle {}", "İ".repeat(150), "b ".repeat(200));
+        let (_, first_match) =
+            ClaudeSearchService::score_text(&text, "needle", &["needle".to_string()]).unwrap();
+
+        let matched: String = text.chars().skip(first_match).take(6).collect();
+        assert_eq!(matched, "needle");
+
+        let snippet = ClaudeSearchService::build_snippet(&text, first_match);
+        assert!(snippet.contains("needle"));
+    }
+}
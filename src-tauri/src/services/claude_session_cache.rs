@@ -0,0 +1,212 @@
+use crate::models::ClaudeSession;
+use crate::services::storage_service::StorageService;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CACHE_VERSION: u32 = 2;
+
+/// Size and mtime of a file at the time it was parsed.
+/// A cached entry is only reused while both still match.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct FileFingerprint {
+    pub size: u64,
+    pub modified_nanos: u64,
+}
+
+impl FileFingerprint {
+    pub(crate) fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified_nanos = metadata
+            .modified()
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_nanos() as u64;
+
+        Some(Self {
+            size: metadata.len(),
+            modified_nanos,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedJsonlFile {
+    fingerprint: FileFingerprint,
+    /// `None` when the file was parsed but holds no conversational messages.
+    session: Option<ClaudeSession>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CachedIndexEntry {
+    pub file_path: PathBuf,
+    pub session: ClaudeSession,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedIndexFile {
+    fingerprint: FileFingerprint,
+    entries: Vec<CachedIndexEntry>,
+}
+
+/// On-disk cache of parsed Claude session metadata, stored in the app data dir.
+///
+/// JSONL files and sessions-index.json files are keyed by absolute path and only
+/// reused while their fingerprint is unchanged. Project path -> project dir
+/// resolutions are remembered so lookups don't walk ~/.claude/projects.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct ClaudeSessionCache {
+    version: u32,
+    jsonl_files: HashMap<String, CachedJsonlFile>,
+    index_files: HashMap<String, CachedIndexFile>,
+    project_dirs: HashMap<String, PathBuf>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl ClaudeSessionCache {
+    pub(crate) fn load() -> Self {
+        let cache_path = StorageService::claude_session_cache_file();
+        if !cache_path.exists() {
+            return Self::empty();
+        }
+
+        match StorageService::read::<Self>(&cache_path) {
+            Ok(cache) if cache.version == CACHE_VERSION => cache,
+            Ok(_) => {
+                log::info!("Discarding Claude session cache with outdated version");
+                Self::empty()
+            }
+            Err(error) => {
+                log::warn!(
+                    "Failed to read Claude session cache at {}: {}",
+                    cache_path.display(),
+                    error
+                );
+                Self::empty()
+            }
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            version: CACHE_VERSION,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn save_if_dirty(&mut self) {
+        if !self.dirty {
+            return;
+        }
+
+        let cache_path = StorageService::claude_session_cache_file();
+        match StorageService::write(&cache_path, self) {
+            Ok(()) => self.dirty = false,
+            Err(error) => log::warn!(
+                "Failed to persist Claude session cache at {}: {}",
+                cache_path.display(),
+                error
+            ),
+        }
+    }
+
+    /// Returns the cached parse result for an unchanged JSONL file.
+    /// The outer `Option` is a cache miss, the inner one a file without messages.
+    pub(crate) fn jsonl_session(
+        &self,
+        path: &Path,
+        fingerprint: FileFingerprint,
+    ) -> Option<Option<ClaudeSession>> {
+        self.jsonl_files
+            .get(&Self::key(path))
+            .filter(|cached| cached.fingerprint == fingerprint)
+            .map(|cached| cached.session.clone())
+    }
+
+    pub(crate) fn store_jsonl_session(
+        &mut self,
+        path: &Path,
+        fingerprint: FileFingerprint,
+        session: Option<ClaudeSession>,
+    ) {
+        self.jsonl_files.insert(
+            Self::key(path),
+            CachedJsonlFile {
+                fingerprint,
+                session,
+            },
+        );
+        self.dirty = true;
+    }
+
+    /// Drops cached JSONL entries inside `project_dir` that were not seen in the latest scan.
+    pub(crate) fn retain_jsonl_files_in(&mut self, project_dir: &Path, seen: &HashSet<String>) {
+        let before = self.jsonl_files.len();
+        self.jsonl_files
+            .retain(|key, _| Path::new(key).parent() != Some(project_dir) || seen.contains(key));
+
+        if self.jsonl_files.len() != before {
+            self.dirty = true;
+        }
+    }
+
+    pub(crate) fn index_entries(
+        &self,
+        index_path: &Path,
+        fingerprint: FileFingerprint,
+    ) -> Option<Vec<CachedIndexEntry>> {
+        self.index_files
+            .get(&Self::key(index_path))
+            .filter(|cached| cached.fingerprint == fingerprint)
+            .map(|cached| cached.entries.clone())
+    }
+
+    pub(crate) fn store_index_entries(
+        &mut self,
+        index_path: &Path,
+        fingerprint: FileFingerprint,
+        entries: Vec<CachedIndexEntry>,
+    ) {
+        self.index_files.insert(
+            Self::key(index_path),
+            CachedIndexFile {
+                fingerprint,
+                entries,
+            },
+        );
+        self.dirty = true;
+    }
+
+    /// Returns the remembered Claude project dir for a project path,
+    /// as long as it still exists under `projects_dir`.
+    pub(crate) fn project_dir(&self, project_path: &str, projects_dir: &Path) -> Option<PathBuf> {
+        self.project_dirs
+            .get(project_path)
+            .filter(|dir| dir.starts_with(projects_dir) && dir.is_dir())
+            .cloned()
+    }
+
+    pub(crate) fn store_project_dir(&mut self, project_path: &str, project_dir: &Path) {
+        if self.project_dirs.get(project_path).map(PathBuf::as_path) == Some(project_dir) {
+            return;
+        }
+
+        self.project_dirs
+            .insert(project_path.to_string(), project_dir.to_path_buf());
+        self.dirty = true;
+    }
+
+    pub(crate) fn forget_project_dir(&mut self, project_path: &str) {
+        if self.project_dirs.remove(project_path).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    fn key(path: &Path) -> String {
+        path.to_string_lossy().to_string()
+    }
+}
@@ -1,12 +1,94 @@
-use crate::models::claude_session::{ClaudeSession, ClaudeSessionsIndex, JsonlEntry};
+use crate::models::claude_session::{
+    ClaudeSession, ClaudeSessionsIndex, ClaudeToolResult, ClaudeToolUse, ClaudeTranscriptCursor,
+    ClaudeTranscriptPage, ClaudeTranscriptTurn, JsonlEntry,
+};
+use crate::services::claude_session_cache::{
+    CachedIndexEntry, ClaudeSessionCache, FileFingerprint,
+};
+use serde::de::{self, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use std::collections::HashSet;
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 
+const DEFAULT_TRANSCRIPT_PAGE_SIZE: usize = 200;
+const MAX_TRANSCRIPT_PAGE_SIZE: usize = 1000;
+
+/// Metadata-only view of a JSONL line; message bodies are skipped without being materialized.
+#[derive(Deserialize)]
+struct JsonlEntryHeader {
+    #[serde(rename = "type")]
+    entry_type: Option<String>,
+    timestamp: Option<String>,
+    #[serde(rename = "isSidechain")]
+    is_sidechain: Option<bool>,
+    #[serde(rename = "gitBranch")]
+    git_branch: Option<String>,
+    message: Option<JsonlMessageHeader>,
+}
+
+#[derive(Deserialize)]
+struct JsonlMessageHeader {
+    #[serde(default)]
+    content: JsonlContentHeader,
+}
+
+/// What `message.content` holds, judged by block `type`; string content has no blocks.
+#[derive(Default)]
+struct JsonlContentHeader {
+    has_tool_result: bool,
+}
+
+#[derive(Deserialize)]
+struct JsonlContentBlockHeader {
+    #[serde(rename = "type")]
+    block_type: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for JsonlContentHeader {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ContentVisitor;
+
+        impl<'de> Visitor<'de> for ContentVisitor {
+            type Value = JsonlContentHeader;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("message content")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut content = JsonlContentHeader::default();
+                while let Some(block) = seq.next_element::<Option<JsonlContentBlockHeader>>()? {
+                    if block.and_then(|block| block.block_type).as_deref() == Some("tool_result") {
+                        content.has_tool_result = true;
+                    }
+                }
+                Ok(content)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+                Ok(JsonlContentHeader::default())
+            }
+
+            fn visit_str<E: de::Error>(self, _value: &str) -> Result<Self::Value, E> {
+                Ok(JsonlContentHeader::default())
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(JsonlContentHeader::default())
+            }
+        }
+
+        deserializer.deserialize_any(ContentVisitor)
+    }
+}
+
 pub struct ClaudeSessionService;
 
 impl ClaudeSessionService {
-    fn extract_text_from_json_value(value: &serde_json::Value) -> String {
+    pub(crate) fn extract_text_from_json_value(value: &serde_json::Value) -> String {
         match value {
             serde_json::Value::String(text) => text.clone(),
             serde_json::Value::Array(items) => items
@@ -106,7 +188,7 @@ impl ClaudeSessionService {
         output
     }
 
-    fn sanitize_session_label(raw: &str) -> String {
+    pub(crate) fn sanitize_session_label(raw: &str) -> String {
         let extracted = Self::try_extract_structured_session_text(raw);
         let normalized = Self::strip_html_comments(&extracted)
             .replace("\r\n", "\n")
@@ -141,39 +223,62 @@ impl ClaudeSessionService {
     }
 
     /// Get the Claude Code projects directory (~/.claude/projects/)
-    fn claude_projects_dir() -> Option<PathBuf> {
+    pub(crate) fn claude_projects_dir() -> Option<PathBuf> {
         dirs::home_dir().map(|h| h.join(".claude").join("projects"))
     }
 
     /// Convert a project path to the Claude Code directory name encoding.
-    /// e.g., "/Users/mannix/Project/MeFlow3" -> "-Users-mannix-Project-MeFlow3"
-    fn encode_project_path(project_path: &str) -> String {
-        project_path.replace('/', "-")
+    /// Every character other than an ASCII letter or digit becomes `-`, one per UTF-16 unit,
+    /// e.g., "/Users/mannix/my.app_v2" -> "-Users-mannix-my-app-v2"
+    pub(crate) fn encode_project_path(project_path: &str) -> String {
+        let mut encoded = String::with_capacity(project_path.len());
+        for c in project_path.chars() {
+            if c.is_ascii_alphanumeric() {
+                encoded.push(c);
+            } else {
+                encoded.extend(std::iter::repeat('-').take(c.len_utf16()));
+            }
+        }
+        encoded
     }
 
     /// List native Claude Code sessions for a given project path.
     /// Tries sessions-index.json first, falls back to scanning JSONL files.
+    /// Parsed metadata is served from the on-disk cache while files are unchanged.
     pub fn list_sessions_for_project(
         project_path: &str,
         limit: Option<usize>,
     ) -> Result<Vec<ClaudeSession>, String> {
         let normalized_project_path = Self::normalize_non_empty(project_path, "Project path")?;
+        let mut cache = ClaudeSessionCache::load();
+
+        let result = Self::list_sessions_with_cache(&mut cache, &normalized_project_path, limit);
+        cache.save_if_dirty();
+
+        result
+    }
 
-        let Some(project_dir) = Self::resolve_project_dir(&normalized_project_path)? else {
+    fn list_sessions_with_cache(
+        cache: &mut ClaudeSessionCache,
+        project_path: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<ClaudeSession>, String> {
+        let Some(project_dir) = Self::resolve_project_dir(cache, project_path)? else {
             return Ok(vec![]);
         };
 
         // Try sessions-index.json first
         let index_path = project_dir.join("sessions-index.json");
         let mut sessions = if index_path.exists() {
-            let indexed_sessions = Self::load_from_index(&index_path, &normalized_project_path)?;
+            let indexed_sessions =
+                Self::load_from_index_with_cache(cache, &index_path, project_path)?;
             if indexed_sessions.is_empty() {
-                Self::scan_jsonl_files(&project_dir, &normalized_project_path)?
+                Self::scan_jsonl_files(cache, &project_dir, project_path)?
             } else {
                 indexed_sessions
             }
         } else {
-            Self::scan_jsonl_files(&project_dir, &normalized_project_path)?
+            Self::scan_jsonl_files(cache, &project_dir, project_path)?
         };
 
         // Sort by modified time descending (most recent first)
@@ -187,7 +292,12 @@ impl ClaudeSessionService {
         Ok(sessions)
     }
 
-    fn resolve_project_dir(project_path: &str) -> Result<Option<PathBuf>, String> {
+    /// Finds the Claude project dir for `project_path`: the encoded dir name first, then a
+    /// remembered match, then a scan of every sessions-index.json `originalPath`.
+    pub(crate) fn resolve_project_dir(
+        cache: &mut ClaudeSessionCache,
+        project_path: &str,
+    ) -> Result<Option<PathBuf>, String> {
         let normalized_project_path = Self::normalize_non_empty(project_path, "Project path")?;
         let projects_dir = Self::claude_projects_dir()
             .ok_or_else(|| "Cannot determine home directory".to_string())?;
@@ -198,6 +308,16 @@ impl ClaudeSessionService {
             return Ok(Some(exact_match));
         }
 
+        if let Some(cached_dir) = cache.project_dir(&normalized_project_path, &projects_dir) {
+            // The dir may have been renamed or reused for another project since.
+            if Self::index_original_path(&cached_dir).as_deref()
+                == Some(normalized_project_path.as_str())
+            {
+                return Ok(Some(cached_dir));
+            }
+            cache.forget_project_dir(&normalized_project_path);
+        }
+
         let entries = fs::read_dir(&projects_dir)
             .map_err(|e| format!("Failed to read Claude projects directory: {}", e))?;
 
@@ -207,18 +327,10 @@ impl ClaudeSessionService {
                 continue;
             }
 
-            let index_path = candidate_dir.join("sessions-index.json");
-            if !index_path.exists() {
-                continue;
-            }
-
-            let original_path = fs::read_to_string(&index_path)
-                .ok()
-                .and_then(|content| serde_json::from_str::<ClaudeSessionsIndex>(&content).ok())
-                .and_then(|index| index.original_path)
-                .map(|value| value.trim().to_string());
-
-            if original_path.as_deref() == Some(normalized_project_path.as_str()) {
+            if Self::index_original_path(&candidate_dir).as_deref()
+                == Some(normalized_project_path.as_str())
+            {
+                cache.store_project_dir(&normalized_project_path, &candidate_dir);
                 return Ok(Some(candidate_dir));
             }
         }
@@ -226,11 +338,83 @@ impl ClaudeSessionService {
         Ok(None)
     }
 
+    fn index_original_path(project_dir: &Path) -> Option<String> {
+        let content = fs::read_to_string(project_dir.join("sessions-index.json")).ok()?;
+        serde_json::from_str::<ClaudeSessionsIndex>(&content)
+            .ok()?
+            .original_path
+            .map(|value| value.trim().to_string())
+    }
+
+    fn load_from_index_with_cache(
+        cache: &mut ClaudeSessionCache,
+        index_path: &Path,
+        project_path: &str,
+    ) -> Result<Vec<ClaudeSession>, String> {
+        let Some(fingerprint) = FileFingerprint::of(index_path) else {
+            return Self::load_from_index(index_path, project_path);
+        };
+
+        let mut entries = match cache.index_entries(index_path, fingerprint) {
+            Some(entries) => entries,
+            None => {
+                let entries = Self::read_index_entries(index_path)?;
+                cache.store_index_entries(index_path, fingerprint, entries.clone());
+                entries
+            }
+        };
+        Self::fill_message_counts(cache, &mut entries);
+
+        Ok(Self::existing_index_sessions(entries, project_path))
+    }
+
     /// Load sessions from a sessions-index.json file
     fn load_from_index(
         index_path: &Path,
         project_path: &str,
     ) -> Result<Vec<ClaudeSession>, String> {
+        let mut entries = Self::read_index_entries(index_path)?;
+        Self::fill_message_counts(&mut ClaudeSessionCache::default(), &mut entries);
+        Ok(Self::existing_index_sessions(entries, project_path))
+    }
+
+    /// sessions-index.json has no per-role counts and its totals, `modified` and branch go
+    /// stale while a session grows, so all of them come from the JSONL files. Done on every
+    /// listing rather than stored with the index entries, which are not rewritten meanwhile.
+    fn fill_message_counts(cache: &mut ClaudeSessionCache, entries: &mut [CachedIndexEntry]) {
+        for entry in entries {
+            let Some(fingerprint) = FileFingerprint::of(&entry.file_path) else {
+                continue;
+            };
+
+            let parsed = match cache.jsonl_session(&entry.file_path, fingerprint) {
+                Some(cached) => cached,
+                None => {
+                    let parsed = Self::parse_jsonl_file(
+                        &entry.file_path,
+                        &entry.session.session_id,
+                        &entry.session.project_path,
+                    )
+                    .ok();
+                    cache.store_jsonl_session(&entry.file_path, fingerprint, parsed.clone());
+                    parsed
+                }
+            };
+
+            if let Some(parsed) = parsed {
+                entry.session.user_message_count = parsed.user_message_count;
+                entry.session.assistant_message_count = parsed.assistant_message_count;
+                entry.session.tool_message_count = parsed.tool_message_count;
+                entry.session.message_count = parsed.message_count;
+                entry.session.modified = parsed.modified;
+                if !parsed.git_branch.is_empty() {
+                    entry.session.git_branch = parsed.git_branch;
+                }
+            }
+        }
+    }
+
+    fn read_index_entries(index_path: &Path) -> Result<Vec<CachedIndexEntry>, String> {
         let content = fs::read_to_string(index_path)
             .map_err(|e| format!("Failed to read sessions index: {}", e))?;
 
@@ -241,49 +425,71 @@ impl ClaudeSessionService {
             .parent()
             .ok_or_else(|| "Failed to resolve project directory for sessions index".to_string())?;
 
-        let sessions: Vec<ClaudeSession> = index
+        let entries = index
             .entries
             .into_iter()
             .filter(|entry| !entry.is_sidechain.unwrap_or(false))
-            .filter_map(|entry| {
-                let resolved_path = entry
+            .map(|entry| {
+                let file_path = entry
                     .full_path
                     .as_ref()
                     .map(PathBuf::from)
                     .unwrap_or_else(|| project_dir.join(format!("{}.jsonl", entry.session_id)));
 
-                if !resolved_path.exists() {
+                CachedIndexEntry {
+                    file_path,
+                    session: ClaudeSession {
+                        session_id: entry.session_id,
+                        project_path: String::new(),
+                        summary: Self::sanitize_session_label(&entry.summary.unwrap_or_default()),
+                        first_prompt: Self::sanitize_session_label(
+                            &entry.first_prompt.unwrap_or_default(),
+                        ),
+                        message_count: entry.message_count.unwrap_or(0),
+                        user_message_count: 0,
+                        assistant_message_count: 0,
+                        tool_message_count: 0,
+                        created: entry.created.unwrap_or_default(),
+                        modified: entry.modified.unwrap_or_default(),
+                        git_branch: entry.git_branch.unwrap_or_default(),
+                        is_sidechain: false,
+                    },
+                }
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    fn existing_index_sessions(
+        entries: Vec<CachedIndexEntry>,
+        project_path: &str,
+    ) -> Vec<ClaudeSession> {
+        entries
+            .into_iter()
+            .filter_map(|entry| {
+                if !entry.file_path.exists() {
                     log::debug!(
                         "Skipping stale Claude session index entry {} because file does not exist: {}",
-                        entry.session_id,
-                        resolved_path.display()
+                        entry.session.session_id,
+                        entry.file_path.display()
                     );
                     return None;
                 }
 
                 Some(ClaudeSession {
-                    session_id: entry.session_id,
                     // Always bind to the currently requested project path.
                     // `projectPath` in sessions-index.json can be stale and break PTY cwd/resume.
                     project_path: project_path.to_string(),
-                    summary: Self::sanitize_session_label(&entry.summary.unwrap_or_default()),
-                    first_prompt: Self::sanitize_session_label(
-                        &entry.first_prompt.unwrap_or_default(),
-                    ),
-                    message_count: entry.message_count.unwrap_or(0),
-                    created: entry.created.unwrap_or_default(),
-                    modified: entry.modified.unwrap_or_default(),
-                    git_branch: entry.git_branch.unwrap_or_default(),
-                    is_sidechain: false,
+                    ..entry.session
                 })
             })
-            .collect();
-
-        Ok(sessions)
+            .collect()
     }
 
     /// Scan .jsonl files in the project directory and extract session info
     fn scan_jsonl_files(
+        cache: &mut ClaudeSessionCache,
         project_dir: &Path,
         project_path: &str,
     ) -> Result<Vec<ClaudeSession>, String> {
@@ -291,6 +497,7 @@ impl ClaudeSessionService {
             .map_err(|e| format!("Failed to read project directory: {}", e))?;
 
         let mut sessions = Vec::new();
+        let mut seen_files = HashSet::new();
 
         for entry in entries.flatten() {
             let path = entry.path();
@@ -309,16 +516,38 @@ impl ClaudeSessionService {
                 continue;
             }
 
-            if let Ok(session) = Self::parse_jsonl_file(&path, &file_stem, project_path) {
-                sessions.push(session);
+            let Some(fingerprint) = FileFingerprint::of(&path) else {
+                continue;
+            };
+            seen_files.insert(path.to_string_lossy().to_string());
+
+            let parsed = match cache.jsonl_session(&path, fingerprint) {
+                Some(cached) => cached,
+                None => {
+                    let parsed = Self::parse_jsonl_file(&path, &file_stem, project_path).ok();
+                    cache.store_jsonl_session(&path, fingerprint, parsed.clone());
+                    parsed
+                }
+            };
+
+            if let Some(session) = parsed {
+                sessions.push(ClaudeSession {
+                    project_path: project_path.to_string(),
+                    ..session
+                });
             }
         }
 
+        cache.retain_jsonl_files_in(project_dir, &seen_files);
+
         Ok(sessions)
     }
 
-    /// Parse a JSONL file to extract session metadata
-    fn parse_jsonl_file(
+    /// Parse a JSONL file to extract session metadata.
+    ///
+    /// The whole file is streamed once. Only the first user prompt is fully deserialized;
+    /// every other line goes through `JsonlEntryHeader`, which skips message bodies.
+    pub(crate) fn parse_jsonl_file(
         path: &Path,
         session_id: &str,
         project_path: &str,
@@ -340,71 +569,90 @@ impl ClaudeSessionService {
             })
             .unwrap_or_default();
 
-        let reader = BufReader::new(file);
+        let mut reader = BufReader::new(file);
 
         let mut first_prompt = String::new();
         let mut first_timestamp = String::new();
-        let mut last_timestamp = modified_time.clone();
+        let mut last_assistant_timestamp = String::new();
         let mut git_branch = String::new();
-        let mut message_count: u32 = 0;
+        let mut user_message_count: u32 = 0;
+        let mut assistant_message_count: u32 = 0;
+        let mut tool_message_count: u32 = 0;
         let mut is_sidechain = false;
+        let mut line_bytes = Vec::new();
+
+        loop {
+            line_bytes.clear();
+            match reader.read_until(b'\n', &mut line_bytes) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(_) => break,
+            }
 
-        // Only read first N lines to avoid parsing huge files
-        for line in reader.lines().take(50) {
-            let line = match line {
-                Ok(l) => l,
-                Err(_) => continue,
-            };
-
+            let line = String::from_utf8_lossy(&line_bytes);
             if line.trim().is_empty() {
                 continue;
             }
 
-            let entry: JsonlEntry = match serde_json::from_str(&line) {
-                Ok(e) => e,
+            let header: JsonlEntryHeader = match serde_json::from_str(&line) {
+                Ok(h) => h,
                 Err(_) => continue,
             };
 
-            if let Some(ref t) = entry.entry_type {
-                if t == "user" {
-                    message_count += 1;
-
-                    if message_count == 1 {
-                        // Extract first user prompt
-                        if let Some(ref msg) = entry.message {
-                            if let Some(ref content) = msg.content {
-                                first_prompt = Self::sanitize_session_label(
-                                    &Self::extract_text_from_json_value(content),
-                                )
-                                .chars()
-                                .take(200)
-                                .collect();
-                            }
-                        }
+            if let Some(branch) = header.git_branch.as_deref() {
+                if !branch.is_empty() {
+                    git_branch = branch.to_string();
+                }
+            }
 
-                        if let Some(ref ts) = entry.timestamp {
-                            first_timestamp = ts.clone();
-                        }
+            match header.entry_type.as_deref() {
+                Some("user") => {
+                    if let Some(sc) = header.is_sidechain {
+                        is_sidechain = sc;
+                    }
+
+                    // Tool results are sent back to Claude as user entries.
+                    if header
+                        .message
+                        .as_ref()
+                        .is_some_and(|message| message.content.has_tool_result)
+                    {
+                        tool_message_count += 1;
+                        continue;
+                    }
+
+                    user_message_count += 1;
+                    if user_message_count > 1 {
+                        continue;
                     }
 
-                    if let Some(ref branch) = entry.git_branch {
-                        if !branch.is_empty() {
-                            git_branch = branch.clone();
+                    // Extract first user prompt
+                    if let Ok(entry) = serde_json::from_str::<JsonlEntry>(&line) {
+                        if let Some(content) = entry.message.and_then(|msg| msg.content) {
+                            first_prompt = Self::sanitize_session_label(
+                                &Self::extract_text_from_json_value(&content),
+                            )
+                            .chars()
+                            .take(200)
+                            .collect();
                         }
                     }
 
-                    if let Some(sc) = entry.is_sidechain {
-                        is_sidechain = sc;
+                    if let Some(ts) = header.timestamp {
+                        first_timestamp = ts;
                     }
-                } else if t == "assistant" {
-                    message_count += 1;
-                    if let Some(ref ts) = entry.timestamp {
-                        last_timestamp = ts.clone();
+                }
+                Some("assistant") => {
+                    assistant_message_count += 1;
+                    if let Some(ts) = header.timestamp {
+                        last_assistant_timestamp = ts;
                     }
                 }
+                _ => {}
             }
         }
 
+        let message_count = user_message_count + assistant_message_count + tool_message_count;
         if message_count == 0 {
             return Err("JSONL file does not contain any conversational messages".to_string());
         }
@@ -415,8 +663,15 @@ impl ClaudeSessionService {
             summary: String::new(), // No summary available without index
             first_prompt,
             message_count,
+            user_message_count,
+            assistant_message_count,
+            tool_message_count,
             created: first_timestamp,
-            modified: last_timestamp,
+            modified: if last_assistant_timestamp.is_empty() {
+                modified_time
+            } else {
+                last_assistant_timestamp
+            },
             git_branch,
             is_sidechain,
         })
@@ -452,24 +707,63 @@ impl ClaudeSessionService {
                 continue;
             }
 
-            // Try to read sessions-index.json for the original path
-            let index_path = path.join("sessions-index.json");
-            let original_path = if index_path.exists() {
-                fs::read_to_string(&index_path)
-                    .ok()
-                    .and_then(|content| serde_json::from_str::<ClaudeSessionsIndex>(&content).ok())
-                    .and_then(|index| index.original_path)
-                    .unwrap_or_else(|| Self::decode_project_path(&dir_name))
-            } else {
-                Self::decode_project_path(&dir_name)
-            };
-
+            let original_path = Self::project_original_path(&path);
             projects.push((dir_name, original_path));
         }
 
         Ok(projects)
     }
 
+    /// Original project path of a Claude project dir: sessions-index.json's `originalPath`,
+    /// falling back to decoding the dir name.
+    pub(crate) fn project_original_path(project_dir: &Path) -> String {
+        let dir_name = project_dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+
+        // Try to read sessions-index.json for the original path
+        let index_path = project_dir.join("sessions-index.json");
+        if index_path.exists() {
+            fs::read_to_string(&index_path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<ClaudeSessionsIndex>(&content).ok())
+                .and_then(|index| index.original_path)
+                .unwrap_or_else(|| Self::decode_project_path(dir_name))
+        } else {
+            Self::decode_project_path(dir_name)
+        }
+    }
+
+    /// Claude project dirs paired with their original paths: the single dir for
+    /// `project_path` when given, otherwise every known project.
+    pub(crate) fn collect_project_dirs(
+        project_path: Option<&str>,
+    ) -> Result<Vec<(PathBuf, String)>, String> {
+        let projects_dir = Self::claude_projects_dir()
+            .ok_or_else(|| "Cannot determine home directory".to_string())?;
+
+        if !projects_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        if let Some(project_path) = project_path.filter(|value| !value.trim().is_empty()) {
+            let normalized_project_path = project_path.trim().to_string();
+            let mut cache = ClaudeSessionCache::load();
+            let project_dir = Self::resolve_project_dir(&mut cache, &normalized_project_path);
+            cache.save_if_dirty();
+
+            return Ok(project_dir?
+                .map(|dir| vec![(dir, normalized_project_path)])
+                .unwrap_or_default());
+        }
+
+        Ok(Self::list_claude_projects()?
+            .into_iter()
+            .map(|(dir_name, original_path)| (projects_dir.join(dir_name), original_path))
+            .collect())
+    }
+
     /// Rename a Claude Code session by updating sessions-index.json metadata.
     ///
     /// This keeps session name consistent between this app and Claude CLI resume list
@@ -483,7 +777,10 @@ impl ClaudeSessionService {
         let normalized_session_id = Self::normalize_non_empty(session_id, "Session id")?;
         let normalized_name = Self::normalize_non_empty(session_name, "Session name")?;
 
-        let project_dir = Self::resolve_project_dir(&normalized_project_path)?
+        let mut cache = ClaudeSessionCache::load();
+        let project_dir = Self::resolve_project_dir(&mut cache, &normalized_project_path);
+        cache.save_if_dirty();
+        let project_dir = project_dir?
             .ok_or_else(|| "Claude project directory not found for this project".to_string())?;
         let index_path = project_dir.join("sessions-index.json");
 
@@ -491,100 +788,459 @@ impl ClaudeSessionService {
             return Err("sessions-index.json not found for this project".to_string());
         }
 
-        let content = fs::read_to_string(&index_path)
-            .map_err(|e| format!("Failed to read sessions index: {}", e))?;
+        Self::update_sessions_index(&index_path, |entries| {
+            let entry = entries
+                .iter_mut()
+                .find(|entry| {
+                    entry
+                        .get("sessionId")
+                        .and_then(|value| value.as_str())
+                        .map(|value| value == normalized_session_id)
+                        .unwrap_or(false)
+                })
+                .ok_or_else(|| format!("Session not found in index: {}", normalized_session_id))?;
 
-        let mut index_json: serde_json::Value = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse sessions index: {}", e))?;
+            entry["summary"] = serde_json::Value::String(normalized_name.to_string());
+            Ok(())
+        })
+    }
 
-        let entries = index_json
-            .get_mut("entries")
-            .and_then(|value| value.as_array_mut())
-            .ok_or_else(|| "Invalid sessions-index.json format: entries missing".to_string())?;
+    /// Copy a session up to and including `message_index` into a new session id.
+    ///
+    /// Every `sessionId` in the copied lines is rewritten and the fork is registered in
+    /// sessions-index.json when the project has one, so Claude CLI can resume it directly.
+    pub fn fork_claude_session(
+        project_path: &str,
+        session_id: &str,
+        message_index: u32,
+    ) -> Result<ClaudeSession, String> {
+        let normalized_project_path = Self::normalize_non_empty(project_path, "Project path")?;
+        let normalized_session_id = Self::normalize_non_empty(session_id, "Session id")?;
+        let source_path =
+            Self::session_file_path(&normalized_project_path, &normalized_session_id)?;
+        let project_dir = source_path
+            .parent()
+            .ok_or_else(|| "Claude project directory not found for this project".to_string())?
+            .to_path_buf();
+
+        let fork_id = uuid::Uuid::new_v4().to_string();
+        let content = Self::copy_jsonl_until(
+            &source_path,
+            &normalized_session_id,
+            &fork_id,
+            message_index,
+        )?;
+        let fork_path = project_dir.join(format!("{}.jsonl", fork_id));
+        Self::write_file_atomically(&fork_path, &content)?;
+
+        let mut fork = match Self::parse_jsonl_file(&fork_path, &fork_id, &normalized_project_path)
+        {
+            Ok(fork) => fork,
+            Err(error) => {
+                let _ = fs::remove_file(&fork_path);
+                return Err(error);
+            }
+        };
+
+        let index_path = project_dir.join("sessions-index.json");
+        if index_path.exists() {
+            let source_label = Self::index_summary(&index_path, &normalized_session_id)
+                .unwrap_or_else(|| Self::sanitize_session_label(&fork.first_prompt));
+            fork.summary = format!("{} (fork)", source_label);
+
+            let entry = Self::index_entry_json(&fork, &fork_path);
+
+            if let Err(error) = Self::update_sessions_index(&index_path, |entries| {
+                entries.push(entry);
+                Ok(())
+            }) {
+                let _ = fs::remove_file(&fork_path);
+                return Err(error);
+            }
+        }
+
+        Ok(fork)
+    }
 
-        let mut found = false;
-        for entry in entries.iter_mut() {
-            let is_match = entry
-                .get("sessionId")
-                .and_then(|value| value.as_str())
-                .map(|value| value == normalized_session_id)
-                .unwrap_or(false);
+    /// Returns the source lines up to `message_index` with `sessionId` rewritten to `fork_id`.
+    ///
+    /// Claude writes one line per content block, so the copy is extended to the end of the
+    /// chosen message (lines sharing its `message.id`) and until every `tool_use` it copied
+    /// has its `tool_result`. Cutting anywhere else leaves a transcript `claude -r` rejects.
+    fn copy_jsonl_until(
+        path: &Path,
+        session_id: &str,
+        fork_id: &str,
+        message_index: u32,
+    ) -> Result<String, String> {
+        let file = fs::File::open(path).map_err(|e| format!("Failed to open JSONL file: {}", e))?;
+        let mut reader = BufReader::new(file);
+        let mut content = String::new();
+        let mut next_message_index: u32 = 0;
+        let mut last_message_id: Option<String> = None;
+        let mut pending_tool_uses: HashSet<String> = HashSet::new();
+        let mut line_bytes = Vec::new();
+
+        loop {
+            line_bytes.clear();
+            match reader.read_until(b'\n', &mut line_bytes) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => return Err(format!("Failed to read JSONL file: {}", e)),
+            }
 
-            if !is_match {
+            let Ok(mut value) = serde_json::from_slice::<serde_json::Value>(&line_bytes) else {
                 continue;
+            };
+
+            if matches!(
+                value.get("type").and_then(|t| t.as_str()),
+                Some("user" | "assistant")
+            ) {
+                let message = value.get("message");
+                let message_id = message
+                    .and_then(|message| message.get("id"))
+                    .and_then(|id| id.as_str())
+                    .map(str::to_string);
+                let continues_message = message_id.is_some() && message_id == last_message_id;
+
+                if next_message_index > message_index
+                    && pending_tool_uses.is_empty()
+                    && !continues_message
+                {
+                    break;
+                }
+                next_message_index += 1;
+                last_message_id = message_id;
+
+                let blocks = message
+                    .and_then(|message| message.get("content"))
+                    .and_then(|content| content.as_array());
+                for block in blocks.into_iter().flatten() {
+                    match block.get("type").and_then(|t| t.as_str()) {
+                        Some("tool_use") => {
+                            if let Some(id) = block.get("id").and_then(|id| id.as_str()) {
+                                pending_tool_uses.insert(id.to_string());
+                            }
+                        }
+                        Some("tool_result") => {
+                            if let Some(id) = block.get("tool_use_id").and_then(|id| id.as_str()) {
+                                pending_tool_uses.remove(id);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
             }
 
-            entry["summary"] = serde_json::Value::String(normalized_name.to_string());
-            found = true;
-            break;
+            Self::rewrite_session_id(&mut value, session_id, fork_id);
+            content.push_str(&value.to_string());
+            content.push('\n');
         }
 
-        if !found {
+        if next_message_index <= message_index {
             return Err(format!(
-                "Session not found in index: {}",
-                normalized_session_id
+                "Message index {} is out of range for session {}",
+                message_index, session_id
             ));
         }
 
-        let serialized = serde_json::to_string_pretty(&index_json)
-            .map_err(|e| format!("Failed to serialize sessions index: {}", e))?;
+        if !pending_tool_uses.is_empty() {
+            return Err(format!(
+                "Message index {} is inside a tool call that has not finished yet",
+                message_index
+            ));
+        }
+
+        Ok(content)
+    }
+
+    fn rewrite_session_id(value: &mut serde_json::Value, session_id: &str, fork_id: &str) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if key == "sessionId" && field.as_str() == Some(session_id) {
+                        *field = serde_json::Value::String(fork_id.to_string());
+                    } else {
+                        Self::rewrite_session_id(field, session_id, fork_id);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    Self::rewrite_session_id(item, session_id, fork_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// A sessions-index.json entry describing `session` stored at `file_path`.
+    pub(crate) fn index_entry_json(session: &ClaudeSession, file_path: &Path) -> serde_json::Value {
+        serde_json::json!({
+            "sessionId": session.session_id,
+            "fullPath": file_path.to_string_lossy(),
+            "fileMtime": FileFingerprint::of(file_path)
+                .map(|fingerprint| fingerprint.modified_nanos / 1_000_000)
+                .unwrap_or_default(),
+            "firstPrompt": session.first_prompt,
+            "summary": session.summary,
+            "messageCount": session.message_count,
+            "created": session.created,
+            "modified": session.modified,
+            "gitBranch": session.git_branch,
+            "projectPath": session.project_path,
+            "isSidechain": session.is_sidechain,
+        })
+    }
 
-        Self::write_file_atomically(&index_path, &serialized)?;
+    fn index_summary(index_path: &Path, session_id: &str) -> Option<String> {
+        let content = fs::read_to_string(index_path).ok()?;
+        let index: ClaudeSessionsIndex = serde_json::from_str(&content).ok()?;
 
-        Ok(())
+        index
+            .entries
+            .into_iter()
+            .find(|entry| entry.session_id == session_id)
+            .and_then(|entry| entry.summary)
+            .map(|summary| summary.trim().to_string())
+            .filter(|summary| !summary.is_empty())
     }
 
-    /// Delete a Claude Code session JSONL file for the given project path and session ID.
-    pub fn delete_claude_session(project_path: &str, session_id: &str) -> Result<(), String> {
-        let normalized_project_path = Self::normalize_non_empty(project_path, "Project path")?;
-        let normalized_session_id = Self::normalize_non_empty(session_id, "Session id")?;
+    /// Apply `update` to the entries of sessions-index.json and write it back atomically.
+    pub(crate) fn update_sessions_index<F>(index_path: &Path, update: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut Vec<serde_json::Value>) -> Result<(), String>,
+    {
+        let content = fs::read_to_string(index_path)
+            .map_err(|e| format!("Failed to read sessions index: {}", e))?;
 
-        let project_dir = Self::resolve_project_dir(&normalized_project_path)?
-            .ok_or_else(|| "Claude project directory not found for this project".to_string())?;
-        let jsonl_path = project_dir.join(format!("{}.jsonl", normalized_session_id));
+        let mut index_json: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse sessions index: {}", e))?;
 
-        if !jsonl_path.exists() {
-            return Err(format!("Session file not found: {}", normalized_session_id));
-        }
+        let entries = index_json
+            .get_mut("entries")
+            .and_then(|value| value.as_array_mut())
+            .ok_or_else(|| "Invalid sessions-index.json format: entries missing".to_string())?;
 
-        fs::remove_file(&jsonl_path)
-            .map_err(|e| format!("Failed to delete session file: {}", e))?;
+        update(entries)?;
 
-        Ok(())
+        let serialized = serde_json::to_string_pretty(&index_json)
+            .map_err(|e| format!("Failed to serialize sessions index: {}", e))?;
+
+        Self::write_file_atomically(index_path, &serialized)
     }
 
-    /// Decode the directory name back to a path (best effort)
-    /// e.g., "-Users-mannix-Project-MeFlow3" -> "/Users/mannix/Project/MeFlow3"
-    fn decode_project_path(encoded: &str) -> String {
-        if let Some(stripped) = encoded.strip_prefix('-') {
-            // Replace leading dash with / and subsequent dashes with /
-            format!("/{}", stripped.replace('-', "/"))
-        } else {
-            encoded.replace('-', "/")
-        }
+    /// Read a page of structured turns from a session transcript.
+    /// Pass the previous page's `next_cursor` to continue where it stopped.
+    pub fn read_transcript(
+        project_path: &str,
+        session_id: &str,
+        cursor: Option<ClaudeTranscriptCursor>,
+        limit: Option<usize>,
+    ) -> Result<ClaudeTranscriptPage, String> {
+        let normalized_session_id = Self::normalize_non_empty(session_id, "Session id")?;
+        let jsonl_path = Self::session_file_path(project_path, &normalized_session_id)?;
+
+        Self::read_transcript_page(
+            &jsonl_path,
+            &normalized_session_id,
+            cursor.unwrap_or_default(),
+            limit,
+        )
     }
 
-    fn normalize_non_empty(value: &str, field: &str) -> Result<String, String> {
-        let trimmed = value.trim();
-        if trimmed.is_empty() {
-            return Err(format!("{} cannot be empty", field));
+    pub(crate) fn read_transcript_page(
+        path: &Path,
+        session_id: &str,
+        cursor: ClaudeTranscriptCursor,
+        limit: Option<usize>,
+    ) -> Result<ClaudeTranscriptPage, String> {
+        let page_size = limit
+            .unwrap_or(DEFAULT_TRANSCRIPT_PAGE_SIZE)
+            .clamp(1, MAX_TRANSCRIPT_PAGE_SIZE);
+
+        let mut file =
+            fs::File::open(path).map_err(|e| format!("Failed to open JSONL file: {}", e))?;
+        let file_len = file
+            .metadata()
+            .map_err(|e| format!("Failed to get file metadata: {}", e))?
+            .len();
+
+        if cursor.byte_offset > file_len {
+            return Err("Transcript cursor is past the end of the session file".to_string());
         }
 
-        Ok(trimmed.to_string())
-    }
+        file.seek(SeekFrom::Start(cursor.byte_offset))
+            .map_err(|e| format!("Failed to seek JSONL file: {}", e))?;
+
+        let mut reader = BufReader::new(file);
+        let mut byte_offset = cursor.byte_offset;
+        let mut message_index = cursor.message_index;
+        let mut turns = Vec::new();
+        let mut line = Vec::new();
+
+        while turns.len() < page_size {
+            line.clear();
+            let read = reader
+                .read_until(b'\n', &mut line)
+                .map_err(|e| format!("Failed to read JSONL file: {}", e))?;
+            if read == 0 {
+                break;
+            }
+            // A live session may be mid-write; leave the partial line for the next page.
+            if !line.ends_with(b"\n") {
+                break;
+            }
+            byte_offset += read as u64;
 
-    fn write_file_atomically(path: &Path, content: &str) -> Result<(), String> {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|error| format!("Failed to prepare parent directory: {}", error))?;
+            if let Some(turn) =
+                Self::parse_transcript_turn(&String::from_utf8_lossy(&line), message_index)
+            {
+                message_index += 1;
+                turns.push(turn);
+            }
         }
 
-        let temp_path = Self::temp_path_for(path);
-        fs::write(&temp_path, content)
-            .map_err(|error| format!("Failed to write temporary sessions index: {}", error))?;
+        let next_cursor = (byte_offset < file_len).then_some(ClaudeTranscriptCursor {
+            byte_offset,
+            message_index,
+        });
 
-        match fs::rename(&temp_path, path) {
-            Ok(()) => Ok(()),
+        Ok(ClaudeTranscriptPage {
+            session_id: session_id.to_string(),
+            turns,
+            next_cursor,
+        })
+    }
+
+    fn parse_transcript_turn(line: &str, message_index: u32) -> Option<ClaudeTranscriptTurn> {
+        if line.trim().is_empty() {
+            return None;
+        }
+
+        let entry: JsonlEntry = serde_json::from_str(line).ok()?;
+        let role = match entry.entry_type.as_deref() {
+            Some(role @ ("user" | "assistant")) => role.to_string(),
+            _ => return None,
+        };
+
+        let mut text_parts = Vec::new();
+        let mut tool_uses = Vec::new();
+        let mut tool_results = Vec::new();
+
+        match entry.message.and_then(|msg| msg.content) {
+            Some(serde_json::Value::Array(blocks)) => {
+                for block in blocks {
+                    match block.get("type").and_then(|value| value.as_str()) {
+                        Some("tool_use") => tool_uses.push(ClaudeToolUse {
+                            id: Self::json_str_field(&block, "id"),
+                            name: Self::json_str_field(&block, "name"),
+                            input: block
+                                .get("input")
+                                .cloned()
+                                .unwrap_or(serde_json::Value::Null),
+                        }),
+                        Some("tool_result") => tool_results.push(ClaudeToolResult {
+                            tool_use_id: Self::json_str_field(&block, "tool_use_id"),
+                            content: block
+                                .get("content")
+                                .map(Self::extract_text_from_json_value)
+                                .unwrap_or_default(),
+                            is_error: block
+                                .get("is_error")
+                                .and_then(|value| value.as_bool())
+                                .unwrap_or(false),
+                        }),
+                        // Extended thinking is not part of the visible conversation.
+                        Some("thinking") | Some("redacted_thinking") => {}
+                        _ => {
+                            let text = Self::extract_text_from_json_value(&block);
+                            if !text.trim().is_empty() {
+                                text_parts.push(text);
+                            }
+                        }
+                    }
+                }
+            }
+            Some(content) => text_parts.push(Self::extract_text_from_json_value(&content)),
+            None => {}
+        }
+
+        Some(ClaudeTranscriptTurn {
+            message_index,
+            uuid: entry.uuid.unwrap_or_default(),
+            role,
+            timestamp: entry.timestamp.unwrap_or_default(),
+            is_sidechain: entry.is_sidechain.unwrap_or(false),
+            git_branch: entry.git_branch.unwrap_or_default(),
+            text: text_parts.join("\n\n"),
+            tool_uses,
+            tool_results,
+        })
+    }
+
+    fn json_str_field(value: &serde_json::Value, key: &str) -> String {
+        value
+            .get(key)
+            .and_then(|field| field.as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    pub(crate) fn session_file_path(
+        project_path: &str,
+        session_id: &str,
+    ) -> Result<PathBuf, String> {
+        let normalized_project_path = Self::normalize_non_empty(project_path, "Project path")?;
+        let normalized_session_id = Self::normalize_non_empty(session_id, "Session id")?;
+
+        let mut cache = ClaudeSessionCache::load();
+        let project_dir = Self::resolve_project_dir(&mut cache, &normalized_project_path);
+        cache.save_if_dirty();
+        let project_dir = project_dir?
+            .ok_or_else(|| "Claude project directory not found for this project".to_string())?;
+        let jsonl_path = project_dir.join(format!("{}.jsonl", normalized_session_id));
+
+        if !jsonl_path.exists() {
+            return Err(format!("Session file not found: {}", normalized_session_id));
+        }
+
+        Ok(jsonl_path)
+    }
+
+    /// Decode the directory name back to a path (best effort)
+    /// e.g., "-Users-mannix-Project-MeFlow3" -> "/Users/mannix/Project/MeFlow3"
+    fn decode_project_path(encoded: &str) -> String {
+        if let Some(stripped) = encoded.strip_prefix('-') {
+            // Replace leading dash with / and subsequent dashes with /
+            format!("/{}", stripped.replace('-', "/"))
+        } else {
+            encoded.replace('-', "/")
+        }
+    }
+
+    pub(crate) fn normalize_non_empty(value: &str, field: &str) -> Result<String, String> {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            return Err(format!("{} cannot be empty", field));
+        }
+
+        Ok(trimmed.to_string())
+    }
+
+    pub(crate) fn write_file_atomically(path: &Path, content: &str) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|error| format!("Failed to prepare parent directory: {}", error))?;
+        }
+
+        let temp_path = Self::temp_path_for(path);
+        fs::write(&temp_path, content)
+            .map_err(|error| format!("Failed to write temporary sessions index: {}", error))?;
+
+        match fs::rename(&temp_path, path) {
+            Ok(()) => Ok(()),
             Err(rename_error) => {
                 if path.exists() {
                     fs::remove_file(path)
@@ -622,7 +1278,10 @@ impl ClaudeSessionService {
 #[cfg(test)]
 mod tests {
     use super::ClaudeSessionService;
-    use crate::services::storage_service::storage_test_env_lock;
+    use crate::services::claude_session_cache::ClaudeSessionCache;
+    use crate::services::storage_service::{
+        storage_test_env_lock, StorageService, DATA_DIR_OVERRIDE_ENV,
+    };
     use std::fs;
     use std::path::PathBuf;
 
@@ -637,6 +1296,22 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn encode_project_path_matches_claude_directory_names() {
+        assert_eq!(
+            ClaudeSessionService::encode_project_path("/Users/mannix/Project/MeFlow3"),
+            "-Users-mannix-Project-MeFlow3"
+        );
+        assert_eq!(
+            ClaudeSessionService::encode_project_path("/home/me/my.app_v2/Some Dir"),
+            "-home-me-my-app-v2-Some-Dir"
+        );
+        assert_eq!(
+            ClaudeSessionService::encode_project_path("/tmp/caf\u{e9}-\u{1f600}"),
+            "-tmp-caf----"
+        );
+    }
+
     #[test]
     fn list_sessions_for_project_falls_back_to_index_original_path_match() {
         let _guard = storage_test_env_lock().lock().unwrap();
@@ -733,6 +1408,129 @@ mod tests {
         let _ = fs::remove_dir_all(temp_home);
     }
 
+    #[test]
+    fn fork_claude_session_copies_prefix_and_registers_index_entry() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_temp_home("fork-session");
+        let project_dir = temp_home.join(".claude/projects/-Users-mannix-Project-Fork");
+        fs::create_dir_all(&project_dir).unwrap();
+
+        let session_id = "source-session";
+        fs::write(
+            project_dir.join(format!("{session_id}.jsonl")),
+            r#"{"type":"summary","summary":"Fix login","leafUuid":"u2"}
+{"type":"user","uuid":"u1","sessionId":"source-session","timestamp":"2026-04-07T01:00:00Z","message":{"role":"user","content":"Fix the login bug"}}
+{"type":"assistant","uuid":"u2","parentUuid":"u1","sessionId":"source-session","timestamp":"2026-04-07T01:01:00Z","message":{"role":"assistant","content":[{"type":"text","text":"Try approach A"}]}}
+{"type":"user","uuid":"u3","parentUuid":"u2","sessionId":"source-session","timestamp":"2026-04-07T01:02:00Z","message":{"role":"user","content":"Do it"}}
+{"type":"assistant","uuid":"u4","parentUuid":"u3","sessionId":"source-session","timestamp":"2026-04-07T01:03:00Z","message":{"role":"assistant","content":[{"type":"text","text":"Done"}]}}
+"#,
+        )
+        .unwrap();
+        let index_path = project_dir.join("sessions-index.json");
+        fs::write(
+            &index_path,
+            r#"{
+  "version": 1,
+  "originalPath": "/Users/mannix/Project/Fork",
+  "entries": [
+    {
+      "sessionId": "source-session",
+      "summary": "Login bug",
+      "messageCount": 4,
+      "created": "2026-04-07T01:00:00Z",
+      "modified": "2026-04-07T01:03:00Z",
+      "projectPath": "/Users/mannix/Project/Fork",
+      "isSidechain": false
+    }
+  ]
+}"#,
+        )
+        .unwrap();
+
+        std::env::set_var("HOME", &temp_home);
+
+        let fork =
+            ClaudeSessionService::fork_claude_session("/Users/mannix/Project/Fork", session_id, 1)
+                .unwrap();
+        let out_of_range =
+            ClaudeSessionService::fork_claude_session("/Users/mannix/Project/Fork", session_id, 4);
+        let sessions =
+            ClaudeSessionService::list_sessions_for_project("/Users/mannix/Project/Fork", None)
+                .unwrap();
+
+        std::env::remove_var("HOME");
+
+        assert!(uuid::Uuid::parse_str(&fork.session_id).is_ok());
+        assert_eq!(fork.message_count, 2);
+        assert_eq!(fork.summary, "Login bug (fork)");
+        assert_eq!(fork.modified, "2026-04-07T01:01:00Z");
+        assert!(out_of_range.is_err());
+
+        let fork_content =
+            fs::read_to_string(project_dir.join(format!("{}.jsonl", fork.session_id))).unwrap();
+        assert_eq!(fork_content.lines().count(), 3);
+        assert!(!fork_content.contains("source-session"));
+        assert!(!fork_content.contains("Do it"));
+
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions
+            .iter()
+            .any(|session| session.session_id == fork.session_id));
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn fork_claude_session_keeps_multi_block_turns_and_tool_results_together() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_temp_home("fork-tool-turn");
+        let project_dir = temp_home.join(".claude/projects/-Users-mannix-Project-Fork");
+        fs::create_dir_all(&project_dir).unwrap();
+
+        let session_id = "source-session";
+        fs::write(
+            project_dir.join(format!("{session_id}.jsonl")),
+            r#"{"type":"user","uuid":"u1","sessionId":"source-session","message":{"role":"user","content":"Fix the login bug"}}
+{"type":"assistant","uuid":"a1","parentUuid":"u1","sessionId":"source-session","message":{"id":"msg_1","role":"assistant","content":[{"type":"text","text":"Reading the handler"}]}}
+{"type":"assistant","uuid":"a2","parentUuid":"a1","sessionId":"source-session","message":{"id":"msg_1","role":"assistant","content":[{"type":"tool_use","id":"toolu_1","name":"Read","input":{"file_path":"login.rs"}}]}}
+{"type":"user","uuid":"u2","parentUuid":"a2","sessionId":"source-session","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"fn login() {}"}]}}
+{"type":"assistant","uuid":"a3","parentUuid":"u2","sessionId":"source-session","message":{"id":"msg_2","role":"assistant","content":[{"type":"text","text":"Fixed it"}]}}
+{"type":"user","uuid":"u3","parentUuid":"a3","sessionId":"source-session","message":{"role":"user","content":"Now try approach B"}}
+{"type":"assistant","uuid":"a4","parentUuid":"u3","sessionId":"source-session","message":{"id":"msg_3","role":"assistant","content":[{"type":"tool_use","id":"toolu_2","name":"Bash","input":{"command":"make"}}]}}
+"#,
+        )
+        .unwrap();
+
+        std::env::set_var("HOME", &temp_home);
+
+        let mid_turn =
+            ClaudeSessionService::fork_claude_session("/Users/mannix/Project/Fork", session_id, 1)
+                .unwrap();
+        let prompt_only =
+            ClaudeSessionService::fork_claude_session("/Users/mannix/Project/Fork", session_id, 0)
+                .unwrap();
+        let unanswered =
+            ClaudeSessionService::fork_claude_session("/Users/mannix/Project/Fork", session_id, 6);
+
+        std::env::remove_var("HOME");
+
+        let mid_turn_content =
+            fs::read_to_string(project_dir.join(format!("{}.jsonl", mid_turn.session_id))).unwrap();
+        assert_eq!(mid_turn_content.lines().count(), 4);
+        assert!(mid_turn_content.contains("toolu_1"));
+        assert!(mid_turn_content.contains("fn login() {}"));
+        assert!(!mid_turn_content.contains("Fixed it"));
+
+        let prompt_only_content =
+            fs::read_to_string(project_dir.join(format!("{}.jsonl", prompt_only.session_id)))
+                .unwrap();
+        assert_eq!(prompt_only_content.lines().count(), 1);
+
+        assert!(unanswered.is_err());
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
     #[test]
     fn list_sessions_for_project_skips_snapshot_only_jsonl_files() {
         let _guard = storage_test_env_lock().lock().unwrap();
@@ -836,4 +1634,418 @@ mod tests {
 
         let _ = fs::remove_dir_all(temp_home);
     }
+
+    #[test]
+    fn list_sessions_for_project_reuses_cache_until_jsonl_changes() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_temp_home("session-cache");
+        let project_dir = temp_home.join(".claude/projects/-Users-mannix-Project-Cached");
+        fs::create_dir_all(&project_dir).unwrap();
+
+        let jsonl_path = project_dir.join("cached-session.jsonl");
+        fs::write(
+            &jsonl_path,
+            r#"{"type":"user","timestamp":"2026-04-07T01:00:00Z","message":{"role":"user","content":"Original prompt"}}
+"#,
+        )
+        .unwrap();
+
+        std::env::set_var("HOME", &temp_home);
+        std::env::set_var(DATA_DIR_OVERRIDE_ENV, temp_home.join("app-data"));
+
+        let first =
+            ClaudeSessionService::list_sessions_for_project("/Users/mannix/Project/Cached", None)
+                .unwrap();
+
+        // Tamper with the cached entry to prove the next listing is served from the cache.
+        let cache_path = StorageService::claude_session_cache_file();
+        let cache_content = fs::read_to_string(&cache_path).unwrap();
+        fs::write(
+            &cache_path,
+            cache_content.replace("Original prompt", "Cached prompt"),
+        )
+        .unwrap();
+
+        let cached =
+            ClaudeSessionService::list_sessions_for_project("/Users/mannix/Project/Cached", None)
+                .unwrap();
+
+        let mut appended = fs::read_to_string(&jsonl_path).unwrap();
+        appended.push_str(
+            r#"{"type":"assistant","timestamp":"2026-04-07T01:01:00Z","message":{"role":"assistant","content":"Reply"}}
+"#,
+        );
+        fs::write(&jsonl_path, appended).unwrap();
+
+        let refreshed =
+            ClaudeSessionService::list_sessions_for_project("/Users/mannix/Project/Cached", None)
+                .unwrap();
+
+        std::env::remove_var(DATA_DIR_OVERRIDE_ENV);
+        std::env::remove_var("HOME");
+
+        assert_eq!(first[0].first_prompt, "Original prompt");
+        assert_eq!(cached[0].first_prompt, "Cached prompt");
+        assert_eq!(refreshed[0].first_prompt, "Original prompt");
+        assert_eq!(refreshed[0].message_count, 2);
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn list_sessions_for_project_fills_role_counts_for_index_entries() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_temp_home("index-counts");
+        let project_dir = temp_home.join(".claude/projects/-Users-mannix-Project-Indexed");
+        fs::create_dir_all(&project_dir).unwrap();
+
+        let jsonl_path = project_dir.join("indexed-session.jsonl");
+        fs::write(
+            &jsonl_path,
+            r#"{"type":"user","timestamp":"2026-04-07T01:00:00Z","message":{"role":"user","content":"Run the tests"}}
+{"type":"assistant","timestamp":"2026-04-07T01:00:05Z","message":{"role":"assistant","content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"cargo test"}}]}}
+{"type":"user","timestamp":"2026-04-07T01:00:09Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"ok"}]}}
+"#,
+        )
+        .unwrap();
+        fs::write(
+            project_dir.join("sessions-index.json"),
+            r#"{"version":1,"originalPath":"/Users/mannix/Project/Indexed","entries":[{"sessionId":"indexed-session","summary":"Tests","messageCount":3,"created":"2026-04-07T01:00:00Z","modified":"2026-04-07T01:00:09Z","isSidechain":false}]}"#,
+        )
+        .unwrap();
+
+        std::env::set_var("HOME", &temp_home);
+        std::env::set_var(DATA_DIR_OVERRIDE_ENV, temp_home.join("app-data"));
+
+        let first =
+            ClaudeSessionService::list_sessions_for_project("/Users/mannix/Project/Indexed", None)
+                .unwrap();
+
+        // The index is left alone while the session grows; counts still follow the JSONL.
+        let mut appended = fs::read_to_string(&jsonl_path).unwrap();
+        appended.push_str(
+            r#"{"type":"assistant","timestamp":"2026-04-07T01:00:12Z","gitBranch":"feature/green","message":{"role":"assistant","content":"All green"}}
+"#,
+        );
+        fs::write(&jsonl_path, appended).unwrap();
+
+        let refreshed =
+            ClaudeSessionService::list_sessions_for_project("/Users/mannix/Project/Indexed", None)
+                .unwrap();
+
+        std::env::remove_var(DATA_DIR_OVERRIDE_ENV);
+        std::env::remove_var("HOME");
+
+        assert_eq!(first[0].summary, "Tests");
+        assert_eq!(
+            (
+                first[0].user_message_count,
+                first[0].assistant_message_count,
+                first[0].tool_message_count
+            ),
+            (1, 1, 1)
+        );
+        assert_eq!(refreshed[0].assistant_message_count, 2);
+        assert_eq!(refreshed[0].message_count, 4);
+        assert_eq!(refreshed[0].modified, "2026-04-07T01:00:12Z");
+        assert_eq!(refreshed[0].git_branch, "feature/green");
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn resolve_project_dir_remembers_original_path_match() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_temp_home("project-dir-cache");
+        let project_dir = temp_home.join(".claude/projects/-Users-mannix-Project-Legacy-dir");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(
+            project_dir.join("sessions-index.json"),
+            r#"{"version":1,"originalPath":"/Users/mannix/Project/Renamed_dir","entries":[]}"#,
+        )
+        .unwrap();
+
+        std::env::set_var("HOME", &temp_home);
+
+        let mut cache = ClaudeSessionCache::default();
+        let resolved = ClaudeSessionService::resolve_project_dir(
+            &mut cache,
+            "/Users/mannix/Project/Renamed_dir",
+        )
+        .unwrap();
+        let remembered = cache.project_dir(
+            "/Users/mannix/Project/Renamed_dir",
+            &temp_home.join(".claude/projects"),
+        );
+
+        std::env::remove_var("HOME");
+
+        assert_eq!(resolved.as_deref(), Some(project_dir.as_path()));
+        assert_eq!(remembered.as_deref(), Some(project_dir.as_path()));
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn resolve_project_dir_revalidates_a_remembered_match() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_temp_home("project-dir-stale");
+        let projects_dir = temp_home.join(".claude/projects");
+        let old_dir = projects_dir.join("-Users-mannix-Project-Legacy-dir");
+        let new_dir = projects_dir.join("-Users-mannix-Project-Moved-dir");
+        fs::create_dir_all(&old_dir).unwrap();
+        fs::create_dir_all(&new_dir).unwrap();
+        fs::write(
+            old_dir.join("sessions-index.json"),
+            r#"{"version":1,"originalPath":"/Users/mannix/Project/Renamed_dir","entries":[]}"#,
+        )
+        .unwrap();
+
+        std::env::set_var("HOME", &temp_home);
+
+        let mut cache = ClaudeSessionCache::default();
+        let first = ClaudeSessionService::resolve_project_dir(
+            &mut cache,
+            "/Users/mannix/Project/Renamed_dir",
+        )
+        .unwrap();
+
+        // The remembered dir now belongs to another project; the history lives elsewhere.
+        fs::write(
+            old_dir.join("sessions-index.json"),
+            r#"{"version":1,"originalPath":"/Users/mannix/Project/Other","entries":[]}"#,
+        )
+        .unwrap();
+        fs::write(
+            new_dir.join("sessions-index.json"),
+            r#"{"version":1,"originalPath":"/Users/mannix/Project/Renamed_dir","entries":[]}"#,
+        )
+        .unwrap();
+        let moved = ClaudeSessionService::resolve_project_dir(
+            &mut cache,
+            "/Users/mannix/Project/Renamed_dir",
+        )
+        .unwrap();
+
+        fs::remove_dir_all(&new_dir).unwrap();
+        let deleted = ClaudeSessionService::resolve_project_dir(
+            &mut cache,
+            "/Users/mannix/Project/Renamed_dir",
+        )
+        .unwrap();
+        let remembered = cache.project_dir("/Users/mannix/Project/Renamed_dir", &projects_dir);
+
+        std::env::remove_var("HOME");
+
+        assert_eq!(first.as_deref(), Some(old_dir.as_path()));
+        assert_eq!(moved.as_deref(), Some(new_dir.as_path()));
+        assert_eq!(deleted, None);
+        assert_eq!(remembered, None);
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn read_transcript_pages_structured_turns_with_cursor() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_temp_home("transcript");
+        let project_dir = temp_home.join(".claude/projects/-Users-mannix-Project-Viewer");
+        fs::create_dir_all(&project_dir).unwrap();
+
+        let jsonl_path = project_dir.join("viewer-session.jsonl");
+        fs::write(
+            &jsonl_path,
+            r#"{"type":"user","uuid":"u1","timestamp":"2026-04-07T01:00:00Z","message":{"role":"user","content":"List the files"}}
+{"type":"file-history-snapshot","messageId":"m1","snapshot":{}}
+{"type":"assistant","uuid":"a1","timestamp":"2026-04-07T01:00:05Z","message":{"role":"assistant","content":[{"type":"thinking","thinking":"hidden"},{"type":"text","text":"Listing now."},{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"ls"}}]}}
+{"type":"user","uuid":"u2","timestamp":"2026-04-07T01:00:06Z","isSidechain":true,"message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":[{"type":"text","text":"Cargo.toml"}],"is_error":false}]}}
+"#,
+        )
+        .unwrap();
+
+        let first_page = ClaudeSessionService::read_transcript_page(
+            &jsonl_path,
+            "viewer-session",
+            Default::default(),
+            Some(2),
+        )
+        .unwrap();
+        let second_page = ClaudeSessionService::read_transcript_page(
+            &jsonl_path,
+            "viewer-session",
+            first_page.next_cursor.unwrap(),
+            Some(2),
+        )
+        .unwrap();
+
+        assert_eq!(first_page.turns.len(), 2);
+        assert_eq!(first_page.turns[0].text, "List the files");
+        assert_eq!(first_page.turns[1].message_index, 1);
+        assert_eq!(first_page.turns[1].text, "Listing now.");
+        assert_eq!(first_page.turns[1].tool_uses[0].name, "Bash");
+        assert_eq!(first_page.turns[1].tool_uses[0].input["command"], "ls");
+
+        assert_eq!(second_page.turns.len(), 1);
+        assert_eq!(second_page.turns[0].message_index, 2);
+        assert_eq!(second_page.turns[0].uuid, "u2");
+        assert!(second_page.turns[0].is_sidechain);
+        assert_eq!(second_page.turns[0].tool_results[0].tool_use_id, "toolu_1");
+        assert_eq!(second_page.turns[0].tool_results[0].content, "Cargo.toml");
+        assert!(second_page.next_cursor.is_none());
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn read_transcript_page_leaves_a_partially_written_line_for_the_next_page() {
+        let temp_home = unique_temp_home("transcript-partial");
+        fs::create_dir_all(&temp_home).unwrap();
+        let jsonl_path = temp_home.join("live-session.jsonl");
+        let answer = r#"{"type":"assistant","uuid":"a1","message":{"role":"assistant","content":"Still typing"}}"#;
+        let (head, tail) = answer.split_at(40);
+        fs::write(
+            &jsonl_path,
+            format!(
+                "{}\n{}",
+                r#"{"type":"user","uuid":"u1","message":{"role":"user","content":"Hello"}}"#, head
+            ),
+        )
+        .unwrap();
+
+        let first_page = ClaudeSessionService::read_transcript_page(
+            &jsonl_path,
+            "live-session",
+            Default::default(),
+            None,
+        )
+        .unwrap();
+
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(&jsonl_path)
+            .unwrap();
+        std::io::Write::write_all(&mut file, format!("{}\n", tail).as_bytes()).unwrap();
+        drop(file);
+
+        let cursor = first_page.next_cursor.unwrap();
+        let second_page =
+            ClaudeSessionService::read_transcript_page(&jsonl_path, "live-session", cursor, None)
+                .unwrap();
+
+        assert_eq!(first_page.turns.len(), 1);
+        assert_eq!(cursor.message_index, 1);
+        assert_eq!(second_page.turns.len(), 1);
+        assert_eq!(second_page.turns[0].uuid, "a1");
+        assert_eq!(second_page.turns[0].message_index, 1);
+        assert!(second_page.next_cursor.is_none());
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    fn write_large_session_fixture(path: &std::path::Path, exchanges: usize) {
+        let mut content = String::new();
+        content.push_str(
+            r#"{"type":"file-history-snapshot","messageId":"m0","snapshot":{"trackedFileBackups":{}}}"#,
+        );
+        content.push('\n');
+
+        for index in 0..exchanges {
+            let branch = if index + 1 == exchanges {
+                "feature/final"
+            } else {
+                "main"
+            };
+            let minute = index % 60;
+            let hour = index / 60 % 24;
+
+            content.push_str(&format!(
+                r#"{{"type":"user","gitBranch":"{branch}","timestamp":"2026-04-07T{hour:02}:{minute:02}:00Z","message":{{"role":"user","content":"Prompt number {index} {padding}"}}}}"#,
+                padding = "x".repeat(512)
+            ));
+            content.push('\n');
+            content.push_str(&format!(
+                r#"{{"type":"assistant","gitBranch":"{branch}","timestamp":"2026-04-07T{hour:02}:{minute:02}:30Z","message":{{"role":"assistant","content":[{{"type":"tool_use","id":"toolu_{index}","name":"Bash","input":{{"command":"ls"}}}}]}}}}"#
+            ));
+            content.push('\n');
+            content.push_str(&format!(
+                r#"{{"type":"user","gitBranch":"{branch}","timestamp":"2026-04-07T{hour:02}:{minute:02}:40Z","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"toolu_{index}","content":"ok"}}]}}}}"#
+            ));
+            content.push('\n');
+        }
+
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn parse_jsonl_file_detects_tool_results_by_content_block_type() {
+        let temp_home = unique_temp_home("tool-result-blocks");
+        fs::create_dir_all(&temp_home).unwrap();
+        let jsonl_path = temp_home.join("tool-results.jsonl");
+        fs::write(
+            &jsonl_path,
+            r#"{"type":"user","timestamp":"2026-04-07T01:00:00Z","message":{"role":"user","content":[{"type":"text","text":"Why is this a tool result?"}],"meta":{"type":"tool_result"}}}
+{"type":"assistant","timestamp":"2026-04-07T01:00:05Z","message":{"role":"assistant","content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"ls"}}]}}
+{"type": "user", "timestamp": "2026-04-07T01:00:09Z", "message": {"role": "user", "content": [{"type": "tool_result", "tool_use_id": "toolu_1", "content": "ok"}]}}
+"#,
+        )
+        .unwrap();
+
+        let session =
+            ClaudeSessionService::parse_jsonl_file(&jsonl_path, "tool-results", "/tmp/tools")
+                .unwrap();
+
+        assert_eq!(session.user_message_count, 1);
+        assert_eq!(session.tool_message_count, 1);
+        assert_eq!(session.first_prompt, "Why is this a tool result?");
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn parse_jsonl_file_counts_every_turn_in_large_sessions() {
+        let temp_home = unique_temp_home("large-jsonl");
+        fs::create_dir_all(&temp_home).unwrap();
+        let jsonl_path = temp_home.join("large-session.jsonl");
+        write_large_session_fixture(&jsonl_path, 5_000);
+
+        let session =
+            ClaudeSessionService::parse_jsonl_file(&jsonl_path, "large-session", "/tmp/large")
+                .unwrap();
+
+        assert_eq!(session.user_message_count, 5_000);
+        assert_eq!(session.assistant_message_count, 5_000);
+        assert_eq!(session.tool_message_count, 5_000);
+        assert_eq!(session.message_count, 15_000);
+        assert!(session.first_prompt.starts_with("Prompt number 0 "));
+        assert_eq!(session.created, "2026-04-07T00:00:00Z");
+        // 4_999 exchanges in: hour 83 % 24 = 11, minute 19.
+        assert_eq!(session.modified, "2026-04-07T11:19:30Z");
+        assert_eq!(session.git_branch, "feature/final");
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn parse_jsonl_file_falls_back_to_file_mtime_without_assistant_turns() {
+        let temp_home = unique_temp_home("user-only-jsonl");
+        fs::create_dir_all(&temp_home).unwrap();
+        let jsonl_path = temp_home.join("user-only.jsonl");
+        fs::write(
+            &jsonl_path,
+            r#"{"type":"user","timestamp":"2026-04-07T01:00:00Z","message":{"role":"user","content":"Only a prompt"}}
+"#,
+        )
+        .unwrap();
+
+        let session =
+            ClaudeSessionService::parse_jsonl_file(&jsonl_path, "user-only", "/tmp/user-only")
+                .unwrap();
+
+        assert_eq!(session.message_count, 1);
+        assert_eq!(session.assistant_message_count, 0);
+        assert!(!session.modified.is_empty());
+        assert_ne!(session.modified, "2026-04-07T01:00:00Z");
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
 }
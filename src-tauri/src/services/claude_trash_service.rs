@@ -0,0 +1,405 @@
+use crate::models::claude_session::ClaudeTrashEntry;
+use crate::services::storage_service::StorageService;
+use crate::services::ClaudeSessionService;
+use chrono::{DateTime, Duration, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TRASH_ENTRY_FILE: &str = "entry.json";
+const TRASH_SESSION_FILE: &str = "session.jsonl";
+/// Per-session side directory Claude keeps next to the JSONL (subagent transcripts etc.)
+const TRASH_SESSION_DATA_DIR: &str = "session-data";
+
+pub struct ClaudeTrashService;
+
+impl ClaudeTrashService {
+    /// Move a session JSONL into the trash folder and drop it from sessions-index.json.
+    pub fn trash_session(project_path: &str, session_id: &str) -> Result<ClaudeTrashEntry, String> {
+        let normalized_project_path =
+            ClaudeSessionService::normalize_non_empty(project_path, "Project path")?;
+        let normalized_session_id =
+            ClaudeSessionService::normalize_non_empty(session_id, "Session id")?;
+        let jsonl_path = ClaudeSessionService::session_file_path(
+            &normalized_project_path,
+            &normalized_session_id,
+        )?;
+        let project_dir = jsonl_path
+            .parent()
+            .ok_or_else(|| "Claude project directory not found for this project".to_string())?
+            .to_path_buf();
+        let index_path = project_dir.join("sessions-index.json");
+
+        let index_entry = Self::find_index_entry(&index_path, &normalized_session_id);
+        let parsed = ClaudeSessionService::parse_jsonl_file(
+            &jsonl_path,
+            &normalized_session_id,
+            &normalized_project_path,
+        )
+        .ok();
+
+        let entry = ClaudeTrashEntry {
+            trash_id: uuid::Uuid::new_v4().to_string(),
+            session_id: normalized_session_id.clone(),
+            project_path: normalized_project_path,
+            original_file_path: jsonl_path.to_string_lossy().to_string(),
+            deleted_at: Utc::now().to_rfc3339(),
+            summary: index_entry
+                .as_ref()
+                .and_then(|value| value.get("summary"))
+                .and_then(|value| value.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            first_prompt: parsed
+                .as_ref()
+                .map(|session| session.first_prompt.clone())
+                .unwrap_or_default(),
+            message_count: parsed
+                .as_ref()
+                .map(|session| session.message_count)
+                .unwrap_or_default(),
+            index_entry,
+        };
+
+        let trash_dir = Self::trash_dir().join(&entry.trash_id);
+        fs::create_dir_all(&trash_dir)
+            .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+
+        if let Err(error) = StorageService::write(&trash_dir.join(TRASH_ENTRY_FILE), &entry)
+            .map_err(|e| format!("Failed to write trash metadata: {}", e))
+            .and_then(|_| Self::move_path(&jsonl_path, &trash_dir.join(TRASH_SESSION_FILE)))
+        {
+            let _ = fs::remove_dir_all(&trash_dir);
+            return Err(error);
+        }
+
+        let session_data_dir = project_dir.join(&normalized_session_id);
+        if session_data_dir.is_dir() {
+            if let Err(error) =
+                Self::move_path(&session_data_dir, &trash_dir.join(TRASH_SESSION_DATA_DIR))
+            {
+                log::warn!(
+                    "Left session data {} in place: {}",
+                    session_data_dir.display(),
+                    error
+                );
+            }
+        }
+
+        if entry.index_entry.is_some() {
+            if let Err(error) =
+                ClaudeSessionService::update_sessions_index(&index_path, |entries| {
+                    entries.retain(|value| !Self::is_session_entry(value, &normalized_session_id));
+                    Ok(())
+                })
+            {
+                log::warn!(
+                    "Failed to remove {} from sessions index: {}",
+                    normalized_session_id,
+                    error
+                );
+            }
+        }
+
+        Ok(entry)
+    }
+
+    /// Trashed sessions, most recently deleted first.
+    pub fn list_trash() -> Result<Vec<ClaudeTrashEntry>, String> {
+        let trash_root = Self::trash_dir();
+        if !trash_root.exists() {
+            return Ok(vec![]);
+        }
+
+        let dirs = fs::read_dir(&trash_root)
+            .map_err(|e| format!("Failed to read trash directory: {}", e))?;
+
+        let mut entries = Vec::new();
+        for dir in dirs.flatten() {
+            let entry_path = dir.path().join(TRASH_ENTRY_FILE);
+            match StorageService::read::<ClaudeTrashEntry>(&entry_path) {
+                Ok(entry) => entries.push(entry),
+                Err(error) => log::debug!(
+                    "Skipping unreadable trash entry {}: {}",
+                    entry_path.display(),
+                    error
+                ),
+            }
+        }
+
+        entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+        Ok(entries)
+    }
+
+    /// Put a trashed session back where it was deleted from and re-register it in the index.
+    pub fn restore_session(trash_id: &str) -> Result<ClaudeTrashEntry, String> {
+        let trash_dir = Self::entry_dir(trash_id)?;
+        let entry = StorageService::read::<ClaudeTrashEntry>(&trash_dir.join(TRASH_ENTRY_FILE))
+            .map_err(|e| format!("Failed to read trash metadata: {}", e))?;
+
+        let original_path = PathBuf::from(&entry.original_file_path);
+        if original_path.exists() {
+            return Err(format!(
+                "A session file already exists at {}",
+                original_path.display()
+            ));
+        }
+
+        let project_dir = original_path
+            .parent()
+            .ok_or_else(|| "Invalid original session path".to_string())?;
+        fs::create_dir_all(project_dir)
+            .map_err(|e| format!("Failed to recreate Claude project directory: {}", e))?;
+
+        Self::move_path(&trash_dir.join(TRASH_SESSION_FILE), &original_path)?;
+
+        let trashed_data_dir = trash_dir.join(TRASH_SESSION_DATA_DIR);
+        let session_data_dir = project_dir.join(&entry.session_id);
+        if trashed_data_dir.is_dir() && !session_data_dir.exists() {
+            if let Err(error) = Self::move_path(&trashed_data_dir, &session_data_dir) {
+                log::warn!(
+                    "Failed to restore session data for {}: {}",
+                    entry.session_id,
+                    error
+                );
+            }
+        }
+
+        let index_path = project_dir.join("sessions-index.json");
+        if let Some(index_entry) = entry.index_entry.clone() {
+            if index_path.exists() {
+                ClaudeSessionService::update_sessions_index(&index_path, |entries| {
+                    if !entries
+                        .iter()
+                        .any(|value| Self::is_session_entry(value, &entry.session_id))
+                    {
+                        entries.push(index_entry);
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+
+        if let Err(error) = fs::remove_dir_all(&trash_dir) {
+            log::warn!(
+                "Failed to clean up trash entry {}: {}",
+                trash_dir.display(),
+                error
+            );
+        }
+
+        Ok(entry)
+    }
+
+    /// Permanently delete one trashed session, or the whole trash when `trash_id` is `None`.
+    /// Returns the number of sessions removed.
+    pub fn purge(trash_id: Option<&str>) -> Result<u32, String> {
+        let trash_dirs = match trash_id {
+            Some(trash_id) => vec![Self::entry_dir(trash_id)?],
+            None => Self::list_trash()?
+                .into_iter()
+                .filter_map(|entry| Self::entry_dir(&entry.trash_id).ok())
+                .collect(),
+        };
+
+        let mut purged: u32 = 0;
+        for trash_dir in trash_dirs {
+            fs::remove_dir_all(&trash_dir)
+                .map_err(|e| format!("Failed to purge trash entry: {}", e))?;
+            purged += 1;
+        }
+
+        Ok(purged)
+    }
+
+    /// Purge sessions deleted more than `retention_days` ago.
+    pub fn purge_expired(retention_days: u32) -> Result<u32, String> {
+        let cutoff = Utc::now() - Duration::days(i64::from(retention_days));
+
+        let mut purged: u32 = 0;
+        for entry in Self::list_trash()? {
+            let expired = DateTime::parse_from_rfc3339(&entry.deleted_at)
+                .map(|deleted_at| deleted_at.with_timezone(&Utc) < cutoff)
+                .unwrap_or(false);
+
+            if expired {
+                purged += Self::purge(Some(&entry.trash_id))?;
+            }
+        }
+
+        Ok(purged)
+    }
+
+    fn trash_dir() -> PathBuf {
+        StorageService::claude_trash_dir()
+    }
+
+    fn entry_dir(trash_id: &str) -> Result<PathBuf, String> {
+        let normalized = ClaudeSessionService::normalize_non_empty(trash_id, "Trash id")?;
+        if uuid::Uuid::parse_str(&normalized).is_err() {
+            return Err(format!("Invalid trash id: {}", normalized));
+        }
+
+        let trash_dir = Self::trash_dir().join(&normalized);
+        if !trash_dir.is_dir() {
+            return Err(format!("Trash entry not found: {}", normalized));
+        }
+
+        Ok(trash_dir)
+    }
+
+    fn find_index_entry(index_path: &Path, session_id: &str) -> Option<serde_json::Value> {
+        let content = fs::read_to_string(index_path).ok()?;
+        let index: serde_json::Value = serde_json::from_str(&content).ok()?;
+
+        index
+            .get("entries")?
+            .as_array()?
+            .iter()
+            .find(|value| Self::is_session_entry(value, session_id))
+            .cloned()
+    }
+
+    fn is_session_entry(value: &serde_json::Value, session_id: &str) -> bool {
+        value.get("sessionId").and_then(|value| value.as_str()) == Some(session_id)
+    }
+
+    /// Rename, falling back to copy + delete for files when the trash is on another volume.
+    fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+        match fs::rename(from, to) {
+            Ok(()) => Ok(()),
+            Err(rename_error) if from.is_file() => {
+                fs::copy(from, to)
+                    .map_err(|e| format!("Failed to move {}: {}", from.display(), e))?;
+                fs::remove_file(from).map_err(|e| {
+                    format!(
+                        "Failed to remove {} after copy ({}): {}",
+                        from.display(),
+                        rename_error,
+                        e
+                    )
+                })
+            }
+            Err(error) => Err(format!("Failed to move {}: {}", from.display(), error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClaudeTrashService;
+    use crate::services::storage_service::{
+        storage_test_env_lock, unique_test_data_dir, DATA_DIR_OVERRIDE_ENV,
+    };
+    use crate::services::StorageService;
+    use std::fs;
+
+    #[test]
+    fn trash_and_restore_round_trip_keeps_index_in_sync() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_test_data_dir("claude-trash-round-trip");
+        let project_dir = temp_home.join(".claude/projects/-Users-mannix-Project-Trash");
+        fs::create_dir_all(&project_dir).unwrap();
+
+        let session_id = "doomed-session";
+        let jsonl_path = project_dir.join(format!("{session_id}.jsonl"));
+        fs::write(
+            &jsonl_path,
+            r#"{"type":"user","timestamp":"2026-04-07T01:00:00Z","message":{"role":"user","content":"Keep me"}}
+"#,
+        )
+        .unwrap();
+        let index_path = project_dir.join("sessions-index.json");
+        fs::write(
+            &index_path,
+            r#"{
+  "version": 1,
+  "originalPath": "/Users/mannix/Project/Trash",
+  "entries": [
+    { "sessionId": "doomed-session", "summary": "Important work", "isSidechain": false }
+  ]
+}"#,
+        )
+        .unwrap();
+
+        std::env::set_var("HOME", &temp_home);
+        std::env::set_var(DATA_DIR_OVERRIDE_ENV, temp_home.join("app-data"));
+
+        let trashed =
+            ClaudeTrashService::trash_session("/Users/mannix/Project/Trash", session_id).unwrap();
+        let file_gone = !jsonl_path.exists();
+        let index_after_delete = fs::read_to_string(&index_path).unwrap();
+        let listed = ClaudeTrashService::list_trash().unwrap();
+
+        let restored = ClaudeTrashService::restore_session(&trashed.trash_id).unwrap();
+        let index_after_restore = fs::read_to_string(&index_path).unwrap();
+        let trash_after_restore = ClaudeTrashService::list_trash().unwrap();
+        let missing = ClaudeTrashService::restore_session(&trashed.trash_id);
+
+        std::env::remove_var(DATA_DIR_OVERRIDE_ENV);
+        std::env::remove_var("HOME");
+
+        assert!(file_gone);
+        assert!(!index_after_delete.contains("doomed-session"));
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].summary, "Important work");
+        assert_eq!(listed[0].first_prompt, "Keep me");
+
+        assert_eq!(restored.session_id, session_id);
+        assert!(jsonl_path.exists());
+        assert!(index_after_restore.contains("\"summary\": \"Important work\""));
+        assert!(trash_after_restore.is_empty());
+        assert!(missing.is_err());
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn purge_expired_only_removes_old_entries() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_test_data_dir("claude-trash-expiry");
+        let project_dir = temp_home.join(".claude/projects/-Users-mannix-Project-Expiry");
+        fs::create_dir_all(&project_dir).unwrap();
+
+        for session_id in ["old-session", "new-session"] {
+            fs::write(
+                project_dir.join(format!("{session_id}.jsonl")),
+                r#"{"type":"user","timestamp":"2026-04-07T01:00:00Z","message":{"role":"user","content":"Hello"}}
+"#,
+            )
+            .unwrap();
+        }
+
+        std::env::set_var("HOME", &temp_home);
+        std::env::set_var(DATA_DIR_OVERRIDE_ENV, temp_home.join("app-data"));
+
+        let mut old_entry =
+            ClaudeTrashService::trash_session("/Users/mannix/Project/Expiry", "old-session")
+                .unwrap();
+        ClaudeTrashService::trash_session("/Users/mannix/Project/Expiry", "new-session").unwrap();
+
+        old_entry.deleted_at = "2020-01-01T00:00:00+00:00".to_string();
+        StorageService::write(
+            &StorageService::claude_trash_dir()
+                .join(&old_entry.trash_id)
+                .join("entry.json"),
+            &old_entry,
+        )
+        .unwrap();
+
+        let expired = ClaudeTrashService::purge_expired(30).unwrap();
+        let remaining = ClaudeTrashService::list_trash().unwrap();
+        let purged_all = ClaudeTrashService::purge(None).unwrap();
+        let invalid = ClaudeTrashService::purge(Some("../escape"));
+
+        std::env::remove_var(DATA_DIR_OVERRIDE_ENV);
+        std::env::remove_var("HOME");
+
+        assert_eq!(expired, 1);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].session_id, "new-session");
+        assert_eq!(purged_all, 1);
+        assert!(invalid.is_err());
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::models::app_settings::ModelPrice;
+use crate::models::claude_session::{JsonlEntry, JsonlUsage};
+use crate::models::claude_usage::{
+    ClaudeDailyUsage, ClaudeProjectUsage, ClaudeSessionUsage, ClaudeUsageReport, ClaudeUsageTotals,
+};
+use crate::services::ClaudeSessionService;
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+const TOKENS_PER_PRICE_UNIT: f64 = 1_000_000.0;
+
+pub struct ClaudeUsageService;
+
+#[derive(Default)]
+struct UsageAccumulator {
+    total: ClaudeUsageTotals,
+    sessions: HashMap<(String, String), ClaudeUsageTotals>,
+    projects: HashMap<String, ClaudeUsageTotals>,
+    days: BTreeMap<String, ClaudeUsageTotals>,
+    unpriced_models: BTreeSet<String>,
+    seen_message_ids: HashSet<String>,
+}
+
+impl ClaudeUsageService {
+    /// Sum token usage of assistant messages per session, project and day.
+    /// Subagent transcripts are attributed to the session that spawned them.
+    pub fn usage_report(
+        project_path: Option<&str>,
+        prices: &[ModelPrice],
+    ) -> Result<ClaudeUsageReport, String> {
+        let mut accumulator = UsageAccumulator::default();
+
+        for (project_dir, original_path) in
+            ClaudeSessionService::collect_project_dirs(project_path)?
+        {
+            let entries = match fs::read_dir(&project_dir) {
+                Ok(entries) => entries,
+                Err(error) => {
+                    log::debug!(
+                        "Skipping unreadable Claude project dir {}: {}",
+                        project_dir.display(),
+                        error
+                    );
+                    continue;
+                }
+            };
+
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                    continue;
+                }
+
+                if let Err(error) =
+                    Self::accumulate_jsonl_file(&path, &original_path, prices, &mut accumulator)
+                {
+                    log::debug!("Skipping Claude session {}: {}", path.display(), error);
+                }
+            }
+        }
+
+        Ok(Self::build_report(accumulator))
+    }
+
+    fn accumulate_jsonl_file(
+        path: &Path,
+        project_path: &str,
+        prices: &[ModelPrice],
+        accumulator: &mut UsageAccumulator,
+    ) -> Result<(), String> {
+        let file_stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| "Invalid session file name".to_string())?;
+        let file = fs::File::open(path).map_err(|e| format!("Failed to open JSONL file: {}", e))?;
+        let reader = BufReader::new(file);
+
+        for line in reader.lines() {
+            let line = match line {
+                Ok(l) => l,
+                Err(_) => continue,
+            };
+
+            // Cheap pre-filter: only assistant lines carry usage
+            if !line.contains("\"usage\"") {
+                continue;
+            }
+
+            let entry: JsonlEntry = match serde_json::from_str(&line) {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+
+            if entry.entry_type.as_deref() != Some("assistant") {
+                continue;
+            }
+
+            let Some(message) = entry.message else {
+                continue;
+            };
+            let Some(usage) = message.usage else {
+                continue;
+            };
+
+            // Claude Code writes one line per content block, each repeating the same usage
+            if let Some(message_id) = message.id {
+                if !accumulator.seen_message_ids.insert(message_id) {
+                    continue;
+                }
+            }
+
+            let model = message.model.unwrap_or_default();
+            let cost = match Self::price_for_model(&model, prices) {
+                Some(price) => Self::estimate_cost(&usage, price),
+                None => {
+                    if !model.is_empty() && Self::token_count(&usage) > 0 {
+                        accumulator.unpriced_models.insert(model);
+                    }
+                    0.0
+                }
+            };
+
+            let session_id = entry
+                .session_id
+                .filter(|value| !value.is_empty())
+                .unwrap_or_else(|| file_stem.to_string());
+
+            Self::add_usage(&mut accumulator.total, &usage, cost);
+            Self::add_usage(
+                accumulator
+                    .sessions
+                    .entry((project_path.to_string(), session_id))
+                    .or_default(),
+                &usage,
+                cost,
+            );
+            Self::add_usage(
+                accumulator
+                    .projects
+                    .entry(project_path.to_string())
+                    .or_default(),
+                &usage,
+                cost,
+            );
+            if let Some(date) = entry.timestamp.as_deref().and_then(Self::utc_date) {
+                Self::add_usage(accumulator.days.entry(date).or_default(), &usage, cost);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn build_report(accumulator: UsageAccumulator) -> ClaudeUsageReport {
+        let mut sessions: Vec<ClaudeSessionUsage> = accumulator
+            .sessions
+            .into_iter()
+            .map(|((project_path, session_id), totals)| ClaudeSessionUsage {
+                session_id,
+                project_path,
+                totals,
+            })
+            .collect();
+        sessions.sort_by(|a, b| Self::compare_totals(&a.totals, &b.totals));
+
+        let mut projects: Vec<ClaudeProjectUsage> = accumulator
+            .projects
+            .into_iter()
+            .map(|(project_path, totals)| ClaudeProjectUsage {
+                project_path,
+                totals,
+            })
+            .collect();
+        projects.sort_by(|a, b| Self::compare_totals(&a.totals, &b.totals));
+
+        ClaudeUsageReport {
+            total: accumulator.total,
+            sessions,
+            projects,
+            days: accumulator
+                .days
+                .into_iter()
+                .map(|(date, totals)| ClaudeDailyUsage { date, totals })
+                .collect(),
+            unpriced_models: accumulator.unpriced_models.into_iter().collect(),
+        }
+    }
+
+    /// Most expensive first, falling back to token volume for unpriced usage.
+    fn compare_totals(a: &ClaudeUsageTotals, b: &ClaudeUsageTotals) -> Ordering {
+        b.estimated_cost_usd
+            .partial_cmp(&a.estimated_cost_usd)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| {
+                let a_tokens = a.input_tokens + a.output_tokens;
+                let b_tokens = b.input_tokens + b.output_tokens;
+                b_tokens.cmp(&a_tokens)
+            })
+    }
+
+    /// The longest pattern contained in the model id wins.
+    fn price_for_model<'a>(model: &str, prices: &'a [ModelPrice]) -> Option<&'a ModelPrice> {
+        let model = model.to_lowercase();
+        prices
+            .iter()
+            .filter(|price| !price.model_pattern.is_empty() && model.contains(&price.model_pattern))
+            .max_by_key(|price| price.model_pattern.len())
+    }
+
+    fn estimate_cost(usage: &JsonlUsage, price: &ModelPrice) -> f64 {
+        let cost = usage.input_tokens.unwrap_or(0) as f64 * price.input_per_mtok
+            + usage.output_tokens.unwrap_or(0) as f64 * price.output_per_mtok
+            + usage.cache_creation_input_tokens.unwrap_or(0) as f64 * price.cache_write_per_mtok
+            + usage.cache_read_input_tokens.unwrap_or(0) as f64 * price.cache_read_per_mtok;
+
+        cost / TOKENS_PER_PRICE_UNIT
+    }
+
+    fn token_count(usage: &JsonlUsage) -> u64 {
+        usage.input_tokens.unwrap_or(0)
+            + usage.output_tokens.unwrap_or(0)
+            + usage.cache_creation_input_tokens.unwrap_or(0)
+            + usage.cache_read_input_tokens.unwrap_or(0)
+    }
+
+    fn add_usage(totals: &mut ClaudeUsageTotals, usage: &JsonlUsage, cost: f64) {
+        totals.input_tokens += usage.input_tokens.unwrap_or(0);
+        totals.output_tokens += usage.output_tokens.unwrap_or(0);
+        totals.cache_creation_input_tokens += usage.cache_creation_input_tokens.unwrap_or(0);
+        totals.cache_read_input_tokens += usage.cache_read_input_tokens.unwrap_or(0);
+        totals.request_count += 1;
+        totals.estimated_cost_usd += cost;
+    }
+
+    fn utc_date(timestamp: &str) -> Option<String> {
+        DateTime::parse_from_rfc3339(timestamp)
+            .ok()
+            .map(|value| value.with_timezone(&Utc).format("%Y-%m-%d").to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClaudeUsageService;
+    use crate::models::app_settings::ClaudeSettings;
+    use crate::services::storage_service::{storage_test_env_lock, unique_test_data_dir};
+    use std::fs;
+
+    #[test]
+    fn usage_report_dedupes_split_messages_and_prices_by_model() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_home = unique_test_data_dir("claude-usage-report");
+        let project_dir = temp_home.join(".claude/projects/-Users-mannix-Project-Alpha");
+        fs::create_dir_all(&project_dir).unwrap();
+
+        fs::write(
+            project_dir.join("session-a.jsonl"),
+            r#"{"type":"user","sessionId":"session-a","timestamp":"2026-04-07T23:59:00Z","message":{"role":"user","content":"hi"}}
+{"type":"assistant","sessionId":"session-a","timestamp":"2026-04-07T23:59:30Z","message":{"id":"msg_1","role":"assistant","model":"claude-sonnet-4-5-20250929","content":[{"type":"thinking","thinking":"..."}],"usage":{"input_tokens":1000,"output_tokens":2000,"cache_creation_input_tokens":0,"cache_read_input_tokens":10000}}}
+{"type":"assistant","sessionId":"session-a","timestamp":"2026-04-07T23:59:31Z","message":{"id":"msg_1","role":"assistant","model":"claude-sonnet-4-5-20250929","content":[{"type":"text","text":"hello"}],"usage":{"input_tokens":1000,"output_tokens":2000,"cache_creation_input_tokens":0,"cache_read_input_tokens":10000}}}
+{"type":"assistant","sessionId":"session-a","timestamp":"2026-04-08T00:00:10Z","message":{"id":"msg_2","role":"assistant","model":"claude-opus-4-1-20250805","content":[{"type":"text","text":"done"}],"usage":{"input_tokens":1000,"output_tokens":1000}}}
+"#,
+        )
+        .unwrap();
+        fs::write(
+            project_dir.join("agent-1234.jsonl"),
+            r#"{"type":"assistant","sessionId":"session-a","isSidechain":true,"timestamp":"2026-04-08T00:00:05Z","message":{"id":"msg_3","role":"assistant","model":"some-local-model","content":[{"type":"text","text":"sub"}],"usage":{"input_tokens":50,"output_tokens":5}}}
+"#,
+        )
+        .unwrap();
+
+        std::env::set_var("HOME", &temp_home);
+        let prices = ClaudeSettings::default().model_prices;
+        let report = ClaudeUsageService::usage_report(None, &prices).unwrap();
+        std::env::remove_var("HOME");
+
+        // sonnet: 1000*3 + 2000*15 + 10000*0.3 = 36000 -> $0.036
+        // opus:   1000*15 + 1000*75             = 90000 -> $0.090
+        assert_eq!(report.total.request_count, 3);
+        assert_eq!(report.total.input_tokens, 2050);
+        assert_eq!(report.total.output_tokens, 3005);
+        assert_eq!(report.total.cache_read_input_tokens, 10000);
+        assert!((report.total.estimated_cost_usd - 0.126).abs() < 1e-9);
+        assert_eq!(report.unpriced_models, vec!["some-local-model".to_string()]);
+
+        assert_eq!(report.sessions.len(), 1);
+        assert_eq!(report.sessions[0].session_id, "session-a");
+        assert_eq!(report.sessions[0].totals.request_count, 3);
+        assert_eq!(report.projects.len(), 1);
+        assert_eq!(
+            report.projects[0].project_path,
+            "/Users/mannix/Project/Alpha"
+        );
+
+        let days: Vec<&str> = report.days.iter().map(|day| day.date.as_str()).collect();
+        assert_eq!(days, vec!["2026-04-07", "2026-04-08"]);
+        assert_eq!(report.days[1].totals.request_count, 2);
+
+        let _ = fs::remove_dir_all(temp_home);
+    }
+
+    #[test]
+    fn price_for_model_prefers_longest_matching_pattern() {
+        let prices = ClaudeSettings::default().model_prices;
+
+        let opus_4_5 = ClaudeUsageService::price_for_model("claude-opus-4-5-20251101", &prices)
+            .expect("opus 4.5 should be priced");
+        let opus_4_1 = ClaudeUsageService::price_for_model("claude-opus-4-1-20250805", &prices)
+            .expect("opus 4.1 should be priced");
+
+        assert_eq!(opus_4_5.model_pattern, "claude-opus-4-5");
+        assert_eq!(opus_4_1.model_pattern, "claude-opus-4");
+        assert!(ClaudeUsageService::price_for_model("gpt-4o", &prices).is_none());
+    }
+}
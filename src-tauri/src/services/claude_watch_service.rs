@@ -0,0 +1,487 @@
+use crate::models::claude_session::{
+    ClaudeProjectChange, ClaudeSessionChangeKind, ClaudeSessionFileChange,
+};
+use crate::services::ClaudeSessionService;
+use notify::event::{CreateKind, EventKind, ModifyKind};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const EVENT_CLAUDE_SESSIONS_CHANGED: &str = "claude-sessions-changed";
+
+/// Quiet period before a batch of changes is emitted.
+pub const DEFAULT_WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+/// Upper bound on how long a busy project (e.g. a streaming session) waits for an event.
+const MAX_BATCH_DELAY: Duration = Duration::from_millis(1000);
+
+#[derive(Default)]
+struct PendingProject {
+    sessions: BTreeMap<String, ClaudeSessionChangeKind>,
+    index_changed: bool,
+}
+
+/// Watches ~/.claude/projects and reports debounced per-project changes
+/// to session JSONL files and sessions-index.json.
+///
+/// The directory is never created here. While it is missing, its nearest existing
+/// ancestor is watched instead, and the watch moves down as directories appear.
+pub struct ClaudeWatchService {
+    // Dropping the watcher closes the event channel, which stops the debounce thread.
+    // The thread only holds a weak reference, to move the watch.
+    _watcher: Arc<Mutex<RecommendedWatcher>>,
+}
+
+impl ClaudeWatchService {
+    pub fn start<F>(projects_dir: PathBuf, debounce: Duration, on_change: F) -> Result<Self, String>
+    where
+        F: Fn(ClaudeProjectChange) + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher = notify::recommended_watcher(move |result| {
+            let _ = sender.send(result);
+        })
+        .map_err(|e| format!("Failed to create filesystem watcher: {}", e))?;
+
+        let watched = Self::watch_nearest(&mut watcher, &projects_dir, None)?;
+        let watcher = Arc::new(Mutex::new(watcher));
+        let weak_watcher = Arc::downgrade(&watcher);
+
+        thread::Builder::new()
+            .name("claude-session-watcher".to_string())
+            .spawn(move || {
+                Self::debounce_loop(
+                    &projects_dir,
+                    weak_watcher,
+                    watched,
+                    receiver,
+                    debounce,
+                    on_change,
+                )
+            })
+            .map_err(|e| format!("Failed to start watcher thread: {}", e))?;
+
+        Ok(Self { _watcher: watcher })
+    }
+
+    /// Watches `projects_dir` recursively if it exists, otherwise its nearest existing
+    /// ancestor non-recursively. Returns the path now watched.
+    fn watch_nearest(
+        watcher: &mut RecommendedWatcher,
+        projects_dir: &Path,
+        mut watched: Option<PathBuf>,
+    ) -> Result<PathBuf, String> {
+        // Repeats until stable, in case a deeper level appeared while the watch was moving.
+        loop {
+            let target = projects_dir
+                .ancestors()
+                .find(|path| path.is_dir())
+                .ok_or_else(|| {
+                    format!(
+                        "No existing directory above Claude projects directory {}",
+                        projects_dir.display()
+                    )
+                })?;
+            if watched.as_deref() == Some(target) {
+                return Ok(target.to_path_buf());
+            }
+
+            if let Some(previous) = watched.take() {
+                let _ = watcher.unwatch(&previous);
+            }
+            let mode = if target == projects_dir {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            watcher.watch(target, mode).map_err(|e| {
+                format!(
+                    "Failed to watch Claude projects directory {}: {}",
+                    target.display(),
+                    e
+                )
+            })?;
+            watched = Some(target.to_path_buf());
+        }
+    }
+
+    /// Moves the watch after a change outside `projects_dir` or a removal. Returns true
+    /// when files already inside a newly watched `projects_dir` were recorded.
+    fn rewatch(
+        watcher: &Weak<Mutex<RecommendedWatcher>>,
+        projects_dir: &Path,
+        watched: &mut PathBuf,
+        pending: &mut BTreeMap<String, PendingProject>,
+    ) -> bool {
+        let Some(watcher) = watcher.upgrade() else {
+            return false;
+        };
+        let Ok(mut watcher) = watcher.lock() else {
+            return false;
+        };
+
+        match Self::watch_nearest(&mut watcher, projects_dir, Some(watched.clone())) {
+            Ok(now) if now != *watched => {
+                log::debug!("Claude session watcher now watching {}", now.display());
+                *watched = now;
+                watched.as_path() == projects_dir && Self::record_existing(projects_dir, pending)
+            }
+            Ok(_) => false,
+            Err(error) => {
+                log::warn!("{}", error);
+                false
+            }
+        }
+    }
+
+    /// Records the files of a projects dir that was created after the watcher started,
+    /// since they may have been written before the recursive watch was in place.
+    fn record_existing(
+        projects_dir: &Path,
+        pending: &mut BTreeMap<String, PendingProject>,
+    ) -> bool {
+        let Ok(project_dirs) = fs::read_dir(projects_dir) else {
+            return false;
+        };
+
+        let mut recorded = false;
+        for project_dir in project_dirs.flatten() {
+            let Ok(files) = fs::read_dir(project_dir.path()) else {
+                continue;
+            };
+            for file in files.flatten() {
+                let event = Event::new(EventKind::Create(CreateKind::Any)).add_path(file.path());
+                recorded |= Self::record_event(projects_dir, &event, pending);
+            }
+        }
+        recorded
+    }
+
+    fn debounce_loop<F>(
+        projects_dir: &Path,
+        watcher: Weak<Mutex<RecommendedWatcher>>,
+        mut watched: PathBuf,
+        receiver: Receiver<notify::Result<Event>>,
+        debounce: Duration,
+        on_change: F,
+    ) where
+        F: Fn(ClaudeProjectChange),
+    {
+        let mut pending: BTreeMap<String, PendingProject> = BTreeMap::new();
+        let mut first_event_at: Option<Instant> = None;
+        let mut last_event_at = Instant::now();
+
+        loop {
+            let timeout = match first_event_at {
+                Some(first) => debounce
+                    .saturating_sub(last_event_at.elapsed())
+                    .min(MAX_BATCH_DELAY.saturating_sub(first.elapsed())),
+                None => Duration::from_secs(3600),
+            };
+
+            match receiver.recv_timeout(timeout) {
+                Ok(Ok(event)) => {
+                    let mut recorded = Self::record_event(projects_dir, &event, &mut pending);
+                    if watched != projects_dir || matches!(event.kind, EventKind::Remove(_)) {
+                        recorded |=
+                            Self::rewatch(&watcher, projects_dir, &mut watched, &mut pending);
+                    }
+                    if recorded {
+                        last_event_at = Instant::now();
+                        first_event_at.get_or_insert(last_event_at);
+                    }
+                }
+                Ok(Err(error)) => log::warn!("Claude session watcher error: {}", error),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            let due = first_event_at.is_some_and(|first| {
+                last_event_at.elapsed() >= debounce || first.elapsed() >= MAX_BATCH_DELAY
+            });
+            if due {
+                Self::flush(projects_dir, &mut pending, &on_change);
+                first_event_at = None;
+            }
+        }
+    }
+
+    fn flush<F>(projects_dir: &Path, pending: &mut BTreeMap<String, PendingProject>, on_change: &F)
+    where
+        F: Fn(ClaudeProjectChange),
+    {
+        for (project_dir_name, project) in std::mem::take(pending) {
+            let project_path =
+                ClaudeSessionService::project_original_path(&projects_dir.join(&project_dir_name));
+
+            on_change(ClaudeProjectChange {
+                project_dir_name,
+                project_path,
+                sessions: project
+                    .sessions
+                    .into_iter()
+                    .map(|(session_id, kind)| ClaudeSessionFileChange { session_id, kind })
+                    .collect(),
+                index_changed: project.index_changed,
+            });
+        }
+    }
+
+    /// Folds a raw notify event into `pending`. Returns false when nothing relevant changed.
+    fn record_event(
+        projects_dir: &Path,
+        event: &Event,
+        pending: &mut BTreeMap<String, PendingProject>,
+    ) -> bool {
+        let mut recorded = false;
+
+        for path in &event.paths {
+            let Some((project_dir_name, file_name)) = Self::split_project_file(projects_dir, path)
+            else {
+                continue;
+            };
+
+            if file_name == "sessions-index.json" {
+                if matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) {
+                    pending.entry(project_dir_name).or_default().index_changed = true;
+                    recorded = true;
+                }
+                continue;
+            }
+
+            let Some(session_id) = file_name.strip_suffix(".jsonl") else {
+                continue;
+            };
+            // Agent/subagent files are not listed as sessions
+            if session_id.starts_with("agent-") {
+                continue;
+            }
+
+            let kind = match event.kind {
+                EventKind::Create(_) => ClaudeSessionChangeKind::Created,
+                EventKind::Remove(_) => ClaudeSessionChangeKind::Deleted,
+                EventKind::Modify(ModifyKind::Name(_)) => {
+                    if path.exists() {
+                        ClaudeSessionChangeKind::Created
+                    } else {
+                        ClaudeSessionChangeKind::Deleted
+                    }
+                }
+                EventKind::Modify(_) => ClaudeSessionChangeKind::Updated,
+                _ => continue,
+            };
+
+            let sessions = &mut pending.entry(project_dir_name).or_default().sessions;
+            let merged = match sessions.get(session_id) {
+                Some(previous) => Self::merge_kinds(*previous, kind),
+                None => kind,
+            };
+            sessions.insert(session_id.to_string(), merged);
+            recorded = true;
+        }
+
+        recorded
+    }
+
+    fn merge_kinds(
+        previous: ClaudeSessionChangeKind,
+        next: ClaudeSessionChangeKind,
+    ) -> ClaudeSessionChangeKind {
+        use ClaudeSessionChangeKind::{Created, Deleted, Updated};
+
+        match (previous, next) {
+            (Created, Updated) => Created,
+            (Deleted, Created) => Updated,
+            (_, next) => next,
+        }
+    }
+
+    /// Splits `<projects_dir>/<project dir>/<file>` into its two parts;
+    /// deeper paths (per-session subdirectories) are ignored.
+    fn split_project_file(projects_dir: &Path, path: &Path) -> Option<(String, String)> {
+        let relative = path.strip_prefix(projects_dir).ok()?;
+        let mut components = relative.components();
+
+        let (Some(Component::Normal(dir)), Some(Component::Normal(file)), None) =
+            (components.next(), components.next(), components.next())
+        else {
+            return None;
+        };
+
+        Some((dir.to_str()?.to_string(), file.to_str()?.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClaudeWatchService;
+    use crate::models::claude_session::{ClaudeProjectChange, ClaudeSessionChangeKind};
+    use crate::services::storage_service::unique_test_data_dir;
+    use notify::event::{CreateKind, DataChange, EventKind, ModifyKind, RemoveKind};
+    use notify::Event;
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    fn event(kind: EventKind, path: &Path) -> Event {
+        Event::new(kind).add_path(path.to_path_buf())
+    }
+
+    #[test]
+    fn record_event_merges_changes_per_project() {
+        let projects_dir = PathBuf::from("/home/me/.claude/projects");
+        let session_path = projects_dir.join("-work-foo/abc.jsonl");
+        let mut pending = BTreeMap::new();
+
+        let events = [
+            event(EventKind::Create(CreateKind::File), &session_path),
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                &session_path,
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                &projects_dir.join("-work-foo/sessions-index.json"),
+            ),
+            event(
+                EventKind::Remove(RemoveKind::File),
+                &projects_dir.join("-work-bar/old.jsonl"),
+            ),
+            event(
+                EventKind::Create(CreateKind::File),
+                &projects_dir.join("-work-foo/agent-1.jsonl"),
+            ),
+            event(
+                EventKind::Create(CreateKind::File),
+                &projects_dir.join("-work-foo/abc/subagents/x.jsonl"),
+            ),
+        ];
+        let recorded: Vec<bool> = events
+            .iter()
+            .map(|event| ClaudeWatchService::record_event(&projects_dir, event, &mut pending))
+            .collect();
+
+        assert_eq!(recorded, vec![true, true, true, true, false, false]);
+        assert_eq!(
+            pending["-work-foo"].sessions.get("abc"),
+            Some(&ClaudeSessionChangeKind::Created)
+        );
+        assert!(pending["-work-foo"].index_changed);
+        assert_eq!(
+            pending["-work-bar"].sessions.get("old"),
+            Some(&ClaudeSessionChangeKind::Deleted)
+        );
+        assert!(!pending["-work-bar"].index_changed);
+    }
+
+    #[test]
+    fn watcher_emits_debounced_project_changes() {
+        let projects_dir = unique_test_data_dir("claude-watch-live");
+        let project_dir = projects_dir.join("-work-live");
+        fs::create_dir_all(&project_dir).unwrap();
+
+        let (sender, receiver) = mpsc::channel::<ClaudeProjectChange>();
+        let watcher = ClaudeWatchService::start(
+            projects_dir.clone(),
+            Duration::from_millis(100),
+            move |change| {
+                let _ = sender.send(change);
+            },
+        )
+        .unwrap();
+
+        let session_path = project_dir.join("live-session.jsonl");
+        let mut file = fs::File::create(&session_path).unwrap();
+        for index in 0..5 {
+            writeln!(
+                file,
+                r#"{{"type":"user","message":{{"content":"line {index}"}}}}"#
+            )
+            .unwrap();
+        }
+        drop(file);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut batches = Vec::new();
+        while Instant::now() < deadline {
+            match receiver.recv_timeout(Duration::from_millis(200)) {
+                Ok(change) => batches.push(change),
+                Err(_) if !batches.is_empty() => break,
+                Err(_) => {}
+            }
+        }
+
+        assert!(!batches.is_empty(), "expected a change event");
+        assert!(batches.len() < 5, "changes should be debounced");
+        assert_eq!(batches[0].project_dir_name, "-work-live");
+        assert_eq!(batches[0].project_path, "/work/live");
+        assert_eq!(batches[0].sessions[0].session_id, "live-session");
+        assert_eq!(
+            batches[0].sessions[0].kind,
+            ClaudeSessionChangeKind::Created
+        );
+
+        fs::remove_file(&session_path).unwrap();
+        let deleted = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(deleted.sessions[0].kind, ClaudeSessionChangeKind::Deleted);
+
+        drop(watcher);
+        let _ = fs::remove_dir_all(projects_dir);
+    }
+
+    #[test]
+    fn watcher_picks_up_projects_dir_created_after_start() {
+        let home = unique_test_data_dir("claude-watch-missing");
+        fs::create_dir_all(&home).unwrap();
+        let projects_dir = home.join(".claude/projects");
+
+        let (sender, receiver) = mpsc::channel::<ClaudeProjectChange>();
+        let watcher = ClaudeWatchService::start(
+            projects_dir.clone(),
+            Duration::from_millis(100),
+            move |change| {
+                let _ = sender.send(change);
+            },
+        )
+        .unwrap();
+        assert!(!home.join(".claude").exists(), "must not create ~/.claude");
+
+        // Created in one go, as Claude does on first run; the file may land before
+        // the watch reaches the projects dir.
+        let project_dir = projects_dir.join("-work-late");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(project_dir.join("late-session.jsonl"), "{}\n").unwrap();
+
+        let change = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(change.project_dir_name, "-work-late");
+        assert_eq!(change.sessions[0].session_id, "late-session");
+        assert_eq!(change.sessions[0].kind, ClaudeSessionChangeKind::Created);
+
+        // From here on the projects dir itself is watched.
+        fs::write(project_dir.join("second.jsonl"), "{}\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut seen_second = false;
+        while !seen_second && Instant::now() < deadline {
+            if let Ok(change) = receiver.recv_timeout(Duration::from_millis(200)) {
+                seen_second = change
+                    .sessions
+                    .iter()
+                    .any(|session| session.session_id == "second");
+            }
+        }
+        assert!(seen_second, "expected a change for the second session");
+
+        drop(watcher);
+        let _ = fs::remove_dir_all(home);
+    }
+}
@@ -1,12 +1,35 @@
+pub mod claude_diagnostics_service;
+pub mod claude_export_service;
+pub mod claude_job_service;
+pub mod claude_migration_service;
+pub mod claude_search_service;
+pub mod claude_session_cache;
 pub mod claude_session_service;
+pub mod claude_trash_service;
+pub mod claude_usage_service;
+pub mod claude_watch_service;
 pub mod project_service;
+pub mod pty_coalescer;
+#[cfg(unix)]
+pub mod pty_daemon;
+pub mod pty_env_service;
+pub mod pty_process_service;
+pub mod pty_recording_service;
+pub mod pty_scrollback;
 pub mod pty_service;
+pub mod pty_shutdown;
 pub mod session_service;
 pub mod settings_service;
 pub mod storage_service;
 pub mod update_service;
 
+pub use claude_diagnostics_service::ClaudeDiagnosticsService;
+pub use claude_export_service::ClaudeExportService;
+pub use claude_migration_service::ClaudeMigrationService;
+pub use claude_search_service::ClaudeSearchService;
 pub use claude_session_service::ClaudeSessionService;
+pub use claude_trash_service::ClaudeTrashService;
+pub use claude_usage_service::ClaudeUsageService;
 pub use project_service::ProjectService;
 pub use session_service::SessionService;
 pub use settings_service::SettingsService;
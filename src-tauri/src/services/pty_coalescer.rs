@@ -0,0 +1,217 @@
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// Longest a chunk waits before it is flushed during sustained output.
+pub const OUTPUT_FLUSH_INTERVAL: Duration = Duration::from_millis(12);
+pub const OUTPUT_FLUSH_BYTES: usize = 64 * 1024;
+/// Reads queued between the PTY reader and the emitter; a full queue stalls the
+/// reader, which in turn lets the kernel throttle the child.
+pub const OUTPUT_CHANNEL_CHUNKS: usize = 64;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CoalescePolicy {
+    pub interval: Duration,
+    pub max_bytes: usize,
+}
+
+impl Default for CoalescePolicy {
+    fn default() -> Self {
+        Self {
+            interval: OUTPUT_FLUSH_INTERVAL,
+            max_bytes: OUTPUT_FLUSH_BYTES,
+        }
+    }
+}
+
+/// Batches chunks from `chunks` into `flush` calls. A chunk arriving after a quiet
+/// period is flushed right away; during a flood flushes happen at most once per
+/// `interval` or whenever `max_bytes` have accumulated.
+///
+/// Returns once the sender hangs up (after flushing what is left) or `flush`
+/// returns false.
+pub(crate) fn coalesce<F>(chunks: Receiver<Vec<u8>>, policy: CoalescePolicy, mut flush: F)
+where
+    F: FnMut(&[u8]) -> bool,
+{
+    let mut pending = Vec::<u8>::new();
+    let mut last_flush: Option<Instant> = None;
+
+    loop {
+        let received = if pending.is_empty() {
+            chunks.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            let deadline = last_flush.map_or_else(Instant::now, |at| at + policy.interval);
+            chunks.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        };
+
+        let due = match received {
+            Ok(chunk) => {
+                pending.extend_from_slice(&chunk);
+                pending.len() >= policy.max_bytes
+                    || last_flush.map_or(true, |at| at.elapsed() >= policy.interval)
+            }
+            Err(RecvTimeoutError::Timeout) => true,
+            Err(RecvTimeoutError::Disconnected) => {
+                if !pending.is_empty() {
+                    flush(&pending);
+                }
+                return;
+            }
+        };
+
+        if due {
+            if !flush(&pending) {
+                return;
+            }
+            pending.clear();
+            last_flush = Some(Instant::now());
+        }
+    }
+}
+
+/// Decodes a byte stream as UTF-8 across chunk boundaries. Invalid sequences become
+/// U+FFFD; an incomplete trailing character is held until the next chunk.
+#[derive(Default)]
+pub(crate) struct Utf8Stream {
+    carry: Vec<u8>,
+}
+
+impl Utf8Stream {
+    /// Returns the decoded text and how many stream bytes it covers.
+    pub fn decode(&mut self, data: &[u8]) -> (String, usize) {
+        self.carry.extend_from_slice(data);
+
+        let mut text = String::with_capacity(self.carry.len());
+        let mut consumed = 0;
+        while consumed < self.carry.len() {
+            match std::str::from_utf8(&self.carry[consumed..]) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    consumed = self.carry.len();
+                }
+                Err(error) => {
+                    let valid_up_to = consumed + error.valid_up_to();
+                    text.push_str(
+                        std::str::from_utf8(&self.carry[consumed..valid_up_to]).unwrap_or(""),
+                    );
+                    consumed = valid_up_to;
+                    match error.error_len() {
+                        Some(len) => {
+                            text.push('\u{FFFD}');
+                            consumed += len;
+                        }
+                        None => break,
+                    }
+                }
+            }
+        }
+
+        self.carry.drain(..consumed);
+        (text, consumed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{coalesce, CoalescePolicy, Utf8Stream, OUTPUT_CHANNEL_CHUNKS};
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn utf8_stream_carries_split_characters_and_replaces_invalid_bytes() {
+        let mut stream = Utf8Stream::default();
+        let bytes = "é漢".as_bytes();
+
+        assert_eq!(stream.decode(&bytes[..1]), (String::new(), 0));
+        assert_eq!(stream.decode(&bytes[1..3]), ("é".to_string(), 2));
+        assert_eq!(stream.decode(&bytes[3..]), ("漢".to_string(), 3));
+        assert_eq!(stream.decode(b"a\xffb\xe6"), ("a\u{FFFD}b".to_string(), 3));
+        assert_eq!(stream.decode(b"\xbc"), (String::new(), 0));
+        assert_eq!(stream.decode(b"\xa2!"), ("漢!".to_string(), 4));
+    }
+
+    #[test]
+    fn sparse_chunks_are_flushed_without_delay() {
+        let (sender, receiver) = mpsc::sync_channel(OUTPUT_CHANNEL_CHUNKS);
+        let (flushed_sender, flushed) = mpsc::channel();
+        // A long interval, so a chunk held back until the next flush is unmistakable
+        // even on a loaded machine.
+        let policy = CoalescePolicy {
+            interval: Duration::from_millis(500),
+            ..CoalescePolicy::default()
+        };
+        let emitter = std::thread::spawn(move || {
+            coalesce(receiver, policy, |data| {
+                flushed_sender.send((data.to_vec(), Instant::now())).is_ok()
+            });
+        });
+
+        for key in [b"l".to_vec(), b"s".to_vec()] {
+            let sent = Instant::now();
+            sender.send(key.clone()).unwrap();
+            let (data, at) = flushed.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(data, key);
+            let waited = at.duration_since(sent);
+            assert!(
+                waited < policy.interval / 2,
+                "sparse chunk waited {:?}",
+                waited
+            );
+            std::thread::sleep(policy.interval + Duration::from_millis(100));
+        }
+
+        drop(sender);
+        emitter.join().unwrap();
+    }
+
+    /// Benchmark: a 16 MiB flood in 4 KiB reads must collapse into a few hundred
+    /// events without losing or reordering bytes.
+    #[test]
+    fn flood_is_coalesced_into_few_events() {
+        const CHUNK: usize = 4096;
+        const CHUNKS: usize = 4096;
+
+        let (sender, receiver) = mpsc::sync_channel(OUTPUT_CHANNEL_CHUNKS);
+        let producer = std::thread::spawn(move || {
+            for index in 0..CHUNKS {
+                let byte = b'a' + (index % 26) as u8;
+                sender.send(vec![byte; CHUNK]).unwrap();
+            }
+        });
+
+        let started = Instant::now();
+        let mut events = 0usize;
+        let mut received = Vec::with_capacity(CHUNK * CHUNKS);
+        coalesce(receiver, CoalescePolicy::default(), |data| {
+            events += 1;
+            received.extend_from_slice(data);
+            true
+        });
+        producer.join().unwrap();
+        let elapsed = started.elapsed();
+
+        assert_eq!(received.len(), CHUNK * CHUNKS);
+        assert!(received
+            .chunks(CHUNK)
+            .enumerate()
+            .all(|(index, chunk)| chunk.iter().all(|byte| *byte == b'a' + (index % 26) as u8)));
+
+        // At most one event per 64 KiB plus one per interval elapsed.
+        let limit = CHUNK * CHUNKS / (64 * 1024) + elapsed.as_millis() as usize / 12 + 2;
+        assert!(
+            events <= limit,
+            "{} events in {:?}, limit {}",
+            events,
+            elapsed,
+            limit
+        );
+        assert!(
+            events * 8 < CHUNKS,
+            "{} events for {} reads ({} MiB) in {:?}",
+            events,
+            CHUNKS,
+            CHUNK * CHUNKS / (1024 * 1024),
+            elapsed
+        );
+    }
+}
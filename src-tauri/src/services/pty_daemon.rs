@@ -0,0 +1,1318 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::services::pty_process_service::PtyProcessReport;
+use crate::services::pty_service::{
+    PtyAttachment, PtyByteSink, PtyByteSubscription, PtyClaudeSession, PtyEventSink, PtyExitInfo,
+    PtyLaunchConfig, PtyManager,
+};
+use crate::services::pty_shutdown::{PtyCloseOptions, PtyCloseOutcome};
+use crate::services::storage_service::StorageService;
+
+/// Command-line flag that turns the app binary into the PTY daemon: `<exe> --pty-daemon <socket>`.
+pub const PTY_DAEMON_ARG: &str = "--pty-daemon";
+/// How long a daemon with no sessions and no connected clients lingers before exiting.
+pub const DEFAULT_DAEMON_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+const PROTOCOL_VERSION: u32 = 7;
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const CLIENT_WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Lines queued for a client before it is considered stalled and dropped.
+const CLIENT_QUEUE_LINES: usize = 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const SPAWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Requests sent by clients, one JSON object per line: `{"id": 1, "op": "list"}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PtyDaemonOp {
+    Hello,
+    Create {
+        session_id: String,
+        working_dir: String,
+        launch_config: Box<PtyLaunchConfig>,
+    },
+    Write {
+        session_id: String,
+        data: String,
+    },
+    WriteBytes {
+        session_id: String,
+        #[serde(with = "base64_bytes")]
+        data: Vec<u8>,
+    },
+    Resize {
+        session_id: String,
+        cols: u16,
+        rows: u16,
+    },
+    Attach {
+        session_id: String,
+    },
+    /// Raw output for the session follows as `output_bytes` messages carrying `tag`.
+    SubscribeBytes {
+        session_id: String,
+        tag: u64,
+    },
+    UnsubscribeBytes {
+        session_id: String,
+        subscription_id: u64,
+    },
+    Close {
+        session_id: String,
+        #[serde(default)]
+        options: PtyCloseOptions,
+    },
+    CloseAll {
+        #[serde(default)]
+        options: PtyCloseOptions,
+    },
+    List,
+    /// Empty `session_ids` inspects every PTY.
+    Processes {
+        #[serde(default)]
+        session_ids: Vec<String>,
+    },
+    ClaudeSessions,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PtyDaemonRequest {
+    pub id: u64,
+    #[serde(flatten)]
+    pub op: PtyDaemonOp,
+}
+
+/// Lines written by the daemon. Output, exit and Claude session events go to every
+/// connected client; raw output only to the client that subscribed to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PtyDaemonMessage {
+    Response {
+        id: u64,
+        #[serde(default)]
+        result: Value,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    Output {
+        session_id: String,
+        data: String,
+        offset: u64,
+    },
+    Exit {
+        session_id: String,
+        #[serde(flatten)]
+        exit: PtyExitInfo,
+    },
+    OutputBytes {
+        tag: u64,
+        #[serde(with = "base64_bytes")]
+        data: Vec<u8>,
+    },
+    ClaudeSession {
+        #[serde(flatten)]
+        session: PtyClaudeSession,
+    },
+    ResumeFallback {
+        session_id: String,
+    },
+}
+
+/// Raw bytes travel base64-encoded inside the JSON lines.
+mod base64_bytes {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PtyDaemonHello {
+    protocol: u32,
+    pid: u32,
+}
+
+fn encode_line<T: Serialize>(message: &T) -> Result<Vec<u8>, String> {
+    let mut line = serde_json::to_vec(message)
+        .map_err(|e| format!("Failed to encode PTY daemon message: {}", e))?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+fn write_line(writer: &Mutex<UnixStream>, line: &[u8]) -> std::io::Result<()> {
+    let mut stream = match writer.lock() {
+        Ok(stream) => stream,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    stream.write_all(line)
+}
+
+/// Outgoing lines of one daemon client. A dedicated thread writes them, so a client
+/// that stops reading never blocks PTY readers or the other clients.
+struct ClientWriter {
+    client_id: u64,
+    queue: mpsc::SyncSender<Vec<u8>>,
+    stream: UnixStream,
+}
+
+impl ClientWriter {
+    fn spawn(client_id: u64, stream: &UnixStream) -> Result<Arc<Self>, String> {
+        let clone_stream = || {
+            stream
+                .try_clone()
+                .map_err(|e| format!("Failed to clone PTY daemon client stream: {}", e))
+        };
+        let mut output = clone_stream()?;
+        let (queue, lines) = mpsc::sync_channel::<Vec<u8>>(CLIENT_QUEUE_LINES);
+
+        thread::Builder::new()
+            .name(format!("pty-daemon-writer-{}", client_id))
+            .spawn(move || {
+                for line in lines {
+                    if let Err(error) = output.write_all(&line) {
+                        log::debug!("Dropping PTY daemon client {}: {}", client_id, error);
+                        let _ = output.shutdown(Shutdown::Both);
+                        break;
+                    }
+                }
+            })
+            .map_err(|e| format!("Failed to start PTY daemon writer thread: {}", e))?;
+
+        Ok(Arc::new(Self {
+            client_id,
+            queue,
+            stream: clone_stream()?,
+        }))
+    }
+
+    /// Queues a line without blocking. A client whose queue is full is disconnected,
+    /// which ends its reader thread. Returns false once the client is gone.
+    fn send(&self, line: Vec<u8>) -> bool {
+        match self.queue.try_send(line) {
+            Ok(()) => true,
+            Err(mpsc::TrySendError::Full(_)) => {
+                log::debug!(
+                    "Dropping PTY daemon client {} that stopped reading",
+                    self.client_id
+                );
+                let _ = self.stream.shutdown(Shutdown::Both);
+                false
+            }
+            Err(mpsc::TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+/// Connected clients of a daemon; PTY events are broadcast to all of them.
+#[derive(Default)]
+struct DaemonClients {
+    writers: Mutex<HashMap<u64, Arc<ClientWriter>>>,
+}
+
+impl DaemonClients {
+    fn insert(&self, client_id: u64, writer: Arc<ClientWriter>) {
+        if let Ok(mut writers) = self.writers.lock() {
+            writers.insert(client_id, writer);
+        }
+    }
+
+    fn remove(&self, client_id: u64) {
+        if let Ok(mut writers) = self.writers.lock() {
+            writers.remove(&client_id);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.writers
+            .lock()
+            .map(|writers| writers.is_empty())
+            .unwrap_or(true)
+    }
+
+    fn broadcast(&self, message: &PtyDaemonMessage) {
+        let line = match encode_line(message) {
+            Ok(line) => line,
+            Err(error) => {
+                log::warn!("{}", error);
+                return;
+            }
+        };
+
+        if let Ok(mut writers) = self.writers.lock() {
+            writers.retain(|_, writer| writer.send(line.clone()));
+        }
+    }
+}
+
+impl PtyEventSink for DaemonClients {
+    fn output(&self, session_id: &str, data: &str, offset: u64) -> bool {
+        self.broadcast(&PtyDaemonMessage::Output {
+            session_id: session_id.to_string(),
+            data: data.to_string(),
+            offset,
+        });
+        // Keep reading with nobody attached; the scrollback replays it on attach.
+        true
+    }
+
+    fn exit(&self, session_id: &str, exit: &PtyExitInfo) {
+        self.broadcast(&PtyDaemonMessage::Exit {
+            session_id: session_id.to_string(),
+            exit: exit.clone(),
+        });
+    }
+
+    fn claude_session(&self, session: &PtyClaudeSession) {
+        self.broadcast(&PtyDaemonMessage::ClaudeSession {
+            session: session.clone(),
+        });
+    }
+
+    fn resume_fallback(&self, session_id: &str) {
+        self.broadcast(&PtyDaemonMessage::ResumeFallback {
+            session_id: session_id.to_string(),
+        });
+    }
+}
+
+/// Background process that owns PTY children so they survive app restarts.
+pub struct PtyDaemon;
+
+impl PtyDaemon {
+    /// Socket for this build's protocol. A daemon left over from an older version keeps its
+    /// own socket and exits on its own once its sessions end and it has been idle.
+    pub fn socket_file() -> PathBuf {
+        StorageService::pty_daemon_socket_file(PROTOCOL_VERSION)
+    }
+
+    /// Extracts the socket path when the process was started as the daemon.
+    pub fn socket_arg() -> Option<PathBuf> {
+        let mut args = std::env::args_os().skip(1);
+        if args.next()? != PTY_DAEMON_ARG {
+            return None;
+        }
+        args.next().map(PathBuf::from)
+    }
+
+    /// Serves `socket_path` until there have been no sessions and no clients for `idle_timeout`.
+    pub fn serve(socket_path: &Path, idle_timeout: Duration) -> Result<(), String> {
+        let listener = Self::bind(socket_path)?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure PTY daemon socket: {}", e))?;
+
+        log::info!(
+            "PTY daemon listening pid={} socket={}",
+            std::process::id(),
+            socket_path.display()
+        );
+
+        let clients = Arc::new(DaemonClients::default());
+        let manager = Arc::new(PtyManager::new(
+            Arc::clone(&clients) as Arc<dyn PtyEventSink>
+        ));
+        let mut next_client_id: u64 = 0;
+        let mut idle_since = Some(Instant::now());
+
+        loop {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    next_client_id += 1;
+                    if let Err(error) = Self::spawn_client(
+                        stream,
+                        next_client_id,
+                        Arc::clone(&clients),
+                        Arc::clone(&manager),
+                    ) {
+                        log::warn!("{}", error);
+                    }
+                    continue;
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {}
+                Err(error) => log::warn!("PTY daemon accept failed: {}", error),
+            }
+
+            let idle = clients.is_empty()
+                && manager
+                    .list()
+                    .map(|sessions| sessions.is_empty())
+                    .unwrap_or(false);
+            match idle_since {
+                Some(since) if idle && since.elapsed() >= idle_timeout => break,
+                Some(_) if !idle => idle_since = None,
+                None if idle => idle_since = Some(Instant::now()),
+                _ => {}
+            }
+
+            thread::sleep(ACCEPT_POLL_INTERVAL);
+        }
+
+        log::info!("PTY daemon idle, shutting down");
+        let _ = fs::remove_file(socket_path);
+        Ok(())
+    }
+
+    fn bind(socket_path: &Path) -> Result<UnixListener, String> {
+        if let Some(parent) = socket_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create PTY daemon socket directory: {}", e))?;
+        }
+
+        if socket_path.exists() {
+            if UnixStream::connect(socket_path).is_ok() {
+                return Err(format!(
+                    "PTY daemon already listening on {}",
+                    socket_path.display()
+                ));
+            }
+            // Left behind by a daemon that did not shut down cleanly.
+            fs::remove_file(socket_path)
+                .map_err(|e| format!("Failed to remove stale PTY daemon socket: {}", e))?;
+        }
+
+        let listener = UnixListener::bind(socket_path).map_err(|e| {
+            format!(
+                "Failed to bind PTY daemon socket {}: {}",
+                socket_path.display(),
+                e
+            )
+        })?;
+        fs::set_permissions(socket_path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to restrict PTY daemon socket permissions: {}", e))?;
+
+        Ok(listener)
+    }
+
+    fn spawn_client(
+        stream: UnixStream,
+        client_id: u64,
+        clients: Arc<DaemonClients>,
+        manager: Arc<PtyManager>,
+    ) -> Result<(), String> {
+        // Accepted sockets inherit non-blocking mode on some platforms.
+        stream
+            .set_nonblocking(false)
+            .and_then(|_| stream.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT)))
+            .map_err(|e| format!("Failed to configure PTY daemon client: {}", e))?;
+        let writer = ClientWriter::spawn(client_id, &stream)?;
+        clients.insert(client_id, Arc::clone(&writer));
+
+        thread::Builder::new()
+            .name(format!("pty-daemon-client-{}", client_id))
+            .spawn(move || {
+                log::info!("PTY daemon client connected id={}", client_id);
+                for line in BufReader::new(stream).lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+
+                    let response = match serde_json::from_str::<PtyDaemonRequest>(&line) {
+                        // Closing waits out the grace period and inspecting samples CPU use;
+                        // answer them off this loop so writes to other PTYs keep flowing.
+                        Ok(request)
+                            if matches!(
+                                request.op,
+                                PtyDaemonOp::Close { .. }
+                                    | PtyDaemonOp::CloseAll { .. }
+                                    | PtyDaemonOp::Processes { .. }
+                            ) =>
+                        {
+                            let manager = Arc::clone(&manager);
+                            let writer = Arc::clone(&writer);
+                            let spawned = thread::Builder::new()
+                                .name("pty-daemon-worker".to_string())
+                                .spawn(move || {
+                                    let response = Self::handle(&manager, &writer, request);
+                                    if let Ok(line) = encode_line(&response) {
+                                        writer.send(line);
+                                    }
+                                });
+                            if let Err(error) = spawned {
+                                log::warn!("Failed to start PTY daemon worker thread: {}", error);
+                            }
+                            continue;
+                        }
+                        Ok(request) => Self::handle(&manager, &writer, request),
+                        Err(error) => PtyDaemonMessage::Response {
+                            id: 0,
+                            result: Value::Null,
+                            error: Some(format!("Invalid PTY daemon request: {}", error)),
+                        },
+                    };
+                    let sent = encode_line(&response)
+                        .map_err(|error| log::warn!("{}", error))
+                        .is_ok_and(|line| writer.send(line));
+                    if !sent {
+                        break;
+                    }
+                }
+                clients.remove(client_id);
+                log::info!("PTY daemon client disconnected id={}", client_id);
+            })
+            .map(|_| ())
+            .map_err(|e| format!("Failed to start PTY daemon client thread: {}", e))
+    }
+
+    fn handle(
+        manager: &PtyManager,
+        writer: &Arc<ClientWriter>,
+        request: PtyDaemonRequest,
+    ) -> PtyDaemonMessage {
+        fn to_value<T: Serialize>(result: Result<T, String>) -> Result<Value, String> {
+            result.and_then(|value| serde_json::to_value(value).map_err(|e| e.to_string()))
+        }
+
+        let result = match request.op {
+            PtyDaemonOp::Hello => to_value(Ok(PtyDaemonHello {
+                protocol: PROTOCOL_VERSION,
+                pid: std::process::id(),
+            })),
+            PtyDaemonOp::Create {
+                session_id,
+                working_dir,
+                launch_config,
+            } => to_value(manager.create(&session_id, &working_dir, *launch_config)),
+            PtyDaemonOp::Write { session_id, data } => to_value(manager.write(&session_id, &data)),
+            PtyDaemonOp::WriteBytes { session_id, data } => {
+                to_value(manager.write_bytes(&session_id, &data))
+            }
+            PtyDaemonOp::Resize {
+                session_id,
+                cols,
+                rows,
+            } => to_value(manager.resize(&session_id, cols, rows)),
+            PtyDaemonOp::Attach { session_id } => to_value(manager.attach(&session_id)),
+            PtyDaemonOp::SubscribeBytes { session_id, tag } => {
+                let writer = Arc::clone(writer);
+                let sink: PtyByteSink = Box::new(move |data| {
+                    let message = PtyDaemonMessage::OutputBytes {
+                        tag,
+                        data: data.to_vec(),
+                    };
+                    encode_line(&message).is_ok_and(|line| writer.send(line))
+                });
+                to_value(manager.subscribe_bytes(&session_id, sink))
+            }
+            PtyDaemonOp::UnsubscribeBytes {
+                session_id,
+                subscription_id,
+            } => to_value(manager.unsubscribe_bytes(&session_id, subscription_id)),
+            PtyDaemonOp::Close {
+                session_id,
+                options,
+            } => to_value(manager.close(&session_id, &options)),
+            PtyDaemonOp::CloseAll { options } => to_value(manager.close_all(&options)),
+            PtyDaemonOp::List => to_value(manager.list()),
+            PtyDaemonOp::Processes { session_ids } => to_value(manager.processes(&session_ids)),
+            PtyDaemonOp::ClaudeSessions => to_value(manager.claude_sessions()),
+        };
+
+        match result {
+            Ok(result) => PtyDaemonMessage::Response {
+                id: request.id,
+                result,
+                error: None,
+            },
+            Err(error) => PtyDaemonMessage::Response {
+                id: request.id,
+                result: Value::Null,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+type PendingResponses = Arc<Mutex<HashMap<u64, mpsc::Sender<Result<Value, String>>>>>;
+
+/// Raw-output sinks of one connection, keyed by the tag sent with `subscribe_bytes`.
+#[derive(Default)]
+struct DaemonByteSinks {
+    sinks: HashMap<u64, PtyByteSink>,
+    /// `(session_id, subscription_id)` to tag, for unsubscribing
+    tags: HashMap<(String, u64), u64>,
+}
+
+type SharedByteSinks = Arc<Mutex<DaemonByteSinks>>;
+
+struct DaemonConnection {
+    writer: Mutex<UnixStream>,
+    pending: PendingResponses,
+    byte_sinks: SharedByteSinks,
+    next_id: AtomicU64,
+    closed: Arc<AtomicBool>,
+}
+
+impl DaemonConnection {
+    fn open(socket_path: &Path, events: Arc<dyn PtyEventSink>) -> Result<Arc<Self>, String> {
+        let stream = UnixStream::connect(socket_path).map_err(|e| {
+            format!(
+                "Failed to connect to PTY daemon at {}: {}",
+                socket_path.display(),
+                e
+            )
+        })?;
+        let reader = stream
+            .try_clone()
+            .map_err(|e| format!("Failed to clone PTY daemon stream: {}", e))?;
+
+        let pending: PendingResponses = Arc::new(Mutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        let byte_sinks = SharedByteSinks::default();
+        let reader_pending = Arc::clone(&pending);
+        let reader_byte_sinks = Arc::clone(&byte_sinks);
+        let reader_closed = Arc::clone(&closed);
+
+        thread::Builder::new()
+            .name("pty-daemon-events".to_string())
+            .spawn(move || {
+                Self::read_messages(
+                    reader,
+                    events,
+                    reader_pending,
+                    reader_byte_sinks,
+                    reader_closed,
+                )
+            })
+            .map_err(|e| format!("Failed to start PTY daemon reader: {}", e))?;
+
+        let connection = Arc::new(Self {
+            writer: Mutex::new(stream),
+            pending,
+            byte_sinks,
+            next_id: AtomicU64::new(1),
+            closed,
+        });
+
+        let hello: PtyDaemonHello = connection.call(PtyDaemonOp::Hello)?;
+        if hello.protocol != PROTOCOL_VERSION {
+            return Err(format!(
+                "PTY daemon pid={} speaks protocol {}, expected {}",
+                hello.pid, hello.protocol, PROTOCOL_VERSION
+            ));
+        }
+        log::info!(
+            "Connected to PTY daemon pid={} socket={}",
+            hello.pid,
+            socket_path.display()
+        );
+
+        Ok(connection)
+    }
+
+    fn read_messages(
+        reader: UnixStream,
+        events: Arc<dyn PtyEventSink>,
+        pending: PendingResponses,
+        byte_sinks: SharedByteSinks,
+        closed: Arc<AtomicBool>,
+    ) {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+
+            match serde_json::from_str::<PtyDaemonMessage>(&line) {
+                Ok(PtyDaemonMessage::Response { id, result, error }) => {
+                    let sender = pending.lock().ok().and_then(|mut map| map.remove(&id));
+                    if let Some(sender) = sender {
+                        let _ = sender.send(match error {
+                            Some(error) => Err(error),
+                            None => Ok(result),
+                        });
+                    }
+                }
+                Ok(PtyDaemonMessage::Output {
+                    session_id,
+                    data,
+                    offset,
+                }) => {
+                    events.output(&session_id, &data, offset);
+                }
+                Ok(PtyDaemonMessage::Exit { session_id, exit }) => {
+                    events.exit(&session_id, &exit);
+                }
+                Ok(PtyDaemonMessage::ClaudeSession { session }) => {
+                    events.claude_session(&session);
+                }
+                Ok(PtyDaemonMessage::ResumeFallback { session_id }) => {
+                    events.resume_fallback(&session_id);
+                }
+                Ok(PtyDaemonMessage::OutputBytes { tag, data }) => {
+                    if let Ok(mut byte_sinks) = byte_sinks.lock() {
+                        let delivered = byte_sinks.sinks.get_mut(&tag).map(|sink| sink(&data));
+                        if delivered == Some(false) {
+                            byte_sinks.sinks.remove(&tag);
+                        }
+                    }
+                }
+                Err(error) => log::warn!("Ignoring malformed PTY daemon message: {}", error),
+            }
+        }
+
+        log::warn!("PTY daemon connection closed");
+        closed.store(true, Ordering::SeqCst);
+        // Dropping the senders wakes every caller still waiting for a response.
+        if let Ok(mut map) = pending.lock() {
+            map.clear();
+        }
+        // Subscriptions die with the connection.
+        if let Ok(mut byte_sinks) = byte_sinks.lock() {
+            *byte_sinks = DaemonByteSinks::default();
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    fn call<T: DeserializeOwned>(&self, op: PtyDaemonOp) -> Result<T, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let line = encode_line(&PtyDaemonRequest { id, op })?;
+
+        let (sender, receiver) = mpsc::channel();
+        self.pending
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(id, sender);
+
+        if let Err(error) = write_line(&self.writer, &line) {
+            if let Ok(mut map) = self.pending.lock() {
+                map.remove(&id);
+            }
+            return Err(format!("Failed to send PTY daemon request: {}", error));
+        }
+
+        let result = match receiver.recv_timeout(REQUEST_TIMEOUT) {
+            Ok(result) => result?,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if let Ok(mut map) = self.pending.lock() {
+                    map.remove(&id);
+                }
+                return Err("PTY daemon request timed out".to_string());
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err("PTY daemon disconnected".to_string());
+            }
+        };
+
+        serde_json::from_value(result).map_err(|e| format!("Unexpected PTY daemon response: {}", e))
+    }
+
+    fn subscribe_bytes(
+        &self,
+        session_id: &str,
+        sink: PtyByteSink,
+    ) -> Result<PtyByteSubscription, String> {
+        // Registered first: the daemon sends the snapshot before its response.
+        let tag = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.byte_sinks
+            .lock()
+            .map_err(|e| e.to_string())?
+            .sinks
+            .insert(tag, sink);
+
+        let result: Result<PtyByteSubscription, String> = self.call(PtyDaemonOp::SubscribeBytes {
+            session_id: session_id.to_string(),
+            tag,
+        });
+
+        let mut byte_sinks = self.byte_sinks.lock().map_err(|e| e.to_string())?;
+        match &result {
+            Ok(subscription) => {
+                byte_sinks.tags.insert(
+                    (
+                        subscription.session_id.clone(),
+                        subscription.subscription_id,
+                    ),
+                    tag,
+                );
+            }
+            Err(_) => {
+                byte_sinks.sinks.remove(&tag);
+            }
+        }
+        result
+    }
+
+    fn unsubscribe_bytes(&self, session_id: &str, subscription_id: u64) -> Result<bool, String> {
+        if let Ok(mut byte_sinks) = self.byte_sinks.lock() {
+            let key = (session_id.trim().to_string(), subscription_id);
+            if let Some(tag) = byte_sinks.tags.remove(&key) {
+                byte_sinks.sinks.remove(&tag);
+            }
+        }
+
+        self.call(PtyDaemonOp::UnsubscribeBytes {
+            session_id: session_id.to_string(),
+            subscription_id,
+        })
+    }
+}
+
+impl Drop for DaemonConnection {
+    fn drop(&mut self) {
+        // The reader thread holds a clone of the stream; shut down both directions
+        // so the daemon sees the disconnect.
+        if let Ok(stream) = self.writer.lock() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// App-side handle to the PTY daemon. Reconnects, and respawns the daemon when
+/// allowed, if the connection drops.
+pub struct PtyDaemonClient {
+    socket_path: PathBuf,
+    events: Arc<dyn PtyEventSink>,
+    spawn_if_missing: bool,
+    connection: Mutex<Option<Arc<DaemonConnection>>>,
+}
+
+impl PtyDaemonClient {
+    /// Connects to a daemon that is already listening on `socket_path`.
+    #[cfg(test)]
+    pub fn connect(socket_path: &Path, events: Arc<dyn PtyEventSink>) -> Result<Self, String> {
+        Self::open(socket_path, events, false)
+    }
+
+    /// Connects to the daemon, starting one from the current executable if none is running.
+    pub fn connect_or_spawn(
+        socket_path: &Path,
+        events: Arc<dyn PtyEventSink>,
+    ) -> Result<Self, String> {
+        Self::open(socket_path, events, true)
+    }
+
+    fn open(
+        socket_path: &Path,
+        events: Arc<dyn PtyEventSink>,
+        spawn_if_missing: bool,
+    ) -> Result<Self, String> {
+        let client = Self {
+            socket_path: socket_path.to_path_buf(),
+            events,
+            spawn_if_missing,
+            connection: Mutex::new(None),
+        };
+        client.connection()?;
+        Ok(client)
+    }
+
+    fn connection(&self) -> Result<Arc<DaemonConnection>, String> {
+        let mut guard = self.connection.lock().map_err(|e| e.to_string())?;
+        if let Some(connection) = guard.as_ref().filter(|connection| !connection.is_closed()) {
+            return Ok(Arc::clone(connection));
+        }
+
+        let connection = match DaemonConnection::open(&self.socket_path, Arc::clone(&self.events)) {
+            Ok(connection) => connection,
+            Err(error) if !self.spawn_if_missing => return Err(error),
+            // Something answers on the socket but will not talk to us; a new daemon could
+            // not bind it either, so give up instead of waiting out SPAWN_TIMEOUT.
+            Err(error) if UnixStream::connect(&self.socket_path).is_ok() => return Err(error),
+            Err(error) => {
+                log::info!("{}; starting a new PTY daemon", error);
+                self.spawn_daemon()?
+            }
+        };
+
+        *guard = Some(Arc::clone(&connection));
+        Ok(connection)
+    }
+
+    fn spawn_daemon(&self) -> Result<Arc<DaemonConnection>, String> {
+        let exe = std::env::current_exe()
+            .map_err(|e| format!("Failed to resolve app executable: {}", e))?;
+        let log_file = self.socket_path.with_extension("log");
+        let stderr = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_file)
+            .map(Stdio::from)
+            .unwrap_or_else(|_| Stdio::null());
+
+        // Own process group, so quitting the app does not signal the daemon or its PTYs.
+        Command::new(exe)
+            .arg(PTY_DAEMON_ARG)
+            .arg(&self.socket_path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(stderr)
+            .process_group(0)
+            .spawn()
+            .map_err(|e| format!("Failed to start PTY daemon: {}", e))?;
+
+        let deadline = Instant::now() + SPAWN_TIMEOUT;
+        loop {
+            thread::sleep(ACCEPT_POLL_INTERVAL);
+            match DaemonConnection::open(&self.socket_path, Arc::clone(&self.events)) {
+                Ok(connection) => return Ok(connection),
+                Err(error) if Instant::now() >= deadline => {
+                    return Err(format!("PTY daemon did not start in time: {}", error));
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    fn call<T: DeserializeOwned>(&self, op: PtyDaemonOp) -> Result<T, String> {
+        self.connection()?.call(op)
+    }
+
+    pub fn create(
+        &self,
+        session_id: &str,
+        working_dir: &str,
+        launch_config: PtyLaunchConfig,
+    ) -> Result<bool, String> {
+        self.call(PtyDaemonOp::Create {
+            session_id: session_id.to_string(),
+            working_dir: working_dir.to_string(),
+            launch_config: Box::new(launch_config),
+        })
+    }
+
+    pub fn write(&self, session_id: &str, data: &str) -> Result<(), String> {
+        self.call(PtyDaemonOp::Write {
+            session_id: session_id.to_string(),
+            data: data.to_string(),
+        })
+    }
+
+    pub fn resize(&self, session_id: &str, cols: u16, rows: u16) -> Result<(), String> {
+        self.call(PtyDaemonOp::Resize {
+            session_id: session_id.to_string(),
+            cols,
+            rows,
+        })
+    }
+
+    pub fn attach(&self, session_id: &str) -> Result<PtyAttachment, String> {
+        self.call(PtyDaemonOp::Attach {
+            session_id: session_id.to_string(),
+        })
+    }
+
+    pub fn write_bytes(&self, session_id: &str, data: &[u8]) -> Result<(), String> {
+        self.call(PtyDaemonOp::WriteBytes {
+            session_id: session_id.to_string(),
+            data: data.to_vec(),
+        })
+    }
+
+    pub fn subscribe_bytes(
+        &self,
+        session_id: &str,
+        sink: PtyByteSink,
+    ) -> Result<PtyByteSubscription, String> {
+        self.connection()?.subscribe_bytes(session_id, sink)
+    }
+
+    pub fn unsubscribe_bytes(
+        &self,
+        session_id: &str,
+        subscription_id: u64,
+    ) -> Result<bool, String> {
+        self.connection()?
+            .unsubscribe_bytes(session_id, subscription_id)
+    }
+
+    pub fn close(
+        &self,
+        session_id: &str,
+        options: &PtyCloseOptions,
+    ) -> Result<PtyCloseOutcome, String> {
+        self.call(PtyDaemonOp::Close {
+            session_id: session_id.to_string(),
+            options: options.clone(),
+        })
+    }
+
+    pub fn close_all(&self, options: &PtyCloseOptions) -> Result<Vec<PtyCloseOutcome>, String> {
+        self.call(PtyDaemonOp::CloseAll {
+            options: options.clone(),
+        })
+    }
+
+    pub fn list(&self) -> Result<Vec<String>, String> {
+        self.call(PtyDaemonOp::List)
+    }
+
+    pub fn claude_sessions(&self) -> Result<Vec<PtyClaudeSession>, String> {
+        self.call(PtyDaemonOp::ClaudeSessions)
+    }
+
+    pub fn processes(&self, session_ids: &[String]) -> Result<Vec<PtyProcessReport>, String> {
+        self.call(PtyDaemonOp::Processes {
+            session_ids: session_ids.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ClientWriter, DaemonClients, PtyDaemon, PtyDaemonClient, CLIENT_QUEUE_LINES,
+        PROTOCOL_VERSION, SPAWN_TIMEOUT,
+    };
+    use crate::services::pty_service::{PtyEventSink, PtyExitInfo, PtyLaunchConfig};
+    use crate::services::pty_shutdown::PtyCloseOptions;
+    use crate::services::storage_service::{
+        storage_test_env_lock, unique_test_data_dir, EnvVarGuard, DATA_DIR_OVERRIDE_ENV,
+    };
+    use serde_json::{json, Value};
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn unique_socket_path(name: &str) -> PathBuf {
+        // Kept short: Unix socket paths are limited to ~100 bytes.
+        std::env::temp_dir().join(format!(
+            "ccsm-ptyd-{}-{}.sock",
+            name,
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|value| value.as_nanos() % 1_000_000_000)
+                .unwrap_or_default()
+        ))
+    }
+
+    fn start_daemon(socket_path: &Path) -> thread::JoinHandle<Result<(), String>> {
+        let path = socket_path.to_path_buf();
+        let handle = thread::spawn(move || PtyDaemon::serve(&path, Duration::from_millis(300)));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while UnixStream::connect(socket_path).is_err() {
+            assert!(Instant::now() < deadline, "daemon did not start listening");
+            thread::sleep(Duration::from_millis(20));
+        }
+        handle
+    }
+
+    /// Raw protocol client, as another frontend or a debugging tool would use it.
+    struct RawClient {
+        reader: BufReader<UnixStream>,
+        writer: UnixStream,
+        events: Vec<Value>,
+    }
+
+    impl RawClient {
+        fn connect(socket_path: &Path) -> Self {
+            let writer = UnixStream::connect(socket_path).unwrap();
+            writer
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+            Self {
+                reader: BufReader::new(writer.try_clone().unwrap()),
+                writer,
+                events: Vec::new(),
+            }
+        }
+
+        fn next_message(&mut self) -> Value {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            serde_json::from_str(&line).unwrap()
+        }
+
+        fn send_line(&mut self, line: &str) {
+            self.writer.write_all(line.as_bytes()).unwrap();
+            self.writer.write_all(b"\n").unwrap();
+        }
+
+        /// Sends a request and returns its response, queueing any events read meanwhile.
+        fn request(&mut self, id: u64, request: Value) -> Value {
+            let mut request = request;
+            request["id"] = json!(id);
+            self.send_line(&request.to_string());
+
+            loop {
+                let message = self.next_message();
+                if message["type"] == "response" && message["id"] == id {
+                    return message;
+                }
+                self.events.push(message);
+            }
+        }
+
+        fn wait_for_event(&mut self, matches: impl Fn(&Value) -> bool) -> Value {
+            if let Some(index) = self.events.iter().position(&matches) {
+                return self.events.remove(index);
+            }
+            loop {
+                let message = self.next_message();
+                if matches(&message) {
+                    return message;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn daemon_keeps_sessions_across_client_reconnects() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let _shell = EnvVarGuard::set("SHELL", "/bin/sh");
+        let socket_path = unique_socket_path("raw");
+        let working_dir = std::env::temp_dir();
+        let daemon = start_daemon(&socket_path);
+
+        let mut client = RawClient::connect(&socket_path);
+        let hello = client.request(1, json!({ "op": "hello" }));
+        assert_eq!(hello["result"]["protocol"], PROTOCOL_VERSION);
+
+        let created = client.request(
+            2,
+            json!({
+                "op": "create",
+                "session_id": "s1",
+                "working_dir": working_dir,
+                "launch_config": serde_json::to_value(PtyLaunchConfig::plain()).unwrap(),
+            }),
+        );
+        assert_eq!(created["result"], true, "{}", created);
+
+        let written = client.request(
+            3,
+            json!({ "op": "write", "session_id": "s1", "data": "echo daemon-$((40+2))\n" }),
+        );
+        assert!(written.get("error").is_none(), "{}", written);
+
+        let mut output = String::new();
+        while !output.contains("daemon-42") {
+            let event = client.wait_for_event(|message| message["type"] == "output");
+            assert_eq!(event["session_id"], "s1");
+            output.push_str(event["data"].as_str().unwrap());
+        }
+
+        client.send_line("not json");
+        let rejected = client.wait_for_event(|message| message["type"] == "response");
+        assert!(rejected["error"]
+            .as_str()
+            .unwrap()
+            .contains("Invalid PTY daemon request"));
+        drop(client);
+
+        // The shell keeps running with no client; a new client reattaches to it.
+        let mut client = RawClient::connect(&socket_path);
+        let listed = client.request(5, json!({ "op": "list" }));
+        assert_eq!(listed["result"], json!(["s1"]));
+
+        let attached = client.request(6, json!({ "op": "attach", "session_id": "s1" }));
+        assert!(attached["result"]["data"]
+            .as_str()
+            .unwrap()
+            .contains("daemon-42"));
+        assert!(attached["result"]["offset"].as_u64().unwrap() > 0);
+        assert_eq!(attached["result"]["cols"], 80);
+
+        let closed = client.request(7, json!({ "op": "close", "session_id": "s1" }));
+        assert!(closed.get("error").is_none(), "{}", closed);
+        assert_eq!(closed["result"]["stage"], "graceful");
+        let exit = client.wait_for_event(|message| message["type"] == "exit");
+        assert_eq!(exit["session_id"], "s1");
+        assert_eq!(exit["killed_by_close"], true);
+
+        let missing = client.request(8, json!({ "op": "attach", "session_id": "s1" }));
+        assert!(missing["error"].as_str().unwrap().contains("not found"));
+        drop(client);
+
+        // No sessions and no clients: the daemon exits and removes its socket.
+        assert_eq!(daemon.join().unwrap(), Ok(()));
+        assert!(!socket_path.exists());
+    }
+
+    struct ChannelEvents(Mutex<mpsc::Sender<(String, String)>>);
+
+    impl PtyEventSink for ChannelEvents {
+        fn output(&self, session_id: &str, data: &str, _offset: u64) -> bool {
+            let _ = self
+                .0
+                .lock()
+                .unwrap()
+                .send((session_id.to_string(), data.to_string()));
+            true
+        }
+
+        fn exit(&self, _session_id: &str, _exit: &PtyExitInfo) {}
+    }
+
+    #[test]
+    fn client_forwards_daemon_events_to_sink() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let _shell = EnvVarGuard::set("SHELL", "/bin/sh");
+        let socket_path = unique_socket_path("client");
+        let daemon = start_daemon(&socket_path);
+
+        assert!(PtyDaemon::serve(&socket_path, Duration::from_millis(10))
+            .unwrap_err()
+            .contains("already listening"));
+
+        let (sender, receiver) = mpsc::channel();
+        let client =
+            PtyDaemonClient::connect(&socket_path, Arc::new(ChannelEvents(Mutex::new(sender))))
+                .unwrap();
+
+        let working_dir = std::env::temp_dir();
+        assert!(client
+            .create(
+                "s2",
+                working_dir.to_str().unwrap(),
+                PtyLaunchConfig::plain()
+            )
+            .unwrap());
+        // Creating an existing session is a no-op, which is how the app reattaches.
+        assert!(client
+            .create(
+                "s2",
+                working_dir.to_str().unwrap(),
+                PtyLaunchConfig::plain()
+            )
+            .unwrap());
+        client.resize("s2", 120, 40).unwrap();
+        client.write("s2", "echo client-$((6*7))\n").unwrap();
+
+        let mut output = String::new();
+        while !output.contains("client-42") {
+            let (session_id, data) = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+            assert_eq!(session_id, "s2");
+            output.push_str(&data);
+        }
+        assert_eq!(client.attach("s2").unwrap().cols, 120);
+
+        // Raw output keeps bytes that are not valid UTF-8.
+        let (byte_sender, byte_receiver) = mpsc::channel::<Vec<u8>>();
+        let subscription = client
+            .subscribe_bytes(
+                "s2",
+                Box::new(move |data| byte_sender.send(data.to_vec()).is_ok()),
+            )
+            .unwrap();
+        let snapshot = byte_receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(String::from_utf8_lossy(&snapshot).contains("client-42"));
+        assert_eq!(subscription.offset, snapshot.len() as u64);
+
+        client
+            .write_bytes("s2", b"printf 'raw-\\377\\n'\n")
+            .unwrap();
+        let mut raw = Vec::new();
+        while !raw.windows(5).any(|window| window == b"raw-\xff") {
+            raw.extend(byte_receiver.recv_timeout(Duration::from_secs(10)).unwrap());
+        }
+        assert!(client
+            .unsubscribe_bytes("s2", subscription.subscription_id)
+            .unwrap());
+
+        client.close_all(&PtyCloseOptions::default()).unwrap();
+        assert!(client.list().unwrap().is_empty());
+        drop(client);
+
+        assert_eq!(daemon.join().unwrap(), Ok(()));
+    }
+
+    #[test]
+    fn broadcast_drops_a_stalled_client_without_blocking_the_others() {
+        let clients = DaemonClients::default();
+        // Never read from: its socket buffer and then its queue fill up.
+        let (stalled, _stalled_peer) = UnixStream::pair().unwrap();
+        let (reading, reading_peer) = UnixStream::pair().unwrap();
+        clients.insert(1, ClientWriter::spawn(1, &stalled).unwrap());
+        clients.insert(2, ClientWriter::spawn(2, &reading).unwrap());
+
+        let (received, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reading_peer).lines() {
+                if line.is_err() || received.send(()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        // Paced by the reading client, so only the stalled one can fall behind.
+        let data = "x".repeat(8 * 1024);
+        for offset in 0..CLIENT_QUEUE_LINES * 2 {
+            clients.output("s1", &data, offset as u64);
+            receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        }
+
+        let remaining: Vec<u64> = clients.writers.lock().unwrap().keys().copied().collect();
+        assert_eq!(remaining, vec![2]);
+    }
+
+    #[test]
+    fn client_fails_fast_on_a_daemon_with_another_protocol() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let data_dir = unique_test_data_dir("ptyd-protocol");
+        std::env::set_var(DATA_DIR_OVERRIDE_ENV, &data_dir);
+        let socket_path = PtyDaemon::socket_file();
+        std::env::remove_var(DATA_DIR_OVERRIDE_ENV);
+        assert_eq!(
+            socket_path.file_name().unwrap().to_str().unwrap(),
+            format!("pty-daemon-v{}.sock", PROTOCOL_VERSION)
+        );
+
+        // Stands in for a daemon from another app version holding the socket.
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let old_daemon = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let request: Value = serde_json::from_str(&line).unwrap();
+            let response = json!({
+                "type": "response",
+                "id": request["id"],
+                "result": {"protocol": PROTOCOL_VERSION - 1, "pid": 1},
+            });
+            writeln!(&stream, "{}", response).unwrap();
+            thread::sleep(Duration::from_millis(200));
+        });
+
+        let (sender, _receiver) = mpsc::channel();
+        let started = Instant::now();
+        let error = PtyDaemonClient::connect_or_spawn(
+            &socket_path,
+            Arc::new(ChannelEvents(Mutex::new(sender))),
+        )
+        .err()
+        .unwrap();
+
+        assert!(error.contains("speaks protocol"), "{}", error);
+        assert!(
+            started.elapsed() < SPAWN_TIMEOUT,
+            "waited {:?} for a daemon that could never start",
+            started.elapsed()
+        );
+        old_daemon.join().unwrap();
+        let _ = std::fs::remove_dir_all(data_dir);
+    }
+}
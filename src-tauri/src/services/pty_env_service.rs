@@ -0,0 +1,329 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+const DOTENV_FILE_NAME: &str = ".env";
+
+#[derive(Debug, Clone, PartialEq)]
+struct DotenvEntry {
+    key: String,
+    value: String,
+    /// Single-quoted values are taken literally
+    expand: bool,
+}
+
+/// Builds the extra environment for an embedded terminal from a project `.env`
+/// file and a stored session's variables, expanding `$VAR` references.
+pub struct PtyEnvService;
+
+impl PtyEnvService {
+    /// Variables from `<dotenv_dir>/.env` (when given) overlaid with `session_variables`.
+    /// References resolve against earlier entries first, then the app's environment.
+    pub fn resolve(
+        dotenv_dir: Option<&Path>,
+        session_variables: &HashMap<String, String>,
+    ) -> Result<BTreeMap<String, String>, String> {
+        Self::resolve_with(dotenv_dir, session_variables, |key| std::env::var(key).ok())
+    }
+
+    fn resolve_with<F>(
+        dotenv_dir: Option<&Path>,
+        session_variables: &HashMap<String, String>,
+        base_lookup: F,
+    ) -> Result<BTreeMap<String, String>, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut entries = match dotenv_dir {
+            Some(dir) => Self::read_dotenv(&dir.join(DOTENV_FILE_NAME))?,
+            None => Vec::new(),
+        };
+
+        // Sorted so references between session variables resolve deterministically.
+        let mut session_entries: Vec<(&String, &String)> = session_variables.iter().collect();
+        session_entries.sort();
+        entries.extend(session_entries.into_iter().map(|(key, value)| DotenvEntry {
+            key: key.clone(),
+            value: value.clone(),
+            expand: true,
+        }));
+
+        let mut resolved = BTreeMap::new();
+        for entry in entries {
+            if !Self::is_valid_key(&entry.key) {
+                log::warn!("Ignoring invalid environment variable name: {}", entry.key);
+                continue;
+            }
+
+            let value = if entry.expand {
+                Self::expand(&entry.value, &|key| {
+                    resolved.get(key).cloned().or_else(|| base_lookup(key))
+                })
+            } else {
+                entry.value
+            };
+            resolved.insert(entry.key, value);
+        }
+
+        Ok(resolved)
+    }
+
+    fn read_dotenv(path: &Path) -> Result<Vec<DotenvEntry>, String> {
+        if !path.is_file() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Ok(Self::parse_dotenv(&content, path))
+    }
+
+    /// Parses `KEY=value` lines with optional `export`, `#` comments and quoting.
+    /// Malformed lines are skipped with a warning.
+    fn parse_dotenv(content: &str, path: &Path) -> Vec<DotenvEntry> {
+        let mut entries = Vec::new();
+
+        for (index, raw_line) in content.lines().enumerate() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+            let Some((key, raw_value)) = line.split_once('=') else {
+                log::warn!(
+                    "Skipping malformed line {} in {}",
+                    index + 1,
+                    path.display()
+                );
+                continue;
+            };
+
+            let raw_value = raw_value.trim();
+            let (value, expand) = if let Some(inner) = Self::quoted(raw_value, '\'') {
+                (inner.to_string(), false)
+            } else if let Some(inner) = Self::quoted(raw_value, '"') {
+                (Self::unescape_double_quoted(inner), true)
+            } else {
+                // Unquoted values end at an inline ` #` comment.
+                let value = match raw_value.find(" #") {
+                    Some(position) => raw_value[..position].trim_end(),
+                    None => raw_value,
+                };
+                (value.to_string(), true)
+            };
+
+            entries.push(DotenvEntry {
+                key: key.trim().to_string(),
+                value,
+                expand,
+            });
+        }
+
+        entries
+    }
+
+    fn quoted(value: &str, quote: char) -> Option<&str> {
+        let inner = value.strip_prefix(quote)?;
+        let end = inner.rfind(quote)?;
+        // Anything after the closing quote must be a comment.
+        let rest = inner[end + 1..].trim_start();
+        if !rest.is_empty() && !rest.starts_with('#') {
+            return None;
+        }
+        Some(&inner[..end])
+    }
+
+    fn unescape_double_quoted(value: &str) -> String {
+        let mut result = String::with_capacity(value.len());
+        let mut chars = value.chars();
+
+        while let Some(ch) = chars.next() {
+            if ch != '\\' {
+                result.push(ch);
+                continue;
+            }
+
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('"') => result.push('"'),
+                Some('\\') => result.push('\\'),
+                // Keep `\$` escaped for `expand`.
+                Some(other) => {
+                    result.push('\\');
+                    result.push(other);
+                }
+                None => result.push('\\'),
+            }
+        }
+
+        result
+    }
+
+    fn is_valid_key(key: &str) -> bool {
+        let mut chars = key.chars();
+        matches!(chars.next(), Some(ch) if ch.is_ascii_alphabetic() || ch == '_')
+            && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+    }
+
+    /// Expands `$VAR`, `${VAR}`, `${VAR:-default}` and a leading `~/`.
+    /// Unknown variables expand to an empty string; `\$` is a literal `$`.
+    fn expand(value: &str, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+        let mut result = String::with_capacity(value.len());
+        let mut rest = value;
+
+        if rest == "~" || rest.starts_with("~/") {
+            if let Some(home) = lookup("HOME") {
+                result.push_str(&home);
+                rest = &rest[1..];
+            }
+        }
+
+        while let Some(position) = rest.find(['$', '\\']) {
+            result.push_str(&rest[..position]);
+            let tail = &rest[position..];
+
+            if let Some(escaped) = tail.strip_prefix("\\$") {
+                result.push('$');
+                rest = escaped;
+                continue;
+            }
+            if let Some(after_backslash) = tail.strip_prefix('\\') {
+                result.push('\\');
+                rest = after_backslash;
+                continue;
+            }
+
+            let after_dollar = &tail[1..];
+            if let Some(braced) = after_dollar.strip_prefix('{') {
+                let Some(end) = braced.find('}') else {
+                    result.push_str(tail);
+                    rest = "";
+                    break;
+                };
+                let expression = &braced[..end];
+                let (name, default) = match expression.split_once(":-") {
+                    Some((name, default)) => (name, Some(default)),
+                    None => (expression, None),
+                };
+                let value = lookup(name).filter(|value| !value.is_empty());
+                match (value, default) {
+                    (Some(value), _) => result.push_str(&value),
+                    (None, Some(default)) => result.push_str(&Self::expand(default, lookup)),
+                    (None, None) => {}
+                }
+                rest = &braced[end + 1..];
+                continue;
+            }
+
+            let name_len = after_dollar
+                .char_indices()
+                .take_while(|(index, ch)| {
+                    *ch == '_' || ch.is_ascii_alphabetic() || (*index > 0 && ch.is_ascii_digit())
+                })
+                .count();
+            if name_len == 0 {
+                result.push('$');
+            } else if let Some(value) = lookup(&after_dollar[..name_len]) {
+                result.push_str(&value);
+            }
+            rest = &after_dollar[name_len..];
+        }
+
+        result.push_str(rest);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DotenvEntry, PtyEnvService};
+    use crate::services::storage_service::unique_test_data_dir;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::Path;
+
+    fn lookup(key: &str) -> Option<String> {
+        match key {
+            "HOME" => Some("/home/me".to_string()),
+            "USER" => Some("me".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn parse_dotenv_handles_quotes_comments_and_export() {
+        let content = r#"
+# API settings
+export ANTHROPIC_BASE_URL=https://proxy.example.com/v1 # team proxy
+ANTHROPIC_MODEL="claude-sonnet-4 \"fast\"\n"
+LITERAL='$HOME stays'
+  SPACED = value with spaces
+not a variable
+"#;
+
+        let entries = PtyEnvService::parse_dotenv(content, Path::new(".env"));
+        let entry = |key: &str, value: &str, expand: bool| DotenvEntry {
+            key: key.to_string(),
+            value: value.to_string(),
+            expand,
+        };
+
+        assert_eq!(
+            entries,
+            vec![
+                entry("ANTHROPIC_BASE_URL", "https://proxy.example.com/v1", true),
+                entry("ANTHROPIC_MODEL", "claude-sonnet-4 \"fast\"\n", true),
+                entry("LITERAL", "$HOME stays", false),
+                entry("SPACED", "value with spaces", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_supports_braces_defaults_home_and_escapes() {
+        let expand = |value: &str| PtyEnvService::expand(value, &lookup);
+
+        assert_eq!(expand("$HOME/bin:${USER}x"), "/home/me/bin:mex");
+        assert_eq!(expand("~/work"), "/home/me/work");
+        assert_eq!(expand("a~/b"), "a~/b");
+        assert_eq!(expand("${MISSING:-fallback-$USER}"), "fallback-me");
+        assert_eq!(expand("$MISSING|$1|$"), "|$1|$");
+        assert_eq!(expand(r"cost \$5 C:\tmp"), r"cost $5 C:\tmp");
+        assert_eq!(expand("${UNTERMINATED"), "${UNTERMINATED");
+    }
+
+    #[test]
+    fn resolve_overlays_session_variables_on_project_dotenv() {
+        let project_dir = unique_test_data_dir("pty-env-resolve");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(
+            project_dir.join(".env"),
+            "API_HOST=api.internal\nAPI_URL=https://$API_HOST/v1\nANTHROPIC_MODEL=haiku\nLITERAL='$API_HOST'\n1BAD=x\n",
+        )
+        .unwrap();
+
+        let session_variables = HashMap::from([
+            ("ANTHROPIC_MODEL".to_string(), "opus".to_string()),
+            (
+                "CACHE_DIR".to_string(),
+                "$HOME/.cache/${API_HOST}".to_string(),
+            ),
+        ]);
+
+        let resolved =
+            PtyEnvService::resolve_with(Some(&project_dir), &session_variables, lookup).unwrap();
+
+        assert_eq!(resolved["API_URL"], "https://api.internal/v1");
+        assert_eq!(resolved["ANTHROPIC_MODEL"], "opus");
+        assert_eq!(resolved["CACHE_DIR"], "/home/me/.cache/api.internal");
+        assert_eq!(resolved["LITERAL"], "$API_HOST");
+        assert!(!resolved.contains_key("1BAD"));
+
+        let without_dotenv = PtyEnvService::resolve_with(None, &session_variables, lookup).unwrap();
+        assert_eq!(without_dotenv["CACHE_DIR"], "/home/me/.cache/");
+
+        let _ = fs::remove_dir_all(project_dir);
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// CPU use is measured over this window on Linux.
+const CPU_SAMPLE_WINDOW: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PtyProcessInfo {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub process_group: Option<i32>,
+    pub name: String,
+    pub command_line: String,
+    /// Percent of one core: sampled on Linux, lifetime average from `ps` elsewhere
+    pub cpu_percent: Option<f64>,
+    /// Resident set size
+    pub memory_bytes: Option<u64>,
+}
+
+/// What a PTY is running: its shell, the terminal's foreground job and every
+/// descendant process with their resource use.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PtyProcessReport {
+    pub session_id: String,
+    pub shell_pid: Option<u32>,
+    pub foreground_process_group: Option<i32>,
+    /// Leader of the foreground group (or its first member when the leader is gone)
+    pub foreground: Option<PtyProcessInfo>,
+    /// Shell and descendants, parents before children
+    pub processes: Vec<PtyProcessInfo>,
+    /// The foreground job is the shell itself, waiting at its prompt
+    pub idle: bool,
+    pub running_claude: bool,
+    pub cpu_percent: Option<f64>,
+    pub memory_bytes: Option<u64>,
+}
+
+/// A PTY to inspect, as known to the `PtyManager` that owns it.
+pub struct PtyProcessTarget {
+    pub session_id: String,
+    pub shell_pid: Option<u32>,
+    pub foreground_process_group: Option<i32>,
+}
+
+/// Process table snapshots for embedded terminals: `/proc` on Linux, `ps` on other
+/// Unix systems. Elsewhere only the shell pid is reported.
+pub struct PtyProcessService;
+
+impl PtyProcessService {
+    /// Blocks for the CPU sample window on Linux.
+    pub fn inspect(targets: &[PtyProcessTarget]) -> Vec<PtyProcessReport> {
+        let table = if targets.iter().any(|target| target.shell_pid.is_some()) {
+            Self::snapshot().unwrap_or_else(|error| {
+                log::warn!("Failed reading process table: {}", error);
+                HashMap::new()
+            })
+        } else {
+            HashMap::new()
+        };
+
+        targets
+            .iter()
+            .map(|target| Self::report(target, &table))
+            .collect()
+    }
+
+    fn report(target: &PtyProcessTarget, table: &HashMap<u32, PtyProcessInfo>) -> PtyProcessReport {
+        let processes = target
+            .shell_pid
+            .map(|pid| Self::descendants(pid, table))
+            .unwrap_or_default();
+        let shell_group = target
+            .shell_pid
+            .and_then(|pid| table.get(&pid))
+            .and_then(|shell| shell.process_group);
+        let foreground_process_group = target.foreground_process_group.or(shell_group);
+
+        let foreground = foreground_process_group.and_then(|pgid| {
+            let leader = u32::try_from(pgid).ok().and_then(|pid| table.get(&pid));
+            leader
+                .or_else(|| {
+                    processes
+                        .iter()
+                        .find(|process| process.process_group == Some(pgid))
+                })
+                .cloned()
+        });
+
+        let cpu_values: Vec<f64> = processes.iter().filter_map(|p| p.cpu_percent).collect();
+        let cpu_percent = (!cpu_values.is_empty()).then(|| cpu_values.iter().sum());
+        let memory_values: Vec<u64> = processes.iter().filter_map(|p| p.memory_bytes).collect();
+        let memory_bytes = (!memory_values.is_empty()).then(|| memory_values.iter().sum());
+
+        PtyProcessReport {
+            session_id: target.session_id.clone(),
+            shell_pid: target.shell_pid,
+            idle: shell_group.is_some() && foreground_process_group == shell_group,
+            running_claude: processes
+                .iter()
+                .any(|process| is_claude_command(&process.command_line)),
+            foreground_process_group,
+            foreground,
+            processes,
+            cpu_percent,
+            memory_bytes,
+        }
+    }
+
+    fn descendants(root: u32, table: &HashMap<u32, PtyProcessInfo>) -> Vec<PtyProcessInfo> {
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for process in table.values() {
+            if let Some(parent) = process.parent_pid {
+                children.entry(parent).or_default().push(process.pid);
+            }
+        }
+
+        let mut result = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue = vec![root];
+        while let Some(pid) = queue.pop() {
+            if !seen.insert(pid) {
+                continue;
+            }
+            let Some(process) = table.get(&pid) else {
+                continue;
+            };
+            result.push(process.clone());
+            if let Some(child_pids) = children.get(&pid) {
+                let mut child_pids = child_pids.clone();
+                child_pids.sort_unstable_by(|a, b| b.cmp(a));
+                queue.extend(child_pids);
+            }
+        }
+        result
+    }
+
+    #[cfg(target_os = "linux")]
+    fn snapshot() -> Result<HashMap<u32, PtyProcessInfo>, String> {
+        proc_fs::snapshot(CPU_SAMPLE_WINDOW)
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    fn snapshot() -> Result<HashMap<u32, PtyProcessInfo>, String> {
+        let _ = CPU_SAMPLE_WINDOW;
+        let output = std::process::Command::new("ps")
+            .args(["-A", "-o", "pid=,ppid=,pgid=,rss=,%cpu=,args="])
+            .output()
+            .map_err(|e| format!("Failed to run ps: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("ps exited with {}", output.status));
+        }
+        Ok(parse_ps_output(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    #[cfg(not(unix))]
+    fn snapshot() -> Result<HashMap<u32, PtyProcessInfo>, String> {
+        let _ = CPU_SAMPLE_WINDOW;
+        Ok(HashMap::new())
+    }
+}
+
+/// `claude` itself, or a runtime such as `node` running the `claude` script.
+fn is_claude_command(command_line: &str) -> bool {
+    command_line
+        .split_whitespace()
+        .take(2)
+        .filter_map(|arg| arg.rsplit('/').next())
+        .any(|name| name == "claude")
+}
+
+/// Parses `ps -o pid=,ppid=,pgid=,rss=,%cpu=,args=` lines; `rss` is in KiB.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn parse_ps_output(output: &str) -> HashMap<u32, PtyProcessInfo> {
+    let mut table = HashMap::new();
+    for line in output.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(pid), Some(ppid), Some(pgid), Some(rss), Some(cpu)) = (
+            fields.next().and_then(|value| value.parse::<u32>().ok()),
+            fields.next().and_then(|value| value.parse::<u32>().ok()),
+            fields.next().and_then(|value| value.parse::<i32>().ok()),
+            fields.next().and_then(|value| value.parse::<u64>().ok()),
+            fields.next().and_then(|value| value.parse::<f64>().ok()),
+        ) else {
+            continue;
+        };
+        let command_line = fields.collect::<Vec<_>>().join(" ");
+        let name = command_line
+            .split_whitespace()
+            .next()
+            .and_then(|program| program.rsplit('/').next())
+            .unwrap_or_default()
+            .to_string();
+
+        table.insert(
+            pid,
+            PtyProcessInfo {
+                pid,
+                parent_pid: Some(ppid),
+                process_group: Some(pgid),
+                name,
+                command_line,
+                cpu_percent: Some(cpu),
+                memory_bytes: Some(rss * 1024),
+            },
+        );
+    }
+    table
+}
+
+#[cfg(target_os = "linux")]
+mod proc_fs {
+    use super::PtyProcessInfo;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::time::{Duration, Instant};
+
+    /// Fields of `/proc/<pid>/stat` after the command name.
+    pub(super) struct ProcStat {
+        pub name: String,
+        pub parent_pid: u32,
+        pub process_group: i32,
+        pub cpu_ticks: u64,
+        pub rss_pages: u64,
+    }
+
+    pub(super) fn parse_stat(content: &str) -> Option<ProcStat> {
+        // The name is wrapped in parentheses and may itself contain spaces or ')'.
+        let open = content.find('(')?;
+        let close = content.rfind(')')?;
+        let name = content.get(open + 1..close)?.to_string();
+        let fields: Vec<&str> = content.get(close + 1..)?.split_whitespace().collect();
+        let field = |index: usize| {
+            fields
+                .get(index)
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+
+        Some(ProcStat {
+            name,
+            parent_pid: u32::try_from(field(1)?).ok()?,
+            process_group: i32::try_from(field(2)?).ok()?,
+            cpu_ticks: field(11)? + field(12)?,
+            rss_pages: field(21)?,
+        })
+    }
+
+    fn read_stats() -> Result<HashMap<u32, ProcStat>, String> {
+        let entries = fs::read_dir("/proc").map_err(|e| format!("Failed to read /proc: {}", e))?;
+        Ok(entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter_map(|pid| {
+                let content = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+                Some((pid, parse_stat(&content)?))
+            })
+            .collect())
+    }
+
+    pub(super) fn snapshot(window: Duration) -> Result<HashMap<u32, PtyProcessInfo>, String> {
+        let first = read_stats()?;
+        let started = Instant::now();
+        std::thread::sleep(window);
+        let second = read_stats()?;
+        let elapsed = started.elapsed().as_secs_f64();
+
+        // SAFETY: sysconf only reads configuration values.
+        let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+
+        Ok(second
+            .into_iter()
+            .map(|(pid, stat)| {
+                let cpu_percent = first.get(&pid).map(|before| {
+                    let ticks = stat.cpu_ticks.saturating_sub(before.cpu_ticks) as f64;
+                    ticks / ticks_per_second / elapsed * 100.0
+                });
+                let command_line = fs::read(format!("/proc/{}/cmdline", pid))
+                    .map(|raw| {
+                        raw.split(|byte| *byte == 0)
+                            .filter(|arg| !arg.is_empty())
+                            .map(String::from_utf8_lossy)
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                    .unwrap_or_default();
+
+                let info = PtyProcessInfo {
+                    pid,
+                    parent_pid: Some(stat.parent_pid),
+                    process_group: Some(stat.process_group),
+                    command_line: if command_line.is_empty() {
+                        stat.name.clone()
+                    } else {
+                        command_line
+                    },
+                    name: stat.name,
+                    cpu_percent,
+                    memory_bytes: Some(stat.rss_pages * page_size),
+                };
+                (pid, info)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_claude_command, PtyProcessInfo, PtyProcessService, PtyProcessTarget};
+    use std::collections::HashMap;
+
+    fn process(pid: u32, parent_pid: u32, process_group: i32, command: &str) -> PtyProcessInfo {
+        PtyProcessInfo {
+            pid,
+            parent_pid: Some(parent_pid),
+            process_group: Some(process_group),
+            name: command.split(' ').next().unwrap().to_string(),
+            command_line: command.to_string(),
+            cpu_percent: Some(10.0),
+            memory_bytes: Some(1024),
+        }
+    }
+
+    #[test]
+    fn report_walks_the_tree_and_finds_the_foreground_job() {
+        let table: HashMap<u32, PtyProcessInfo> = [
+            process(1, 0, 1, "init"),
+            process(100, 1, 100, "zsh -l"),
+            process(200, 100, 200, "node /usr/local/bin/claude --resume abc"),
+            process(201, 200, 200, "rg TODO"),
+            process(300, 1, 300, "unrelated"),
+        ]
+        .into_iter()
+        .map(|process| (process.pid, process))
+        .collect();
+
+        let busy = PtyProcessService::report(
+            &PtyProcessTarget {
+                session_id: "busy".to_string(),
+                shell_pid: Some(100),
+                foreground_process_group: Some(200),
+            },
+            &table,
+        );
+        let pids: Vec<u32> = busy.processes.iter().map(|process| process.pid).collect();
+        assert_eq!(pids, vec![100, 200, 201]);
+        assert_eq!(
+            busy.foreground.as_ref().map(|process| process.pid),
+            Some(200)
+        );
+        assert!(busy.running_claude);
+        assert!(!busy.idle);
+        assert_eq!(busy.cpu_percent, Some(30.0));
+        assert_eq!(busy.memory_bytes, Some(3072));
+
+        let idle = PtyProcessService::report(
+            &PtyProcessTarget {
+                session_id: "idle".to_string(),
+                shell_pid: Some(300),
+                foreground_process_group: None,
+            },
+            &table,
+        );
+        assert!(idle.idle);
+        assert!(!idle.running_claude);
+        assert_eq!(idle.foreground_process_group, Some(300));
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    #[test]
+    fn parse_ps_output_reads_columns_and_full_command() {
+        let table = super::parse_ps_output(
+            "  PID  PPID  PGID   RSS  %CPU ARGS\n  42     1    42  2048  12.5 /opt/homebrew/bin/claude --continue\n  bad line\n",
+        );
+        assert_eq!(table.len(), 1);
+        let claude = &table[&42];
+        assert_eq!(claude.name, "claude");
+        assert_eq!(claude.command_line, "/opt/homebrew/bin/claude --continue");
+        assert_eq!(claude.memory_bytes, Some(2048 * 1024));
+        assert_eq!(claude.cpu_percent, Some(12.5));
+    }
+
+    #[test]
+    fn claude_is_detected_directly_or_behind_a_runtime() {
+        assert!(is_claude_command("claude"));
+        assert!(is_claude_command(
+            "/usr/bin/node /home/me/.npm/bin/claude -c"
+        ));
+        assert!(!is_claude_command("vim claude.md"));
+        assert!(!is_claude_command("bash -c echo claude"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn proc_stat_parsing_handles_odd_names_and_live_children() {
+        let stat = super::proc_fs::parse_stat(
+            "4242 (tmux: (server) x) S 1 4242 4242 0 -1 4194560 1 2 0 0 70 30 0 0 20 0 1 0 99 1000 250 18446744073709551615",
+        )
+        .unwrap();
+        assert_eq!(stat.name, "tmux: (server) x");
+        assert_eq!(stat.parent_pid, 1);
+        assert_eq!(stat.process_group, 4242);
+        assert_eq!(stat.cpu_ticks, 100);
+        assert_eq!(stat.rss_pages, 250);
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        let reports = PtyProcessService::inspect(&[PtyProcessTarget {
+            session_id: "self".to_string(),
+            shell_pid: Some(std::process::id()),
+            foreground_process_group: None,
+        }]);
+        let _ = child.kill();
+        let _ = child.wait();
+
+        let sleep = reports[0]
+            .processes
+            .iter()
+            .find(|process| process.pid == child.id())
+            .expect("child in process tree");
+        assert_eq!(sleep.command_line, "sleep 5");
+        assert!(sleep.memory_bytes.unwrap_or_default() > 0);
+        assert!(sleep.cpu_percent.is_some());
+    }
+}
@@ -0,0 +1,524 @@
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::pty_recording::PtyRecording;
+use crate::services::pty_service::PtyEventSink;
+use crate::services::storage_service::StorageService;
+
+const CAST_EXTENSION: &str = "cast";
+const MAX_REPLAY_SPEED: f64 = 100.0;
+const MIN_REPLAY_SPEED: f64 = 0.1;
+
+/// asciicast v2 header line. `title` carries the PTY session id.
+#[derive(Debug, Serialize, Deserialize)]
+struct CastHeader {
+    version: u32,
+    width: u16,
+    height: u16,
+    #[serde(default)]
+    timestamp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    idle_time_limit: Option<f64>,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    env: Value,
+}
+
+/// Appends a PTY session's output and resizes to an asciicast v2 file.
+pub struct PtyRecorder {
+    writer: LineWriter<File>,
+    started: Instant,
+    failed: bool,
+}
+
+impl PtyRecorder {
+    pub fn create(
+        session_id: &str,
+        cols: u16,
+        rows: u16,
+        shell: &str,
+        term: &str,
+    ) -> Result<Self, String> {
+        let recordings_dir = StorageService::pty_recordings_dir();
+        fs::create_dir_all(&recordings_dir)
+            .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+
+        let now = Utc::now();
+        let file_path = recordings_dir.join(format!(
+            "{}-{}.{}",
+            sanitize_file_component(session_id),
+            now.format("%Y%m%d-%H%M%S-%3f"),
+            CAST_EXTENSION
+        ));
+        let file = File::create(&file_path)
+            .map_err(|e| format!("Failed to create recording {}: {}", file_path.display(), e))?;
+
+        let header = CastHeader {
+            version: 2,
+            width: cols,
+            height: rows,
+            timestamp: now.timestamp(),
+            idle_time_limit: None,
+            title: session_id.to_string(),
+            env: serde_json::json!({ "SHELL": shell, "TERM": term }),
+        };
+        let header_line = serde_json::to_string(&header)
+            .map_err(|e| format!("Failed to encode recording header: {}", e))?;
+
+        let mut writer = LineWriter::new(file);
+        writeln!(writer, "{}", header_line)
+            .map_err(|e| format!("Failed to write recording header: {}", e))?;
+
+        log::info!(
+            "PTY recording started sid={} file={}",
+            session_id,
+            file_path.display()
+        );
+
+        Ok(Self {
+            writer,
+            started: Instant::now(),
+            failed: false,
+        })
+    }
+
+    pub fn output(&mut self, data: &str) {
+        self.write_event("o", data);
+    }
+
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.write_event("r", &format!("{}x{}", cols, rows));
+    }
+
+    fn write_event(&mut self, code: &str, data: &str) {
+        if self.failed {
+            return;
+        }
+
+        let elapsed = (self.started.elapsed().as_secs_f64() * 1_000_000.0).round() / 1_000_000.0;
+        let result = serde_json::to_string(&(elapsed, code, data))
+            .map_err(|e| e.to_string())
+            .and_then(|line| writeln!(self.writer, "{}", line).map_err(|e| e.to_string()));
+
+        // Stop after the first failure (e.g. disk full) instead of logging every chunk.
+        if let Err(error) = result {
+            log::warn!("PTY recording stopped: {}", error);
+            self.failed = true;
+        }
+    }
+}
+
+struct CastFrame {
+    time: f64,
+    event: CastEvent,
+}
+
+enum CastEvent {
+    Output(String),
+    Resize { cols: u16, rows: u16 },
+}
+
+pub struct PtyRecordingService;
+
+impl PtyRecordingService {
+    /// Recordings under the app data dir, newest first.
+    pub fn list_recordings() -> Result<Vec<PtyRecording>, String> {
+        let recordings_dir = StorageService::pty_recordings_dir();
+        if !recordings_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let entries = fs::read_dir(&recordings_dir)
+            .map_err(|e| format!("Failed to read recordings directory: {}", e))?;
+
+        let mut recordings: Vec<PtyRecording> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some(CAST_EXTENSION))
+            .filter_map(|path| match Self::read_recording(&path) {
+                Ok(recording) => Some(recording),
+                Err(error) => {
+                    log::debug!("Skipping recording {}: {}", path.display(), error);
+                    None
+                }
+            })
+            .collect();
+
+        recordings.sort_by(|a, b| b.started_at.cmp(&a.started_at).then(b.id.cmp(&a.id)));
+        Ok(recordings)
+    }
+
+    /// Copies a recording to `output_path`. Returns the path of the written file.
+    pub fn export_recording(recording_id: &str, output_path: &str) -> Result<String, String> {
+        let normalized_output_path = output_path.trim();
+        if normalized_output_path.is_empty() {
+            return Err("Export path cannot be empty".to_string());
+        }
+
+        let output_path = PathBuf::from(normalized_output_path);
+        if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            if !parent.is_dir() {
+                return Err(format!(
+                    "Export directory does not exist: {}",
+                    parent.display()
+                ));
+            }
+        }
+
+        let source_path = Self::recording_path(recording_id)?;
+        fs::copy(&source_path, &output_path)
+            .map_err(|e| format!("Failed to export recording: {}", e))?;
+
+        Ok(output_path.to_string_lossy().to_string())
+    }
+
+    /// Plays a recording back as output and replay resize events for `session_id` on a
+    /// background thread, keeping the recorded timing scaled by `speed`. Pauses longer than
+    /// `idle_time_limit` seconds (or the limit stored in the file) are shortened to it.
+    /// The end is reported with `replay_finished`, never as a PTY exit.
+    pub fn replay_recording(
+        recording_id: &str,
+        session_id: &str,
+        speed: Option<f64>,
+        idle_time_limit: Option<f64>,
+        events: Arc<dyn PtyEventSink>,
+    ) -> Result<(), String> {
+        let normalized_session_id = session_id.trim().to_string();
+        if normalized_session_id.is_empty() {
+            return Err("PTY session id cannot be empty".to_string());
+        }
+
+        let path = Self::recording_path(recording_id)?;
+        let (header, frames) = Self::read_frames(&path)?;
+
+        let speed = speed
+            .filter(|value| value.is_finite() && *value > 0.0)
+            .unwrap_or(1.0)
+            .clamp(MIN_REPLAY_SPEED, MAX_REPLAY_SPEED);
+        let idle_time_limit = idle_time_limit
+            .or(header.idle_time_limit)
+            .filter(|value| value.is_finite() && *value > 0.0);
+
+        log::info!(
+            "PTY replay started recording={} sid={} frames={} speed={}",
+            recording_id.trim(),
+            normalized_session_id,
+            frames.len(),
+            speed
+        );
+
+        thread::Builder::new()
+            .name("pty-replay".to_string())
+            .spawn(move || {
+                let started = Instant::now();
+                let mut previous_time = 0.0;
+                let mut offset: u64 = 0;
+
+                for frame in frames {
+                    let mut delay = (frame.time - previous_time).max(0.0);
+                    if let Some(limit) = idle_time_limit {
+                        delay = delay.min(limit);
+                    }
+                    previous_time = frame.time;
+                    thread::sleep(Duration::from_secs_f64(delay / speed));
+
+                    match frame.event {
+                        CastEvent::Output(data) => {
+                            offset += data.len() as u64;
+                            if !events.output(&normalized_session_id, &data, offset) {
+                                log::debug!("PTY replay stopped for {}", normalized_session_id);
+                                return;
+                            }
+                        }
+                        CastEvent::Resize { cols, rows } => {
+                            events.replay_resize(&normalized_session_id, cols, rows);
+                        }
+                    }
+                }
+
+                events
+                    .replay_finished(&normalized_session_id, started.elapsed().as_millis() as u64);
+            })
+            .map_err(|e| format!("Failed to start replay thread: {}", e))?;
+
+        Ok(())
+    }
+
+    fn recording_path(recording_id: &str) -> Result<PathBuf, String> {
+        let normalized_id = recording_id.trim();
+        if normalized_id.is_empty() || sanitize_file_component(normalized_id) != normalized_id {
+            return Err(format!("Invalid recording id: {}", recording_id));
+        }
+
+        let path = StorageService::pty_recordings_dir()
+            .join(format!("{}.{}", normalized_id, CAST_EXTENSION));
+        if !path.is_file() {
+            return Err(format!("Recording not found: {}", normalized_id));
+        }
+
+        Ok(path)
+    }
+
+    fn read_header(reader: &mut impl BufRead) -> Result<CastHeader, String> {
+        let mut header_line = String::new();
+        reader
+            .read_line(&mut header_line)
+            .map_err(|e| format!("Failed to read recording: {}", e))?;
+
+        let header: CastHeader = serde_json::from_str(&header_line)
+            .map_err(|e| format!("Invalid recording header: {}", e))?;
+        if header.version != 2 {
+            return Err(format!("Unsupported asciicast version {}", header.version));
+        }
+
+        Ok(header)
+    }
+
+    /// Parses an event line. A line cut short by a crash mid-write is skipped.
+    fn parse_event(line: &str) -> Option<(f64, String, String)> {
+        serde_json::from_str(line).ok()
+    }
+
+    fn read_recording(path: &Path) -> Result<PtyRecording, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open recording: {}", e))?;
+        let size_bytes = file.metadata().map(|m| m.len()).unwrap_or_default();
+        let mut reader = BufReader::new(file);
+        let header = Self::read_header(&mut reader)?;
+
+        let duration_secs = reader
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| Self::parse_event(&line))
+            .map(|(time, _, _)| time)
+            .fold(0.0, f64::max);
+
+        Ok(PtyRecording {
+            id: path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default()
+                .to_string(),
+            session_id: header.title,
+            file_path: path.to_string_lossy().to_string(),
+            started_at: Utc
+                .timestamp_opt(header.timestamp, 0)
+                .single()
+                .map(|time| time.to_rfc3339())
+                .unwrap_or_default(),
+            duration_secs,
+            width: header.width,
+            height: header.height,
+            size_bytes,
+        })
+    }
+
+    fn read_frames(path: &Path) -> Result<(CastHeader, Vec<CastFrame>), String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open recording: {}", e))?;
+        let mut reader = BufReader::new(file);
+        let header = Self::read_header(&mut reader)?;
+
+        let frames = reader
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| Self::parse_event(&line))
+            .filter_map(|(time, code, data)| {
+                let event = match code.as_str() {
+                    "o" => CastEvent::Output(data),
+                    "r" => {
+                        let (cols, rows) = data.split_once('x')?;
+                        CastEvent::Resize {
+                            cols: cols.trim().parse().ok()?,
+                            rows: rows.trim().parse().ok()?,
+                        }
+                    }
+                    _ => return None,
+                };
+                Some(CastFrame { time, event })
+            })
+            .collect();
+
+        Ok((header, frames))
+    }
+}
+
+fn sanitize_file_component(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PtyRecorder, PtyRecordingService};
+    use crate::services::pty_service::{PtyEventSink, PtyExitInfo};
+    use crate::services::storage_service::{
+        storage_test_env_lock, unique_test_data_dir, DATA_DIR_OVERRIDE_ENV,
+    };
+    use serde_json::Value;
+    use std::fs;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+
+    #[test]
+    fn recorder_writes_asciicast_v2_and_lists_recordings() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_dir = unique_test_data_dir("pty-recording-write");
+        std::env::set_var(DATA_DIR_OVERRIDE_ENV, temp_dir.join("app-data"));
+
+        let mut recorder = PtyRecorder::create("sess/1", 80, 24, "/bin/zsh", "xterm").unwrap();
+        recorder.output("hello ");
+        recorder.resize(100, 30);
+        recorder.output("wörld\r\n");
+        drop(recorder);
+
+        let recordings = PtyRecordingService::list_recordings().unwrap();
+        assert_eq!(recordings.len(), 1);
+        let recording = &recordings[0];
+        assert!(recording.id.starts_with("sess_1-"));
+        assert_eq!(recording.session_id, "sess/1");
+        assert_eq!((recording.width, recording.height), (80, 24));
+
+        let content = fs::read_to_string(&recording.file_path).unwrap();
+        let lines: Vec<Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["env"]["TERM"], "xterm");
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "hello ");
+        assert_eq!(lines[2][1], "r");
+        assert_eq!(lines[2][2], "100x30");
+        assert_eq!(lines[3][2], "wörld\r\n");
+
+        let export_path = temp_dir.join("bug-report.cast");
+        PtyRecordingService::export_recording(&recording.id, export_path.to_str().unwrap())
+            .unwrap();
+        assert_eq!(fs::read_to_string(&export_path).unwrap(), content);
+        assert!(
+            PtyRecordingService::export_recording("../preferences", "/tmp/x.cast")
+                .unwrap_err()
+                .contains("Invalid recording id")
+        );
+
+        std::env::remove_var(DATA_DIR_OVERRIDE_ENV);
+        let _ = fs::remove_dir_all(temp_dir);
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum ReplayEvent {
+        Output(String, u64),
+        Resize(u16, u16),
+        Exit,
+        Finished,
+    }
+
+    struct ChannelEvents(Mutex<mpsc::Sender<ReplayEvent>>);
+
+    impl ChannelEvents {
+        fn send(&self, event: ReplayEvent) -> bool {
+            self.0.lock().unwrap().send(event).is_ok()
+        }
+    }
+
+    impl PtyEventSink for ChannelEvents {
+        fn output(&self, _session_id: &str, data: &str, offset: u64) -> bool {
+            self.send(ReplayEvent::Output(data.to_string(), offset))
+        }
+
+        fn exit(&self, _session_id: &str, _exit: &PtyExitInfo) {
+            self.send(ReplayEvent::Exit);
+        }
+
+        fn replay_resize(&self, _session_id: &str, cols: u16, rows: u16) {
+            self.send(ReplayEvent::Resize(cols, rows));
+        }
+
+        fn replay_finished(&self, _session_id: &str, _duration_ms: u64) {
+            self.send(ReplayEvent::Finished);
+        }
+    }
+
+    #[test]
+    fn replay_emits_frames_in_order_with_timing() {
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let temp_dir = unique_test_data_dir("pty-recording-replay");
+        let recordings_dir = temp_dir.join("app-data").join("pty-recordings");
+        fs::create_dir_all(&recordings_dir).unwrap();
+        std::env::set_var(DATA_DIR_OVERRIDE_ENV, temp_dir.join("app-data"));
+
+        fs::write(
+            recordings_dir.join("demo.cast"),
+            concat!(
+                r#"{"version":2,"width":80,"height":24,"timestamp":1700000000,"title":"demo"}"#,
+                "\n",
+                r#"[0.1,"o","ab"]"#,
+                "\n",
+                r#"[0.2,"r","90x30"]"#,
+                "\n",
+                r#"[60.3,"o","cde"]"#,
+                "\n",
+                r#"[60.4,"o","trunc"#,
+            ),
+        )
+        .unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let started = std::time::Instant::now();
+        PtyRecordingService::replay_recording(
+            "demo",
+            "replay-1",
+            Some(2.0),
+            Some(0.2),
+            Arc::new(ChannelEvents(Mutex::new(sender))),
+        )
+        .unwrap();
+
+        let mut replayed = Vec::new();
+        loop {
+            match receiver.recv_timeout(Duration::from_secs(5)).unwrap() {
+                ReplayEvent::Finished => break,
+                event => replayed.push(event),
+            }
+        }
+        let elapsed = started.elapsed();
+
+        // The end is not reported as an exit, which would close a terminal tab.
+        assert_eq!(
+            replayed,
+            vec![
+                ReplayEvent::Output("ab".to_string(), 2),
+                ReplayEvent::Resize(90, 30),
+                ReplayEvent::Output("cde".to_string(), 5),
+            ]
+        );
+        // 0.1s + the 60s gap capped at 0.2s, at double speed
+        assert!(elapsed >= Duration::from_millis(140), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(3), "{:?}", elapsed);
+
+        let recording = &PtyRecordingService::list_recordings().unwrap()[0];
+        assert_eq!(recording.duration_secs, 60.3);
+        assert_eq!(recording.started_at, "2023-11-14T22:13:20+00:00");
+
+        std::env::remove_var(DATA_DIR_OVERRIDE_ENV);
+        let _ = fs::remove_dir_all(temp_dir);
+    }
+}
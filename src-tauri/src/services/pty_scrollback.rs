@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+
+pub const DEFAULT_SCROLLBACK_BYTES: usize = 256 * 1024;
+
+/// Bounded ring buffer of the most recent PTY output.
+///
+/// `end_offset` counts every byte ever pushed, so a client that attaches with a
+/// snapshot can drop live chunks it has already seen.
+pub struct PtyScrollback {
+    buffer: VecDeque<u8>,
+    capacity: usize,
+    end_offset: u64,
+}
+
+impl PtyScrollback {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffer: VecDeque::with_capacity(capacity.min(64 * 1024)),
+            capacity: capacity.max(1),
+            end_offset: 0,
+        }
+    }
+
+    /// Appends output and returns the stream offset just past it.
+    pub fn push(&mut self, data: &[u8]) -> u64 {
+        self.end_offset += data.len() as u64;
+
+        let kept = if data.len() > self.capacity {
+            &data[data.len() - self.capacity..]
+        } else {
+            data
+        };
+
+        let overflow = (self.buffer.len() + kept.len()).saturating_sub(self.capacity);
+        self.buffer.drain(..overflow);
+        self.buffer.extend(kept);
+
+        self.end_offset
+    }
+
+    pub fn end_offset(&self) -> u64 {
+        self.end_offset
+    }
+
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.buffer.iter().copied().collect()
+    }
+
+    /// Buffered output as text. Bytes of a character cut in half by the
+    /// ring boundary are skipped rather than rendered as garbage, and a character
+    /// still being received is left for the next chunk.
+    pub fn snapshot_text(&self) -> String {
+        let bytes = self.snapshot();
+        let start = bytes
+            .iter()
+            .take(4)
+            .take_while(|byte| (0x80..0xC0).contains(*byte))
+            .count();
+        let end = bytes.len() - incomplete_utf8_tail(&bytes);
+
+        String::from_utf8_lossy(&bytes[start.min(end)..end]).into_owned()
+    }
+
+    /// Stream offset covered by `snapshot_text`.
+    pub fn text_end_offset(&self) -> u64 {
+        let tail: Vec<u8> = self.buffer.iter().rev().take(3).rev().copied().collect();
+        self.end_offset - incomplete_utf8_tail(&tail) as u64
+    }
+}
+
+/// Length of a UTF-8 sequence at the end of `bytes` that still needs more bytes.
+fn incomplete_utf8_tail(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+        if (0x80..0xC0).contains(&byte) {
+            continue;
+        }
+
+        let needed = match byte {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        return if needed > back { back } else { 0 };
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PtyScrollback;
+
+    #[test]
+    fn push_keeps_only_the_most_recent_bytes() {
+        let mut scrollback = PtyScrollback::new(8);
+
+        assert_eq!(scrollback.push(b"hello "), 6);
+        assert_eq!(scrollback.push(b"world"), 11);
+        assert_eq!(scrollback.snapshot(), b"lo world");
+
+        assert_eq!(scrollback.push(b"0123456789abc"), 24);
+        assert_eq!(scrollback.snapshot(), b"56789abc");
+        assert_eq!(scrollback.end_offset(), 24);
+    }
+
+    #[test]
+    fn snapshot_text_skips_partial_leading_character() {
+        let mut scrollback = PtyScrollback::new(4);
+        scrollback.push("ab€cd".as_bytes());
+
+        // "€" is three bytes; the ring keeps only its last two.
+        assert_eq!(scrollback.snapshot().len(), 4);
+        assert_eq!(scrollback.snapshot_text(), "cd");
+    }
+
+    #[test]
+    fn snapshot_text_leaves_incomplete_trailing_character_for_the_next_chunk() {
+        let mut scrollback = PtyScrollback::new(16);
+        let euro = "€".as_bytes();
+        scrollback.push(b"ab");
+        scrollback.push(&euro[..2]);
+
+        assert_eq!(scrollback.snapshot_text(), "ab");
+        assert_eq!(scrollback.text_end_offset(), 2);
+
+        scrollback.push(&euro[2..]);
+        assert_eq!(scrollback.snapshot_text(), "ab€");
+        assert_eq!(scrollback.text_end_offset(), 5);
+
+        // Invalid bytes are not held back.
+        scrollback.push(&[0xFF]);
+        assert_eq!(scrollback.text_end_offset(), 6);
+    }
+}
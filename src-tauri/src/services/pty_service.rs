@@ -1,75 +1,339 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use crate::models::ShellType;
+use crate::services::claude_session_service::ClaudeSessionService;
+use crate::services::pty_coalescer::{coalesce, CoalescePolicy, Utf8Stream, OUTPUT_CHANNEL_CHUNKS};
+#[cfg(unix)]
+use crate::services::pty_daemon::PtyDaemonClient;
+use crate::services::pty_process_service::{PtyProcessReport, PtyProcessService, PtyProcessTarget};
+use crate::services::pty_recording_service::PtyRecorder;
+use crate::services::pty_scrollback::{PtyScrollback, DEFAULT_SCROLLBACK_BYTES};
+use crate::services::pty_shutdown::{
+    self, PtyCloseOptions, PtyCloseOutcome, PtyExitWatch, PtyProcessGroups,
+};
+use crate::utils::resolve_shell_path;
+
+/// Printed by the launch script right before it falls back to a fresh `claude`.
+/// An unregistered OSC sequence, so terminals drop it silently.
+const RESUME_FALLBACK_MARKER: &str = "\x1b]7777;ccsm-resume-fallback\x07";
+const RESUME_FALLBACK_PRINTF: &str = r"printf '\033]7777;ccsm-resume-fallback\007'";
+/// A resume that gives up later than this was a resumed session exiting with an error,
+/// not a failed resume.
+const RESUME_STARTUP_WINDOW: Duration = Duration::from_secs(10);
+/// How often a new Claude session's transcript is looked for.
+const TRANSCRIPT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PtyLaunchMode {
     Plain,
     ClaudeResume,
+    /// A fresh `claude` conversation
+    ClaudeNew,
+    /// `claude --continue`, falling back to a fresh conversation
+    ClaudeContinue,
+    /// `claude -p <prompt>`, which exits after answering
+    ClaudePrompt,
 }
 
-#[derive(Debug, Clone)]
+/// Values accepted by `claude --permission-mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaudePermissionMode {
+    Default,
+    AcceptEdits,
+    Plan,
+    BypassPermissions,
+}
+
+impl ClaudePermissionMode {
+    fn as_arg(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::AcceptEdits => "acceptEdits",
+            Self::Plan => "plan",
+            Self::BypassPermissions => "bypassPermissions",
+        }
+    }
+}
+
+/// Typed `claude` flags, quoted for the target shell in the launch script.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClaudeLaunchOptions {
+    pub model: Option<String>,
+    pub permission_mode: Option<ClaudePermissionMode>,
+    pub allowed_tools: Vec<String>,
+    pub disallowed_tools: Vec<String>,
+    /// Path to an extra MCP server config file
+    pub mcp_config: Option<String>,
+    pub append_system_prompt: Option<String>,
+}
+
+impl ClaudeLaunchOptions {
+    pub(crate) fn to_args(&self) -> Vec<String> {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        // Tool lists go in one comma-separated value: the flags are variadic and
+        // would otherwise swallow whatever argument follows them.
+        let tool_list = |tools: &[String]| {
+            let tools: Vec<&str> = tools
+                .iter()
+                .map(|tool| tool.trim())
+                .filter(|tool| !tool.is_empty())
+                .collect();
+            (!tools.is_empty()).then(|| tools.join(","))
+        };
+
+        let mut args = Vec::new();
+        if let Some(model) = non_empty(&self.model) {
+            args.extend(["--model".to_string(), model]);
+        }
+        if let Some(mode) = self.permission_mode {
+            args.extend(["--permission-mode".to_string(), mode.as_arg().to_string()]);
+        }
+        if let Some(tools) = tool_list(&self.allowed_tools) {
+            args.extend(["--allowedTools".to_string(), tools]);
+        }
+        if let Some(tools) = tool_list(&self.disallowed_tools) {
+            args.extend(["--disallowedTools".to_string(), tools]);
+        }
+        if let Some(path) = non_empty(&self.mcp_config) {
+            args.extend(["--mcp-config".to_string(), path]);
+        }
+        // Not trimmed: the text is passed on verbatim.
+        if let Some(prompt) = self
+            .append_system_prompt
+            .as_ref()
+            .filter(|prompt| !prompt.trim().is_empty())
+        {
+            args.extend(["--append-system-prompt".to_string(), prompt.clone()]);
+        }
+        args
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PtyLaunchConfig {
     pub mode: PtyLaunchMode,
     pub resume_session_id: Option<String>,
+    /// Free-form extra `claude` arguments, after `claude_options`
     pub claude_args: Vec<String>,
+    #[serde(default)]
+    pub claude_options: ClaudeLaunchOptions,
+    /// Prompt for `ClaudePrompt` mode
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// `--session-id` for `ClaudeNew` / `ClaudePrompt`; assigned by `PtyManager::create`
+    /// when unset
+    #[serde(default)]
+    pub claude_session_id: Option<String>,
+    /// Record output and resizes to an asciicast file
+    #[serde(default)]
+    pub record: bool,
+    /// Shell name or path; `$SHELL` when unset
+    #[serde(default)]
+    pub shell: Option<String>,
+    /// Extra shell arguments, passed before the `-c` launch script
+    #[serde(default)]
+    pub shell_args: Vec<String>,
+    /// Extra environment, already expanded; overrides inherited values
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
 }
 
 impl PtyLaunchConfig {
-    pub fn plain() -> Self {
+    fn new(mode: PtyLaunchMode) -> Self {
         Self {
-            mode: PtyLaunchMode::Plain,
+            mode,
             resume_session_id: None,
             claude_args: Vec::new(),
+            claude_options: ClaudeLaunchOptions::default(),
+            prompt: None,
+            claude_session_id: None,
+            record: false,
+            shell: None,
+            shell_args: Vec::new(),
+            env: BTreeMap::new(),
         }
     }
 
+    pub fn plain() -> Self {
+        Self::new(PtyLaunchMode::Plain)
+    }
+
     pub fn claude_resume(resume_session_id: String, claude_args: Vec<String>) -> Self {
         Self {
-            mode: PtyLaunchMode::ClaudeResume,
             resume_session_id: Some(resume_session_id),
             claude_args,
+            ..Self::new(PtyLaunchMode::ClaudeResume)
         }
     }
 
-    fn launch_script(&self, fallback_session_id: &str, shell_path: &str) -> Option<String> {
-        if self.mode == PtyLaunchMode::Plain {
-            return None;
+    pub fn claude_new(claude_args: Vec<String>) -> Self {
+        Self {
+            claude_args,
+            ..Self::new(PtyLaunchMode::ClaudeNew)
         }
+    }
 
-        let resume_id = self
-            .resume_session_id
+    pub fn claude_continue(claude_args: Vec<String>) -> Self {
+        Self {
+            claude_args,
+            ..Self::new(PtyLaunchMode::ClaudeContinue)
+        }
+    }
+
+    pub fn claude_prompt(prompt: String, claude_args: Vec<String>) -> Self {
+        Self {
+            prompt: Some(prompt),
+            claude_args,
+            ..Self::new(PtyLaunchMode::ClaudePrompt)
+        }
+    }
+
+    pub fn with_claude_options(mut self, claude_options: ClaudeLaunchOptions) -> Self {
+        self.claude_options = claude_options;
+        self
+    }
+
+    pub fn with_claude_session_id(mut self, claude_session_id: Option<String>) -> Self {
+        self.claude_session_id = claude_session_id.filter(|value| !value.trim().is_empty());
+        self
+    }
+
+    pub fn with_recording(mut self, record: bool) -> Self {
+        self.record = record;
+        self
+    }
+
+    pub fn with_shell(mut self, shell: Option<String>, shell_args: Vec<String>) -> Self {
+        self.shell = shell.filter(|value| !value.trim().is_empty());
+        self.shell_args = shell_args;
+        self
+    }
+
+    pub fn with_env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let has_prompt = self
+            .prompt
             .as_deref()
-            .filter(|value| !value.trim().is_empty())
-            .unwrap_or(fallback_session_id);
+            .is_some_and(|prompt| !prompt.trim().is_empty());
+        if self.mode == PtyLaunchMode::ClaudePrompt && !has_prompt {
+            return Err("Claude prompt cannot be empty".to_string());
+        }
+        if let Some(claude_session_id) = self.new_claude_session_id() {
+            Uuid::parse_str(claude_session_id)
+                .map_err(|_| format!("Invalid Claude session id: {}", claude_session_id))?;
+        }
+        Ok(())
+    }
+
+    fn starts_new_claude_session(&self) -> bool {
+        matches!(
+            self.mode,
+            PtyLaunchMode::ClaudeNew | PtyLaunchMode::ClaudePrompt
+        )
+    }
+
+    /// The `--session-id` passed to a new Claude session.
+    fn new_claude_session_id(&self) -> Option<&str> {
+        self.claude_session_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty() && self.starts_new_claude_session())
+    }
+
+    /// The Claude session `claude -r` resumes, for a resume launch.
+    fn resume_target<'a>(&'a self, fallback_session_id: &'a str) -> Option<&'a str> {
+        (self.mode == PtyLaunchMode::ClaudeResume).then(|| {
+            self.resume_session_id
+                .as_deref()
+                .filter(|value| !value.trim().is_empty())
+                .unwrap_or(fallback_session_id)
+        })
+    }
+
+    fn launch_script(&self, fallback_session_id: &str, shell: &ShellType) -> Option<String> {
+        if self.mode == PtyLaunchMode::Plain {
+            return None;
+        }
 
         let mut claude_base_parts = vec![String::from("claude")];
+        // The prompt comes first so no option can take it as its value.
+        if self.mode == PtyLaunchMode::ClaudePrompt {
+            claude_base_parts.push(String::from("-p"));
+            claude_base_parts.push(shell_quote(self.prompt.as_deref()?, shell));
+        }
+        if let Some(claude_session_id) = self.new_claude_session_id() {
+            claude_base_parts.push(String::from("--session-id"));
+            claude_base_parts.push(shell_quote(claude_session_id, shell));
+        }
 
+        for arg in self.claude_options.to_args() {
+            claude_base_parts.push(shell_quote(&arg, shell));
+        }
         for arg in self
             .claude_args
             .iter()
             .map(|value| value.trim())
             .filter(|value| !value.is_empty())
         {
-            claude_base_parts.push(shell_quote(arg));
+            claude_base_parts.push(shell_quote(arg, shell));
         }
 
         let claude_base_command = claude_base_parts.join(" ");
 
-        let mut claude_resume_parts = claude_base_parts;
-        claude_resume_parts.push(String::from("-r"));
-        claude_resume_parts.push(shell_quote(resume_id));
+        let mut claude_first_parts = claude_base_parts;
+        match self.mode {
+            PtyLaunchMode::Plain => return None,
+            PtyLaunchMode::ClaudeNew | PtyLaunchMode::ClaudePrompt => {
+                return Some(claude_base_command);
+            }
+            PtyLaunchMode::ClaudeContinue => {
+                claude_first_parts.push(String::from("--continue"));
+            }
+            PtyLaunchMode::ClaudeResume => {
+                let resume_id = self.resume_target(fallback_session_id)?;
+                claude_first_parts.push(String::from("-r"));
+                claude_first_parts.push(shell_quote(resume_id, shell));
+            }
+        }
 
-        let claude_resume_command = claude_resume_parts.join(" ");
-        let claude_command = if is_fish_shell(shell_path) {
-            // fish does not support `||`, use native `or` chaining.
-            format!("{}; or {}", claude_resume_command, claude_base_command)
-        } else {
-            format!("{} || {}", claude_resume_command, claude_base_command)
+        let claude_first_command = claude_first_parts.join(" ");
+        let claude_command = match shell {
+            // fish before 3.0 does not support `||`, use native `or` chaining.
+            ShellType::Fish => format!(
+                "{}; or begin; {}; {}; end",
+                claude_first_command, RESUME_FALLBACK_PRINTF, claude_base_command
+            ),
+            // csh has no `{ }` grouping.
+            ShellType::Tcsh => format!(
+                "{} || ( {} ; {} )",
+                claude_first_command, RESUME_FALLBACK_PRINTF, claude_base_command
+            ),
+            _ => format!(
+                "{} || {{ {}; {}; }}",
+                claude_first_command, RESUME_FALLBACK_PRINTF, claude_base_command
+            ),
         };
 
         Some(claude_command)
@@ -80,16 +344,302 @@ struct PtySession {
     token: String,
     master: Box<dyn MasterPty + Send>,
     writer: Box<dyn Write + Send>,
+    output: Arc<Mutex<PtyOutputState>>,
+    recording: Option<Arc<Mutex<PtyRecorder>>>,
+    size: PtySize,
+    /// Set when the PTY is torn down by `close` / `close_all`
+    closed: Arc<AtomicBool>,
+    pid: Option<u32>,
+    exit_watch: Arc<PtyExitWatch>,
+    claude_session: Option<PtyClaudeSession>,
+}
+
+impl PtySession {
+    fn foreground_process_group(&self) -> Option<i32> {
+        #[cfg(unix)]
+        {
+            self.master.process_group_leader()
+        }
+        #[cfg(not(unix))]
+        {
+            None
+        }
+    }
+
+    /// Signals the child with escalation, then drops the PTY.
+    fn terminate(self, session_id: &str, options: &PtyCloseOptions) -> PtyCloseOutcome {
+        self.closed.store(true, Ordering::SeqCst);
+
+        let foreground = || self.foreground_process_group();
+        let groups = PtyProcessGroups {
+            shell_pid: self.pid,
+            foreground: &foreground,
+        };
+
+        pty_shutdown::terminate(session_id, &groups, &self.exit_watch, options)
+    }
+}
+
+/// Receives raw PTY output; returning false ends the subscription.
+pub type PtyByteSink = Box<dyn FnMut(&[u8]) -> bool + Send>;
+
+/// Scrollback and raw-output subscribers share a lock, so a new subscriber gets the
+/// buffered bytes and then every later byte exactly once.
+struct PtyOutputState {
+    scrollback: PtyScrollback,
+    byte_sinks: Vec<(u64, PtyByteSink)>,
+    next_subscription_id: u64,
+}
+
+impl PtyOutputState {
+    fn new() -> Self {
+        Self {
+            scrollback: PtyScrollback::new(DEFAULT_SCROLLBACK_BYTES),
+            byte_sinks: Vec::new(),
+            next_subscription_id: 1,
+        }
+    }
+}
+
+/// A raw-output subscription. The sink has already been sent the buffered scrollback,
+/// which ends at `offset`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PtyByteSubscription {
+    pub session_id: String,
+    pub subscription_id: u64,
+    pub offset: u64,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// Buffered output and current size of a live PTY, used to restore a remounted terminal.
+/// Live `pty-output` chunks with `offset <= self.offset` are already contained in `data`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PtyAttachment {
+    pub session_id: String,
+    pub data: String,
+    pub offset: u64,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// Links a PTY to the Claude session it started, sent with `pty-claude-session`
+/// once the transcript exists.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PtyClaudeSession {
+    pub session_id: String,
+    pub claude_session_id: String,
+    /// Where Claude writes the session JSONL
+    pub transcript_path: Option<String>,
+    /// The transcript exists on disk
+    pub ready: bool,
+}
+
+/// Why and how a PTY process ended, sent with `pty-exit`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PtyExitInfo {
+    /// Human-readable status, e.g. "Exited with code 1"
+    pub status: String,
+    /// None when the process was killed by a signal or could not be waited on
+    pub exit_code: Option<u32>,
+    /// Signal name such as "SIGHUP" when the process was killed by one
+    pub signal: Option<String>,
+    pub success: bool,
+    /// The PTY was closed by `close_pty` / `close_all` rather than exiting on its own
+    pub killed_by_close: bool,
+    pub duration_ms: u64,
+    /// The `claude -r` resume failed and a new Claude session was started instead.
+    /// Also reported as it happens through `PtyEventSink::resume_fallback`.
+    pub resume_fallback: bool,
+}
+
+impl PtyExitInfo {
+    fn from_status(status: &portable_pty::ExitStatus) -> Self {
+        let text = status.to_string();
+        let signal = text.strip_prefix("Terminated by ").map(signal_name);
+        Self {
+            exit_code: signal.is_none().then(|| status.exit_code()),
+            signal,
+            success: status.success(),
+            status: text,
+            ..Self::default()
+        }
+    }
+}
+
+/// portable-pty reports signals by their `strsignal` description ("Hangup"); this maps
+/// the description back to the signal name ("SIGHUP"). Unknown text is kept as is.
+fn signal_name(description: &str) -> String {
+    #[cfg(unix)]
+    {
+        const SIGNALS: &[(libc::c_int, &str)] = &[
+            (libc::SIGHUP, "SIGHUP"),
+            (libc::SIGINT, "SIGINT"),
+            (libc::SIGQUIT, "SIGQUIT"),
+            (libc::SIGILL, "SIGILL"),
+            (libc::SIGTRAP, "SIGTRAP"),
+            (libc::SIGABRT, "SIGABRT"),
+            (libc::SIGBUS, "SIGBUS"),
+            (libc::SIGFPE, "SIGFPE"),
+            (libc::SIGKILL, "SIGKILL"),
+            (libc::SIGUSR1, "SIGUSR1"),
+            (libc::SIGSEGV, "SIGSEGV"),
+            (libc::SIGUSR2, "SIGUSR2"),
+            (libc::SIGPIPE, "SIGPIPE"),
+            (libc::SIGALRM, "SIGALRM"),
+            (libc::SIGTERM, "SIGTERM"),
+            (libc::SIGCHLD, "SIGCHLD"),
+            (libc::SIGCONT, "SIGCONT"),
+            (libc::SIGSTOP, "SIGSTOP"),
+            (libc::SIGTSTP, "SIGTSTP"),
+            (libc::SIGTTIN, "SIGTTIN"),
+            (libc::SIGTTOU, "SIGTTOU"),
+            (libc::SIGURG, "SIGURG"),
+            (libc::SIGXCPU, "SIGXCPU"),
+            (libc::SIGXFSZ, "SIGXFSZ"),
+            (libc::SIGVTALRM, "SIGVTALRM"),
+            (libc::SIGPROF, "SIGPROF"),
+            (libc::SIGWINCH, "SIGWINCH"),
+            (libc::SIGIO, "SIGIO"),
+            (libc::SIGSYS, "SIGSYS"),
+        ];
+
+        // The same lookup portable-pty used, so the text matches on every libc.
+        for &(number, name) in SIGNALS {
+            let text = unsafe { libc::strsignal(number) };
+            if !text.is_null()
+                && unsafe { std::ffi::CStr::from_ptr(text) }.to_string_lossy() == description
+            {
+                return name.to_string();
+            }
+        }
+    }
+
+    description.to_string()
+}
+
+/// Receives output and exit notifications from PTY reader threads.
+pub trait PtyEventSink: Send + Sync {
+    /// Returns false when output can no longer be delivered, which stops the reader.
+    fn output(&self, session_id: &str, data: &str, offset: u64) -> bool;
+    fn exit(&self, session_id: &str, exit: &PtyExitInfo);
+    /// The transcript of a Claude session started by a PTY appeared on disk.
+    fn claude_session(&self, _session: &PtyClaudeSession) {}
+    /// The `claude -r` resume failed and the PTY fell back to a new Claude session.
+    fn resume_fallback(&self, _session_id: &str) {}
+    /// A replayed recording resized its terminal.
+    fn replay_resize(&self, _session_id: &str, _cols: u16, _rows: u16) {}
+    /// A replay reached the end of its recording. Replays never report `exit`,
+    /// so listeners tracking live terminals do not tear down a tab with the same id.
+    fn replay_finished(&self, _session_id: &str, _duration_ms: u64) {}
+}
+
+/// Forwards PTY events to the webview as `pty-output` / `pty-exit` /
+/// `pty-resume-fallback`, and replays as `pty-output` / `pty-replay-resize` /
+/// `pty-replay-finished`.
+pub struct TauriPtyEvents {
+    app_handle: tauri::AppHandle,
+}
+
+impl TauriPtyEvents {
+    pub fn new(app_handle: tauri::AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+impl PtyEventSink for TauriPtyEvents {
+    fn output(&self, session_id: &str, data: &str, offset: u64) -> bool {
+        emit_pty_output(&self.app_handle, session_id, data, offset)
+    }
+
+    fn exit(&self, session_id: &str, exit: &PtyExitInfo) {
+        emit_pty_exit(&self.app_handle, session_id, exit);
+    }
+
+    fn claude_session(&self, session: &PtyClaudeSession) {
+        use tauri::Emitter;
+
+        if let Err(error) = self.app_handle.emit("pty-claude-session", session) {
+            log::debug!(
+                "Failed emitting pty-claude-session for {}: {}",
+                session.session_id,
+                error
+            );
+        }
+    }
+
+    fn resume_fallback(&self, session_id: &str) {
+        use tauri::Emitter;
+
+        let payload = PtyResumeFallback { session_id };
+        if let Err(error) = self.app_handle.emit("pty-resume-fallback", payload) {
+            log::debug!(
+                "Failed emitting pty-resume-fallback for {}: {}",
+                session_id,
+                error
+            );
+        }
+    }
+
+    fn replay_resize(&self, session_id: &str, cols: u16, rows: u16) {
+        use tauri::Emitter;
+
+        let payload = PtyReplayResize {
+            session_id,
+            cols,
+            rows,
+        };
+        if let Err(error) = self.app_handle.emit("pty-replay-resize", payload) {
+            log::debug!(
+                "Failed emitting pty-replay-resize for {}: {}",
+                session_id,
+                error
+            );
+        }
+    }
+
+    fn replay_finished(&self, session_id: &str, duration_ms: u64) {
+        use tauri::Emitter;
+
+        let payload = PtyReplayFinished {
+            session_id,
+            duration_ms,
+        };
+        if let Err(error) = self.app_handle.emit("pty-replay-finished", payload) {
+            log::debug!(
+                "Failed emitting pty-replay-finished for {}: {}",
+                session_id,
+                error
+            );
+        }
+    }
 }
 
 pub struct PtyManager {
     sessions: Arc<Mutex<HashMap<String, PtySession>>>,
+    events: Arc<dyn PtyEventSink>,
+    /// When set, PTYs live in the background daemon and every call is forwarded to it.
+    #[cfg(unix)]
+    daemon: Option<PtyDaemonClient>,
 }
 
 impl PtyManager {
-    pub fn new() -> Self {
+    pub fn new(events: Arc<dyn PtyEventSink>) -> Self {
+        Self {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            events,
+            #[cfg(unix)]
+            daemon: None,
+        }
+    }
+
+    /// Manager backed by the PTY daemon, so terminals keep running after the app quits.
+    #[cfg(unix)]
+    pub fn with_daemon(events: Arc<dyn PtyEventSink>, daemon: PtyDaemonClient) -> Self {
         Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
+            events,
+            daemon: Some(daemon),
         }
     }
 
@@ -97,9 +647,13 @@ impl PtyManager {
         &self,
         session_id: &str,
         working_dir: &str,
-        launch_config: PtyLaunchConfig,
-        app_handle: tauri::AppHandle,
+        mut launch_config: PtyLaunchConfig,
     ) -> Result<bool, String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.create(session_id, working_dir, launch_config);
+        }
+
         let normalized_session_id = session_id.trim();
         if normalized_session_id.is_empty() {
             return Err("PTY session id cannot be empty".to_string());
@@ -107,14 +661,9 @@ impl PtyManager {
 
         let normalized_working_dir = working_dir.trim();
         validate_working_dir(normalized_working_dir)?;
+        launch_config.validate()?;
 
-        if launch_config.mode == PtyLaunchMode::ClaudeResume {
-            let resume_id = launch_config
-                .resume_session_id
-                .as_deref()
-                .filter(|value| !value.trim().is_empty())
-                .unwrap_or(normalized_session_id);
-
+        if let Some(resume_id) = launch_config.resume_target(normalized_session_id) {
             if let Err(error) = validate_claude_resume_target(normalized_working_dir, resume_id) {
                 log::warn!(
                     "Claude resume target validation failed for sid={} cwd={}: {}. Falling back to non-resume launch path.",
@@ -150,12 +699,28 @@ impl PtyManager {
             .openpty(size)
             .map_err(|e| format!("Failed to open PTY: {}", e))?;
 
-        let shell = std::env::var("SHELL")
-            .ok()
-            .map(|value| value.trim().to_string())
-            .filter(|value| !value.is_empty())
-            .unwrap_or_else(|| "/bin/zsh".to_string());
-        let launch_script = launch_config.launch_script(normalized_session_id, &shell);
+        let shell = match launch_config.shell.as_deref().map(str::trim) {
+            Some(shell) if !shell.is_empty() => resolve_shell_path(shell)?,
+            _ => default_shell(),
+        };
+        // Unknown shells get POSIX syntax.
+        let shell_type = ShellType::from_path(&shell).unwrap_or(ShellType::Sh);
+        if launch_config.starts_new_claude_session() && launch_config.claude_session_id.is_none() {
+            launch_config.claude_session_id = Some(Uuid::new_v4().to_string());
+        }
+        let claude_session = launch_config
+            .new_claude_session_id()
+            .map(|claude_session_id| PtyClaudeSession {
+                session_id: normalized_session_id.to_string(),
+                claude_session_id: claude_session_id.to_string(),
+                transcript_path: claude_session_file_path(
+                    normalized_working_dir,
+                    claude_session_id,
+                )
+                .map(|path| path.display().to_string()),
+                ready: false,
+            });
+        let launch_script = launch_config.launch_script(normalized_session_id, &shell_type);
 
         if let Some(script) = launch_script.as_ref() {
             log::debug!(
@@ -166,14 +731,8 @@ impl PtyManager {
         }
 
         let mut cmd = CommandBuilder::new(&shell);
-        if let Some(script) = launch_script {
-            cmd.arg("-i");
-            cmd.arg("-l");
-            cmd.arg("-c");
-            cmd.arg(script);
-        } else {
-            cmd.arg("-i");
-            cmd.arg("-l");
+        for arg in shell_launch_args(&shell_type, &launch_config.shell_args, launch_script) {
+            cmd.arg(arg);
         }
         cmd.cwd(normalized_working_dir);
 
@@ -193,11 +752,42 @@ impl PtyManager {
             cmd.env("COLORTERM", "truecolor");
         }
 
+        for (key, value) in &launch_config.env {
+            if key != "PATH" {
+                cmd.env(key, value);
+            }
+        }
+
         // Avoid nested Claude Code detection inside embedded terminals.
         // Claude CLI blocks startup when CLAUDECODE is "1".
         cmd.env("CLAUDECODE", "0");
 
-        let inherited_path = std::env::var("PATH").unwrap_or_default();
+        let recording = if launch_config.record {
+            match PtyRecorder::create(
+                normalized_session_id,
+                size.cols,
+                size.rows,
+                &shell,
+                &resolved_term,
+            ) {
+                Ok(recorder) => Some(Arc::new(Mutex::new(recorder))),
+                Err(error) => {
+                    log::warn!(
+                        "PTY recording disabled for sid={}: {}",
+                        normalized_session_id,
+                        error
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let inherited_path = match launch_config.env.get("PATH") {
+            Some(path) => path.clone(),
+            None => std::env::var("PATH").unwrap_or_default(),
+        };
         let runtime_path = build_runtime_path(&inherited_path);
         cmd.env("PATH", runtime_path.as_str());
 
@@ -205,6 +795,7 @@ impl PtyManager {
             .slave
             .spawn_command(cmd)
             .map_err(|e| format!("Failed to spawn shell: {}", e))?;
+        let pid = child.process_id();
 
         drop(pair.slave);
 
@@ -218,31 +809,93 @@ impl PtyManager {
             .try_clone_reader()
             .map_err(|e| format!("Failed to get PTY reader: {}", e))?;
 
+        let started = Instant::now();
         let token = Uuid::new_v4().to_string();
-        let sid = normalized_session_id.to_string();
         let sessions_ref = Arc::clone(&self.sessions);
         let reader_token = token.clone();
+        let output_state = Arc::new(Mutex::new(PtyOutputState::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        let reader_closed = Arc::clone(&closed);
+        let exit_watch = Arc::new(PtyExitWatch::default());
+        let reader_exit_watch = Arc::clone(&exit_watch);
+        let output = PtyOutputPipeline {
+            session_id: normalized_session_id.to_string(),
+            events: Arc::clone(&self.events),
+            output: Arc::clone(&output_state),
+            text_offset: 0,
+            recording: recording.clone(),
+            resume_fallback: ResumeFallbackWatch::new(
+                launch_config
+                    .resume_target(normalized_session_id)
+                    .and_then(|resume_id| {
+                        claude_session_file_path(normalized_working_dir, resume_id)
+                    }),
+            ),
+        };
 
         std::thread::spawn(move || {
-            reader_thread(sid, reader_token, reader, child, app_handle, sessions_ref);
+            reader_thread(
+                reader_token,
+                reader,
+                child,
+                sessions_ref,
+                output,
+                PtyExitContext {
+                    closed: reader_closed,
+                    watch: reader_exit_watch,
+                    started,
+                },
+            );
         });
 
         let session = PtySession {
             token,
             master: pair.master,
             writer,
+            output: output_state,
+            recording,
+            size,
+            closed,
+            pid,
+            exit_watch: Arc::clone(&exit_watch),
+            claude_session: claude_session.clone(),
         };
 
         sessions.insert(normalized_session_id.to_string(), session);
+        if let Some(claude_session) = claude_session {
+            log::info!(
+                "PTY sid={} starts Claude session {}",
+                normalized_session_id,
+                claude_session.claude_session_id
+            );
+            let events = Arc::clone(&self.events);
+            std::thread::spawn(move || watch_claude_transcript(claude_session, events, exit_watch));
+        }
         log::info!(
-            "PTY created sid={} cwd={}",
+            "PTY created sid={} cwd={} shell={}",
             normalized_session_id,
-            normalized_working_dir
+            normalized_working_dir,
+            shell
         );
         Ok(true)
     }
 
     pub fn write(&self, session_id: &str, data: &str) -> Result<(), String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.write(session_id, data);
+        }
+
+        self.write_bytes(session_id, data.as_bytes())
+    }
+
+    /// Writes input as-is, for pastes and key sequences that are not valid UTF-8.
+    pub fn write_bytes(&self, session_id: &str, data: &[u8]) -> Result<(), String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.write_bytes(session_id, data);
+        }
+
         let normalized_session_id = session_id.trim();
         if normalized_session_id.is_empty() {
             return Err("PTY session id cannot be empty".to_string());
@@ -255,64 +908,293 @@ impl PtyManager {
 
         session
             .writer
-            .write_all(data.as_bytes())
+            .write_all(data)
             .map_err(|e| format!("Failed to write to PTY: {}", e))?;
 
         Ok(())
     }
 
     pub fn resize(&self, session_id: &str, cols: u16, rows: u16) -> Result<(), String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.resize(session_id, cols, rows);
+        }
+
         let normalized_session_id = session_id.trim();
         if normalized_session_id.is_empty() {
             return Err("PTY session id cannot be empty".to_string());
         }
 
-        let sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+        let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
         let session = sessions
-            .get(normalized_session_id)
+            .get_mut(normalized_session_id)
             .ok_or_else(|| format!("PTY session not found: {}", normalized_session_id))?;
 
+        let size = PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        };
         session
             .master
-            .resize(PtySize {
-                rows,
-                cols,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
+            .resize(size)
             .map_err(|e| format!("Failed to resize PTY: {}", e))?;
+        session.size = size;
+
+        if let Some(recording) = &session.recording {
+            if let Ok(mut recorder) = recording.lock() {
+                recorder.resize(cols, rows);
+            }
+        }
 
         Ok(())
     }
 
-    pub fn close(&self, session_id: &str) -> Result<(), String> {
+    /// Snapshot of the scrollback for a terminal that (re)mounts on a live PTY.
+    pub fn attach(&self, session_id: &str) -> Result<PtyAttachment, String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.attach(session_id);
+        }
+
         let normalized_session_id = session_id.trim();
         if normalized_session_id.is_empty() {
             return Err("PTY session id cannot be empty".to_string());
         }
 
-        let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
-        sessions
+        let sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+        let session = sessions
+            .get(normalized_session_id)
+            .ok_or_else(|| format!("PTY session not found: {}", normalized_session_id))?;
+
+        let output = session.output.lock().map_err(|e| e.to_string())?;
+        Ok(PtyAttachment {
+            session_id: normalized_session_id.to_string(),
+            data: output.scrollback.snapshot_text(),
+            offset: output.scrollback.text_end_offset(),
+            cols: session.size.cols,
+            rows: session.size.rows,
+        })
+    }
+
+    /// Streams raw output to `sink`, starting with the buffered scrollback. Unlike
+    /// `pty-output` events, nothing is lost to UTF-8 decoding.
+    pub fn subscribe_bytes(
+        &self,
+        session_id: &str,
+        mut sink: PtyByteSink,
+    ) -> Result<PtyByteSubscription, String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.subscribe_bytes(session_id, sink);
+        }
+
+        let normalized_session_id = session_id.trim();
+        if normalized_session_id.is_empty() {
+            return Err("PTY session id cannot be empty".to_string());
+        }
+
+        let sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+        let session = sessions
+            .get(normalized_session_id)
+            .ok_or_else(|| format!("PTY session not found: {}", normalized_session_id))?;
+
+        let mut output = session.output.lock().map_err(|e| e.to_string())?;
+        let snapshot = output.scrollback.snapshot();
+        if !snapshot.is_empty() && !sink(&snapshot) {
+            return Err("PTY byte subscriber closed before the snapshot was sent".to_string());
+        }
+
+        let subscription_id = output.next_subscription_id;
+        output.next_subscription_id += 1;
+        output.byte_sinks.push((subscription_id, sink));
+
+        Ok(PtyByteSubscription {
+            session_id: normalized_session_id.to_string(),
+            subscription_id,
+            offset: output.scrollback.end_offset(),
+            cols: session.size.cols,
+            rows: session.size.rows,
+        })
+    }
+
+    /// Returns false when the subscription had already ended.
+    pub fn unsubscribe_bytes(
+        &self,
+        session_id: &str,
+        subscription_id: u64,
+    ) -> Result<bool, String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.unsubscribe_bytes(session_id, subscription_id);
+        }
+
+        let sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+        let Some(session) = sessions.get(session_id.trim()) else {
+            return Ok(false);
+        };
+
+        let mut output = session.output.lock().map_err(|e| e.to_string())?;
+        let before = output.byte_sinks.len();
+        output.byte_sinks.retain(|(id, _)| *id != subscription_id);
+        Ok(output.byte_sinks.len() < before)
+    }
+
+    /// Signals the child so it can flush its state, escalating to SIGTERM and SIGKILL
+    /// after the grace period. Blocks until the child is reaped or the escalation ends.
+    pub fn close(
+        &self,
+        session_id: &str,
+        options: &PtyCloseOptions,
+    ) -> Result<PtyCloseOutcome, String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.close(session_id, options);
+        }
+
+        let normalized_session_id = session_id.trim();
+        if normalized_session_id.is_empty() {
+            return Err("PTY session id cannot be empty".to_string());
+        }
+
+        // Removed up front so the id can be reused while the old child winds down.
+        let session = self
+            .sessions
+            .lock()
+            .map_err(|e| e.to_string())?
             .remove(normalized_session_id)
             .ok_or_else(|| format!("PTY session not found: {}", normalized_session_id))?;
         log::info!("PTY close requested sid={}", normalized_session_id);
-        Ok(())
+        Ok(session.terminate(normalized_session_id, options))
     }
 
-    pub fn close_all(&self) -> Result<(), String> {
-        let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
-        let count = sessions.len();
-        sessions.clear();
-        if count > 0 {
-            log::info!("PTY close_all requested count={}", count);
+    /// Closes every PTY in parallel and waits for all of them.
+    pub fn close_all(&self, options: &PtyCloseOptions) -> Result<Vec<PtyCloseOutcome>, String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.close_all(options);
         }
-        Ok(())
+
+        let sessions: Vec<(String, PtySession)> = self
+            .sessions
+            .lock()
+            .map_err(|e| e.to_string())?
+            .drain()
+            .collect();
+        if sessions.is_empty() {
+            return Ok(Vec::new());
+        }
+        log::info!("PTY close_all requested count={}", sessions.len());
+
+        let mut outcomes: Vec<PtyCloseOutcome> = std::thread::scope(|scope| {
+            let handles: Vec<_> = sessions
+                .into_iter()
+                .map(|(session_id, session)| {
+                    scope.spawn(move || session.terminate(&session_id, options))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .filter_map(|handle| handle.join().ok())
+                .collect()
+        });
+        outcomes.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        Ok(outcomes)
+    }
+
+    /// Closes in-process PTYs before the app exits. Daemon-owned PTYs keep running.
+    pub fn shutdown(&self, options: &PtyCloseOptions) -> Vec<PtyCloseOutcome> {
+        #[cfg(unix)]
+        if self.daemon.is_some() {
+            return Vec::new();
+        }
+
+        self.close_all(options).unwrap_or_else(|error| {
+            log::warn!("Failed closing PTYs on shutdown: {}", error);
+            Vec::new()
+        })
+    }
+
+    /// Shell pid, foreground job and resource use of each live PTY (all when
+    /// `session_ids` is empty). Blocks for the CPU sample window on Linux.
+    pub fn processes(&self, session_ids: &[String]) -> Result<Vec<PtyProcessReport>, String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.processes(session_ids);
+        }
+
+        let targets: Vec<PtyProcessTarget> = {
+            let sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+            let mut targets: Vec<PtyProcessTarget> = sessions
+                .iter()
+                .filter(|(session_id, _)| {
+                    session_ids.is_empty() || session_ids.iter().any(|id| id.trim() == *session_id)
+                })
+                .map(|(session_id, session)| PtyProcessTarget {
+                    session_id: session_id.clone(),
+                    shell_pid: session.pid,
+                    foreground_process_group: session.foreground_process_group(),
+                })
+                .collect();
+            targets.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+            targets
+        };
+
+        Ok(PtyProcessService::inspect(&targets))
+    }
+
+    /// Claude sessions started by live PTYs with a pre-assigned `--session-id`.
+    pub fn claude_sessions(&self) -> Result<Vec<PtyClaudeSession>, String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.claude_sessions();
+        }
+
+        let sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+        let mut claude_sessions: Vec<PtyClaudeSession> = sessions
+            .values()
+            .filter_map(|session| session.claude_session.clone())
+            .map(|mut claude_session| {
+                claude_session.ready = claude_session
+                    .transcript_path
+                    .as_deref()
+                    .is_some_and(|path| Path::new(path).is_file());
+                claude_session
+            })
+            .collect();
+        claude_sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        Ok(claude_sessions)
+    }
+
+    /// Ids of the live PTY sessions.
+    pub fn list(&self) -> Result<Vec<String>, String> {
+        #[cfg(unix)]
+        if let Some(daemon) = &self.daemon {
+            return daemon.list();
+        }
+
+        let sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+        let mut session_ids: Vec<String> = sessions.keys().cloned().collect();
+        session_ids.sort();
+        Ok(session_ids)
     }
 }
 
 impl Drop for PtyManager {
     fn drop(&mut self) {
-        let _ = self.close_all();
+        // Daemon-owned PTYs outlive the app; that is the point of daemon mode.
+        #[cfg(unix)]
+        if self.daemon.is_some() {
+            return;
+        }
+
+        // Anything left after `shutdown` is dropped, which hangs up its terminal.
+        if let Ok(mut sessions) = self.sessions.lock() {
+            for (_, session) in sessions.drain() {
+                session.closed.store(true, Ordering::SeqCst);
+            }
+        }
     }
 }
 
@@ -320,20 +1202,168 @@ impl Drop for PtyManager {
 struct PtyOutput {
     session_id: String,
     data: String,
+    /// Stream offset just past this chunk
+    offset: u64,
+}
+
+#[derive(serde::Serialize, Clone)]
+struct PtyExit<'a> {
+    session_id: &'a str,
+    #[serde(flatten)]
+    exit: &'a PtyExitInfo,
 }
 
 #[derive(serde::Serialize, Clone)]
-struct PtyExit {
+struct PtyResumeFallback<'a> {
+    session_id: &'a str,
+}
+
+#[derive(serde::Serialize, Clone)]
+struct PtyReplayResize<'a> {
+    session_id: &'a str,
+    cols: u16,
+    rows: u16,
+}
+
+#[derive(serde::Serialize, Clone)]
+struct PtyReplayFinished<'a> {
+    session_id: &'a str,
+    duration_ms: u64,
+}
+
+/// Finds a marker in streamed text even when it is split across chunks.
+struct MarkerScanner {
+    marker: &'static str,
+    tail: String,
+    found: bool,
+}
+
+impl MarkerScanner {
+    fn new(marker: &'static str) -> Self {
+        Self {
+            marker,
+            tail: String::new(),
+            found: false,
+        }
+    }
+
+    /// Returns true for the chunk that completes the marker.
+    fn feed(&mut self, text: &str) -> bool {
+        if self.found {
+            return false;
+        }
+
+        let mut window = std::mem::take(&mut self.tail);
+        window.push_str(text);
+        if window.contains(self.marker) {
+            self.found = true;
+            return true;
+        }
+
+        // Keep just enough to complete a marker that starts at the end of this chunk.
+        let mut start = window.len().saturating_sub(self.marker.len() - 1);
+        while !window.is_char_boundary(start) {
+            start += 1;
+        }
+        self.tail = window.split_off(start);
+        false
+    }
+}
+
+/// Tells a `claude -r` that failed, and fell back to a new session, from a resumed
+/// session that later exited with an error; the launch script prints the fallback
+/// marker for both.
+struct ResumeFallbackWatch {
+    scanner: MarkerScanner,
+    started: Instant,
+    transcript: Option<PathBuf>,
+    transcript_len: Option<u64>,
+    failed: bool,
+}
+
+impl ResumeFallbackWatch {
+    fn new(transcript: Option<PathBuf>) -> Self {
+        let transcript_len = transcript.as_deref().and_then(file_len);
+        Self {
+            scanner: MarkerScanner::new(RESUME_FALLBACK_MARKER),
+            started: Instant::now(),
+            transcript,
+            transcript_len,
+            failed: false,
+        }
+    }
+
+    /// Returns true for the chunk that shows the resume failed: the marker printed
+    /// shortly after launch, with nothing added to the resumed transcript.
+    fn feed(&mut self, text: &str) -> bool {
+        if !self.scanner.feed(text) {
+            return false;
+        }
+
+        let transcript_grew = self.transcript.as_deref().and_then(file_len) != self.transcript_len;
+        self.failed = self.started.elapsed() < RESUME_STARTUP_WINDOW && !transcript_grew;
+        self.failed
+    }
+}
+
+fn file_len(path: &Path) -> Option<u64> {
+    std::fs::metadata(path).ok().map(|metadata| metadata.len())
+}
+
+/// Where a reader thread sends output.
+struct PtyOutputPipeline {
     session_id: String,
-    status: String,
+    events: Arc<dyn PtyEventSink>,
+    output: Arc<Mutex<PtyOutputState>>,
+    recording: Option<Arc<Mutex<PtyRecorder>>>,
+    resume_fallback: ResumeFallbackWatch,
+    /// Raw stream offset covered by the text emitted so far
+    text_offset: u64,
+}
+
+impl PtyOutputPipeline {
+    /// Buffers bytes as read from the PTY and hands them to raw subscribers.
+    fn publish_raw(&mut self, data: &[u8]) {
+        let mut output = match self.output.lock() {
+            Ok(output) => output,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        output.scrollback.push(data);
+        output.byte_sinks.retain_mut(|(_, sink)| sink(data));
+    }
+
+    /// Emits decoded text for `raw_len` bytes that already went through `publish_raw`,
+    /// so its offset never runs ahead of an `attach` snapshot.
+    fn publish_text(&mut self, data: &str, raw_len: usize) -> bool {
+        if self.resume_fallback.feed(data) {
+            log::info!(
+                "PTY resume fell back to a new Claude session sid={}",
+                self.session_id
+            );
+            self.events.resume_fallback(&self.session_id);
+        }
+        self.text_offset += raw_len as u64;
+
+        if let Some(Ok(mut recorder)) = self.recording.as_deref().map(Mutex::lock) {
+            recorder.output(data);
+        }
+
+        self.events.output(&self.session_id, data, self.text_offset)
+    }
 }
 
-fn emit_pty_output(app_handle: &tauri::AppHandle, session_id: &str, data: &str) -> bool {
+fn emit_pty_output(
+    app_handle: &tauri::AppHandle,
+    session_id: &str,
+    data: &str,
+    offset: u64,
+) -> bool {
     use tauri::Emitter;
 
     let payload = PtyOutput {
         session_id: session_id.to_string(),
         data: data.to_string(),
+        offset,
     };
 
     if let Err(error) = app_handle.emit("pty-output", payload) {
@@ -345,124 +1375,111 @@ fn emit_pty_output(app_handle: &tauri::AppHandle, session_id: &str, data: &str)
         return false;
     }
 
-    let channel = format!("pty-output:{}", session_id);
-    if let Err(error) = app_handle.emit(&channel, data.to_string()) {
-        log::debug!(
-            "Failed emitting session-specific PTY channel {}: {}",
-            channel,
-            error
-        );
-    }
-
     true
 }
 
-fn emit_pty_exit(app_handle: &tauri::AppHandle, session_id: &str, status: &str) {
+fn emit_pty_exit(app_handle: &tauri::AppHandle, session_id: &str, exit: &PtyExitInfo) {
     use tauri::Emitter;
 
-    let payload = PtyExit {
-        session_id: session_id.to_string(),
-        status: status.to_string(),
-    };
+    let payload = PtyExit { session_id, exit };
 
     if let Err(error) = app_handle.emit("pty-exit", payload) {
         log::debug!("Failed emitting pty-exit for {}: {}", session_id, error);
     }
 }
 
+/// Publishes reader chunks in coalesced batches and hands the pipeline back when done.
+fn output_emitter(
+    chunks: mpsc::Receiver<Vec<u8>>,
+    mut output: PtyOutputPipeline,
+) -> PtyOutputPipeline {
+    let mut decoder = Utf8Stream::default();
+    let mut logged_chunk_count: usize = 0;
+
+    coalesce(chunks, CoalescePolicy::default(), |data| {
+        output.publish_raw(data);
+        let (text, consumed) = decoder.decode(data);
+        if consumed == 0 {
+            return true;
+        }
+
+        if logged_chunk_count < 6 {
+            log::info!(
+                "PTY output chunk sid={} idx={} bytes={} preview={}",
+                output.session_id,
+                logged_chunk_count,
+                consumed,
+                log_preview(&text)
+            );
+        }
+        logged_chunk_count += 1;
+
+        output.publish_text(&text, consumed)
+    });
+
+    output
+}
+
+/// Reports `claude_session` once Claude has written the transcript. Claude only
+/// creates it with the first message, so this keeps looking until the PTY exits.
+fn watch_claude_transcript(
+    mut claude_session: PtyClaudeSession,
+    events: Arc<dyn PtyEventSink>,
+    exit_watch: Arc<PtyExitWatch>,
+) {
+    let Some(path) = claude_session.transcript_path.clone().map(PathBuf::from) else {
+        return;
+    };
+
+    loop {
+        let exited = exit_watch.wait(TRANSCRIPT_POLL_INTERVAL).is_some();
+        if path.is_file() {
+            log::info!(
+                "Claude transcript ready sid={} claude_session={}",
+                claude_session.session_id,
+                claude_session.claude_session_id
+            );
+            claude_session.ready = true;
+            events.claude_session(&claude_session);
+            return;
+        }
+        if exited {
+            return;
+        }
+    }
+}
+
+/// What the reader thread needs to report how the child ended.
+struct PtyExitContext {
+    closed: Arc<AtomicBool>,
+    watch: Arc<PtyExitWatch>,
+    started: Instant,
+}
+
 fn reader_thread(
-    session_id: String,
     token: String,
     mut reader: Box<dyn Read + Send>,
     mut child: Box<dyn portable_pty::Child + Send + Sync>,
-    app_handle: tauri::AppHandle,
     sessions: Arc<Mutex<HashMap<String, PtySession>>>,
+    output: PtyOutputPipeline,
+    exit_context: PtyExitContext,
 ) {
+    let session_id = output.session_id.clone();
+    let events = Arc::clone(&output.events);
+    let (chunks, chunk_receiver) = mpsc::sync_channel::<Vec<u8>>(OUTPUT_CHANNEL_CHUNKS);
+    let emitter = std::thread::spawn(move || output_emitter(chunk_receiver, output));
     let mut buf = [0u8; 4096];
-    let mut pending_utf8 = Vec::<u8>::new();
-    let mut logged_chunk_count: usize = 0;
 
-    'reader_loop: loop {
+    loop {
         match reader.read(&mut buf) {
             Ok(0) => {
                 log::info!("PTY stream reached EOF sid={}", session_id);
                 break;
             }
             Ok(n) => {
-                pending_utf8.extend_from_slice(&buf[..n]);
-
-                while !pending_utf8.is_empty() {
-                    match std::str::from_utf8(&pending_utf8) {
-                        Ok(valid_text) => {
-                            if logged_chunk_count < 6 {
-                                log::info!(
-                                    "PTY output chunk sid={} idx={} bytes={} preview={}",
-                                    session_id,
-                                    logged_chunk_count,
-                                    valid_text.len(),
-                                    log_preview(valid_text)
-                                );
-                            }
-                            logged_chunk_count += 1;
-
-                            if !emit_pty_output(&app_handle, &session_id, valid_text) {
-                                log::debug!("PTY output emit failed for session {}", session_id);
-                                break 'reader_loop;
-                            }
-
-                            pending_utf8.clear();
-                        }
-                        Err(utf8_error) => {
-                            let valid_up_to = utf8_error.valid_up_to();
-
-                            if valid_up_to > 0 {
-                                let valid_text =
-                                    std::str::from_utf8(&pending_utf8[..valid_up_to]).unwrap();
-
-                                if logged_chunk_count < 6 {
-                                    log::info!(
-                                        "PTY output chunk sid={} idx={} bytes={} preview={}",
-                                        session_id,
-                                        logged_chunk_count,
-                                        valid_up_to,
-                                        log_preview(valid_text)
-                                    );
-                                }
-                                logged_chunk_count += 1;
-
-                                if !emit_pty_output(&app_handle, &session_id, valid_text) {
-                                    log::debug!(
-                                        "PTY output emit failed for session {}",
-                                        session_id
-                                    );
-                                    break 'reader_loop;
-                                }
-
-                                pending_utf8.drain(0..valid_up_to);
-                                continue;
-                            }
-
-                            if utf8_error.error_len().is_none() {
-                                break;
-                            }
-
-                            pending_utf8.drain(0..1);
-                            if logged_chunk_count < 6 {
-                                log::info!(
-                                    "PTY output chunk sid={} idx={} bytes={} preview=�",
-                                    session_id,
-                                    logged_chunk_count,
-                                    1
-                                );
-                            }
-                            logged_chunk_count += 1;
-
-                            if !emit_pty_output(&app_handle, &session_id, "�") {
-                                log::debug!("PTY output emit failed for session {}", session_id);
-                                break 'reader_loop;
-                            }
-                        }
-                    }
+                if chunks.send(buf[..n].to_vec()).is_err() {
+                    log::debug!("PTY output emit failed for session {}", session_id);
+                    break;
                 }
             }
             Err(error) => {
@@ -472,24 +1489,39 @@ fn reader_thread(
         }
     }
 
-    let exit_status = match child.wait() {
+    // Flush everything still queued so the exit event follows the last output.
+    drop(chunks);
+    let resume_fallback = match emitter.join() {
+        Ok(output) => output.resume_fallback.failed,
+        Err(_) => {
+            log::warn!("PTY output emitter panicked for session {}", session_id);
+            false
+        }
+    };
+
+    let mut exit = match child.wait() {
         Ok(status) => {
-            let status_text = format!("{:?}", status);
-            log::info!("PTY child exited sid={} status={}", session_id, status_text);
-            status_text
+            log::info!("PTY child exited sid={} status={}", session_id, status);
+            PtyExitInfo::from_status(&status)
         }
         Err(error) => {
-            let status_text = format!("wait_error: {}", error);
             log::warn!(
                 "Failed waiting PTY child for session {}: {}",
                 session_id,
                 error
             );
-            status_text
+            PtyExitInfo {
+                status: format!("wait_error: {}", error),
+                ..PtyExitInfo::default()
+            }
         }
     };
+    exit.killed_by_close = exit_context.closed.load(Ordering::SeqCst);
+    exit.duration_ms = exit_context.started.elapsed().as_millis() as u64;
+    exit.resume_fallback = resume_fallback;
 
-    emit_pty_exit(&app_handle, &session_id, &exit_status);
+    events.exit(&session_id, &exit);
+    exit_context.watch.set(exit);
 
     if let Ok(mut map) = sessions.lock() {
         let should_remove = map
@@ -519,17 +1551,9 @@ fn log_preview(data: &str) -> String {
     trimmed.chars().take(160).collect::<String>()
 }
 
-fn claude_projects_dir() -> Option<PathBuf> {
-    dirs::home_dir().map(|home| home.join(".claude").join("projects"))
-}
-
-fn encode_project_path(project_path: &str) -> String {
-    project_path.replace('/', "-")
-}
-
 fn claude_session_file_path(project_path: &str, session_id: &str) -> Option<PathBuf> {
-    let projects_dir = claude_projects_dir()?;
-    let encoded_project = encode_project_path(project_path);
+    let projects_dir = ClaudeSessionService::claude_projects_dir()?;
+    let encoded_project = ClaudeSessionService::encode_project_path(project_path);
     Some(
         projects_dir
             .join(encoded_project)
@@ -552,7 +1576,30 @@ fn validate_claude_resume_target(working_dir: &str, session_id: &str) -> Result<
     ))
 }
 
-fn build_runtime_path(existing_path: &str) -> String {
+/// `$SHELL`, or zsh when it is unset.
+pub(crate) fn default_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| "/bin/zsh".to_string())
+}
+
+/// First `program` found in the `:`-separated `path`; names containing `/` are used as is.
+pub(crate) fn find_in_path(program: &str, path: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        return Some(PathBuf::from(program)).filter(|candidate| candidate.is_file());
+    }
+
+    path.split(':')
+        .filter(|dir| !dir.trim().is_empty())
+        .map(|dir| Path::new(dir).join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// `existing_path` followed by the usual install locations of `claude` and its runtimes,
+/// which a GUI app launched outside a login shell does not see.
+pub(crate) fn build_runtime_path(existing_path: &str) -> String {
     let mut ordered_paths: Vec<String> = Vec::new();
     let mut seen = HashSet::new();
 
@@ -634,18 +1681,497 @@ fn validate_working_dir(working_dir: &str) -> Result<(), String> {
     Ok(())
 }
 
-fn shell_quote(value: &str) -> String {
-    if value.is_empty() {
-        return "''".to_string();
+/// Interactive login flags, extra arguments, then `-c <script>` when launching a command.
+pub(crate) fn shell_launch_args(
+    shell: &ShellType,
+    extra_args: &[String],
+    launch_script: Option<String>,
+) -> Vec<String> {
+    let extra_args: Vec<String> = extra_args
+        .iter()
+        .map(|arg| arg.trim())
+        .filter(|arg| !arg.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let mut args = match shell {
+        // tcsh only honours -l when it is the sole argument.
+        ShellType::Tcsh if launch_script.is_none() && extra_args.is_empty() => {
+            vec!["-l".to_string()]
+        }
+        ShellType::Tcsh => vec!["-i".to_string()],
+        _ => vec!["-i".to_string(), "-l".to_string()],
+    };
+    args.extend(extra_args);
+
+    if let Some(script) = launch_script {
+        args.push("-c".to_string());
+        args.push(script);
     }
 
-    format!("'{}'", value.replace('\'', "'\\''"))
+    args
+}
+
+fn shell_quote(value: &str, shell: &ShellType) -> String {
+    match shell {
+        // fish treats `\\` and `\'` as escapes even inside single quotes.
+        ShellType::Fish => format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")),
+        // csh history expansion still applies inside single quotes, and a quoted
+        // newline must be escaped.
+        ShellType::Tcsh => format!(
+            "'{}'",
+            value
+                .replace('\'', "'\\''")
+                .replace('!', "\\!")
+                .replace('\n', "\\\n")
+        ),
+        _ => format!("'{}'", value.replace('\'', "'\\''")),
+    }
 }
 
-fn is_fish_shell(shell_path: &str) -> bool {
-    Path::new(shell_path)
-        .file_name()
-        .and_then(|name| name.to_str())
-        .map(|name| name.eq_ignore_ascii_case("fish"))
-        .unwrap_or(false)
+#[cfg(test)]
+mod tests {
+    use super::{
+        shell_launch_args, shell_quote, ClaudeLaunchOptions, ClaudePermissionMode, MarkerScanner,
+        PtyClaudeSession, PtyEventSink, PtyExitInfo, PtyLaunchConfig, PtyManager,
+        ResumeFallbackWatch, RESUME_FALLBACK_MARKER, RESUME_STARTUP_WINDOW,
+    };
+    use crate::models::ShellType;
+    use crate::services::pty_shutdown::{PtyCloseOptions, PtyCloseStage};
+    use crate::services::storage_service::{
+        storage_test_env_lock, unique_test_data_dir, EnvVarGuard,
+    };
+    use std::fs;
+    use std::process::Command;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+
+    fn claude_config() -> PtyLaunchConfig {
+        PtyLaunchConfig::claude_resume(
+            "abc-123".to_string(),
+            vec!["--model".to_string(), "it's".to_string(), "  ".to_string()],
+        )
+    }
+
+    #[test]
+    fn launch_script_uses_posix_chaining_for_bash_zsh_sh_and_ksh() {
+        for shell in [
+            ShellType::Bash,
+            ShellType::Zsh,
+            ShellType::Sh,
+            ShellType::Ksh,
+        ] {
+            assert_eq!(
+                claude_config().launch_script("fallback", &shell).unwrap(),
+                r#"claude '--model' 'it'\''s' -r 'abc-123' || { printf '\033]7777;ccsm-resume-fallback\007'; claude '--model' 'it'\''s'; }"#,
+                "{}",
+                shell
+            );
+        }
+    }
+
+    #[test]
+    fn launch_script_uses_or_chaining_and_fish_escapes_for_fish() {
+        let config = PtyLaunchConfig::claude_resume(
+            "abc-123".to_string(),
+            vec!["it's".to_string(), r"C:\tmp".to_string()],
+        );
+
+        assert_eq!(
+            config.launch_script("fallback", &ShellType::Fish).unwrap(),
+            r"claude 'it\'s' 'C:\\tmp' -r 'abc-123'; or begin; printf '\033]7777;ccsm-resume-fallback\007'; claude 'it\'s' 'C:\\tmp'; end"
+        );
+    }
+
+    #[test]
+    fn launch_script_escapes_history_and_newlines_for_tcsh() {
+        let config = PtyLaunchConfig::claude_resume(
+            "abc-123".to_string(),
+            vec![
+                "--append-system-prompt".to_string(),
+                "hi!\nit's".to_string(),
+            ],
+        );
+
+        assert_eq!(
+            config.launch_script("fallback", &ShellType::Tcsh).unwrap(),
+            "claude '--append-system-prompt' 'hi\\!\\\nit'\\''s' -r 'abc-123' || \
+             ( printf '\\033]7777;ccsm-resume-fallback\\007' ; \
+             claude '--append-system-prompt' 'hi\\!\\\nit'\\''s' )"
+        );
+    }
+
+    #[test]
+    fn launch_script_falls_back_to_session_id_and_skips_plain_mode() {
+        let config = PtyLaunchConfig::claude_resume("  ".to_string(), Vec::new());
+        assert_eq!(
+            config.launch_script("sid-1", &ShellType::Zsh).unwrap(),
+            r"claude -r 'sid-1' || { printf '\033]7777;ccsm-resume-fallback\007'; claude; }"
+        );
+        assert!(PtyLaunchConfig::plain()
+            .launch_script("sid-1", &ShellType::Zsh)
+            .is_none());
+    }
+
+    #[test]
+    fn launch_script_renders_new_continue_and_prompt_modes_with_typed_options() {
+        let options = ClaudeLaunchOptions {
+            model: Some(" opus ".to_string()),
+            permission_mode: Some(ClaudePermissionMode::AcceptEdits),
+            allowed_tools: vec!["Bash(git log:*)".to_string(), "Edit".to_string()],
+            disallowed_tools: vec![" ".to_string()],
+            mcp_config: Some("/tmp/my mcp.json".to_string()),
+            append_system_prompt: Some("Don't touch $HOME".to_string()),
+        };
+        let flags = r"'--model' 'opus' '--permission-mode' 'acceptEdits' '--allowedTools' 'Bash(git log:*),Edit' '--mcp-config' '/tmp/my mcp.json' '--append-system-prompt' 'Don'\''t touch $HOME'";
+
+        let new = PtyLaunchConfig::claude_new(vec!["--verbose".to_string()])
+            .with_claude_options(options.clone());
+        assert_eq!(
+            new.launch_script("sid", &ShellType::Bash).unwrap(),
+            format!("claude {} '--verbose'", flags)
+        );
+
+        let continued =
+            PtyLaunchConfig::claude_continue(Vec::new()).with_claude_options(options.clone());
+        assert_eq!(
+            continued.launch_script("sid", &ShellType::Bash).unwrap(),
+            format!(
+                r"claude {flags} --continue || {{ printf '\033]7777;ccsm-resume-fallback\007'; claude {flags}; }}",
+                flags = flags
+            )
+        );
+
+        let prompt = PtyLaunchConfig::claude_prompt("fix `ls`; rm -rf /".to_string(), Vec::new())
+            .with_claude_options(options);
+        assert_eq!(
+            prompt.launch_script("sid", &ShellType::Bash).unwrap(),
+            format!("claude -p 'fix `ls`; rm -rf /' {}", flags)
+        );
+        assert!(prompt.validate().is_ok());
+        assert_eq!(
+            PtyLaunchConfig::claude_new(Vec::new())
+                .with_claude_session_id(Some("0b7c2f4e-3c55-4a9e-9d1e-3f1b2a6c8d90".to_string()))
+                .launch_script("sid", &ShellType::Bash)
+                .unwrap(),
+            "claude --session-id '0b7c2f4e-3c55-4a9e-9d1e-3f1b2a6c8d90'"
+        );
+        assert!(PtyLaunchConfig::claude_new(Vec::new())
+            .with_claude_session_id(Some("not-a-uuid".to_string()))
+            .validate()
+            .is_err());
+        assert!(PtyLaunchConfig::claude_prompt("  ".to_string(), Vec::new())
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn shell_launch_args_respect_tcsh_login_rules() {
+        let extra = vec!["--norc".to_string(), " ".to_string()];
+
+        assert_eq!(
+            shell_launch_args(&ShellType::Bash, &extra, Some("claude".to_string())),
+            vec!["-i", "-l", "--norc", "-c", "claude"]
+        );
+        assert_eq!(shell_launch_args(&ShellType::Tcsh, &[], None), vec!["-l"]);
+        assert_eq!(
+            shell_launch_args(&ShellType::Tcsh, &[], Some("claude".to_string())),
+            vec!["-i", "-c", "claude"]
+        );
+        assert_eq!(
+            ShellType::from_path("/usr/local/bin/fish").map(|shell| shell.to_string()),
+            Some("fish".to_string())
+        );
+        assert!(ShellType::from_path("/opt/bin/nu").is_none());
+    }
+
+    #[test]
+    fn quoted_arguments_round_trip_through_installed_shells() {
+        let value = "it's a \"test\" $HOME `x` \\ done!";
+
+        for shell in [
+            ShellType::Bash,
+            ShellType::Zsh,
+            ShellType::Fish,
+            ShellType::Sh,
+            ShellType::Tcsh,
+            ShellType::Ksh,
+        ] {
+            let script = format!("printf '%s' {}", shell_quote(value, &shell));
+            let Ok(output) = Command::new(shell.as_str()).arg("-c").arg(&script).output() else {
+                // Shell not installed here
+                continue;
+            };
+
+            assert_eq!(String::from_utf8_lossy(&output.stdout), value, "{}", shell);
+        }
+    }
+
+    #[test]
+    fn marker_scanner_finds_marker_split_across_chunks() {
+        let mut scanner = MarkerScanner::new(RESUME_FALLBACK_MARKER);
+        let (head, tail) = RESUME_FALLBACK_MARKER.split_at(5);
+
+        scanner.feed("No conversation found ✗");
+        scanner.feed(&format!("é{}", head));
+        assert!(!scanner.found);
+        scanner.feed(&format!("{}Welcome", tail));
+        assert!(scanner.found);
+    }
+
+    #[test]
+    fn resume_fallback_counts_only_when_the_resume_itself_failed() {
+        let dir = unique_test_data_dir("pty-resume-watch");
+        fs::create_dir_all(&dir).unwrap();
+        let transcript = dir.join("resumed.jsonl");
+        fs::write(&transcript, "{}\n").unwrap();
+
+        let mut failed = ResumeFallbackWatch::new(Some(transcript.clone()));
+        assert!(failed.feed(&format!("No conversation found{}", RESUME_FALLBACK_MARKER)));
+        assert!(failed.failed);
+
+        // The resumed session ran and wrote to its transcript before exiting non-zero.
+        let mut resumed = ResumeFallbackWatch::new(Some(transcript.clone()));
+        fs::write(&transcript, "{}\n{}\n").unwrap();
+        assert!(!resumed.feed(RESUME_FALLBACK_MARKER));
+        assert!(!resumed.failed);
+
+        // Or it ran for a while without writing anything.
+        let mut quit_later = ResumeFallbackWatch::new(Some(transcript));
+        if let Some(started) = quit_later.started.checked_sub(RESUME_STARTUP_WINDOW * 2) {
+            quit_later.started = started;
+            assert!(!quit_later.feed(RESUME_FALLBACK_MARKER));
+        }
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resume_fallback_prints_marker_in_installed_shells() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let bin_dir = unique_test_data_dir("pty-fallback");
+        fs::create_dir_all(&bin_dir).unwrap();
+        let fake_claude = bin_dir.join("claude");
+        // Fails to resume, starts fine without `-r`.
+        fs::write(
+            &fake_claude,
+            "#!/bin/sh\nfor arg in \"$@\"; do [ \"$arg\" = -r ] && exit 1; done\necho started\n",
+        )
+        .unwrap();
+        fs::set_permissions(&fake_claude, fs::Permissions::from_mode(0o755)).unwrap();
+        let path = format!("{}:/usr/bin:/bin", bin_dir.display());
+
+        for shell in [
+            ShellType::Bash,
+            ShellType::Zsh,
+            ShellType::Fish,
+            ShellType::Sh,
+            ShellType::Tcsh,
+            ShellType::Ksh,
+        ] {
+            let script = PtyLaunchConfig::claude_resume("abc".to_string(), Vec::new())
+                .launch_script("abc", &shell)
+                .unwrap();
+            let Ok(output) = Command::new(shell.as_str())
+                .arg("-c")
+                .arg(&script)
+                .env("PATH", &path)
+                .output()
+            else {
+                // Shell not installed here
+                continue;
+            };
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert_eq!(
+                stdout,
+                format!("{}started\n", RESUME_FALLBACK_MARKER),
+                "{}",
+                shell
+            );
+        }
+
+        let _ = fs::remove_dir_all(bin_dir);
+    }
+
+    struct ClaudeSessionEvents(Mutex<mpsc::Sender<PtyClaudeSession>>);
+
+    impl PtyEventSink for ClaudeSessionEvents {
+        fn output(&self, _session_id: &str, _data: &str, _offset: u64) -> bool {
+            true
+        }
+
+        fn exit(&self, _session_id: &str, _exit: &PtyExitInfo) {}
+
+        fn claude_session(&self, session: &PtyClaudeSession) {
+            let _ = self.0.lock().unwrap().send(session.clone());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn new_claude_session_gets_an_id_and_reports_its_transcript() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _guard = storage_test_env_lock().lock().unwrap();
+        let root = unique_test_data_dir("pty-claude-id");
+        // Claude turns the dot and underscore into dashes too, not only the slashes.
+        let (home, bin_dir, project) = (
+            root.join("home"),
+            root.join("bin"),
+            root.join("my.project_v2"),
+        );
+        for dir in [&home, &bin_dir, &project] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        // Writes its transcript where Claude would, after a moment.
+        let fake_claude = bin_dir.join("claude");
+        fs::write(
+            &fake_claude,
+            "#!/bin/sh\nwhile [ $# -gt 0 ]; do [ \"$1\" = --session-id ] && id=$2; shift; done\n\
+             dir=\"$HOME/.claude/projects/$(pwd | sed 's/[^A-Za-z0-9]/-/g')\"\nsleep 1\nmkdir -p \"$dir\"\n\
+             echo '{}' > \"$dir/$id.jsonl\"\nsleep 30\n",
+        )
+        .unwrap();
+        fs::set_permissions(&fake_claude, fs::Permissions::from_mode(0o755)).unwrap();
+        // The login shell resets PATH from /etc/profile, then reads this.
+        fs::write(
+            home.join(".profile"),
+            format!("PATH=\"{}:$PATH\"\n", bin_dir.display()),
+        )
+        .unwrap();
+        let _home = EnvVarGuard::set("HOME", &home);
+
+        let (sender, receiver) = mpsc::channel();
+        let manager = PtyManager::new(Arc::new(ClaudeSessionEvents(Mutex::new(sender))));
+        let config =
+            PtyLaunchConfig::claude_new(Vec::new()).with_shell(Some("sh".to_string()), Vec::new());
+        manager
+            .create("tab-1", project.to_str().unwrap(), config)
+            .unwrap();
+
+        let pending = manager.claude_sessions().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert!(!pending[0].ready);
+        assert!(uuid::Uuid::parse_str(&pending[0].claude_session_id).is_ok());
+
+        let bound = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(bound.session_id, "tab-1");
+        assert_eq!(bound.claude_session_id, pending[0].claude_session_id);
+        assert!(bound.ready);
+        assert!(manager.claude_sessions().unwrap()[0].ready);
+
+        manager.close("tab-1", &PtyCloseOptions::default()).unwrap();
+        let _ = fs::remove_dir_all(root);
+    }
+
+    struct ExitEvents(Mutex<mpsc::Sender<(String, PtyExitInfo)>>);
+
+    impl PtyEventSink for ExitEvents {
+        fn output(&self, _session_id: &str, _data: &str, _offset: u64) -> bool {
+            true
+        }
+
+        fn exit(&self, session_id: &str, exit: &PtyExitInfo) {
+            let _ = self
+                .0
+                .lock()
+                .unwrap()
+                .send((session_id.to_string(), exit.clone()));
+        }
+    }
+
+    #[test]
+    fn exit_info_reports_exit_code_and_close() {
+        let (sender, receiver) = mpsc::channel();
+        let manager = PtyManager::new(Arc::new(ExitEvents(Mutex::new(sender))));
+        let working_dir = std::env::temp_dir();
+        let working_dir = working_dir.to_str().unwrap();
+        let sh = || PtyLaunchConfig::plain().with_shell(Some("sh".to_string()), Vec::new());
+
+        manager.create("exits", working_dir, sh()).unwrap();
+        manager.write("exits", "exit 3\n").unwrap();
+        let (session_id, exit) = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(session_id, "exits");
+        assert_eq!(exit.exit_code, Some(3));
+        assert_eq!(exit.signal, None);
+        assert!(!exit.success);
+        assert!(!exit.killed_by_close);
+        assert!(!exit.resume_fallback);
+
+        manager.create("closed", working_dir, sh()).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        let outcome = manager
+            .close("closed", &PtyCloseOptions::default())
+            .unwrap();
+        assert_eq!(outcome.stage, PtyCloseStage::Graceful);
+        let (session_id, exit) = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(session_id, "closed");
+        assert!(exit.killed_by_close);
+        assert_eq!(outcome.exit.as_ref(), Some(&exit));
+        assert!(exit.duration_ms >= 100, "{:?}", exit);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exit_info_names_the_signal() {
+        let status = Command::new("sh")
+            .args(["-c", "kill -HUP $$"])
+            .status()
+            .unwrap();
+        let exit = PtyExitInfo::from_status(&status.into());
+
+        assert_eq!(exit.signal.as_deref(), Some("SIGHUP"));
+        assert_eq!(exit.exit_code, None);
+        assert!(exit.status.starts_with("Terminated by "), "{}", exit.status);
+    }
+
+    struct FallbackEvents(Mutex<mpsc::Sender<&'static str>>);
+
+    impl PtyEventSink for FallbackEvents {
+        fn output(&self, _session_id: &str, _data: &str, _offset: u64) -> bool {
+            true
+        }
+
+        fn exit(&self, _session_id: &str, _exit: &PtyExitInfo) {
+            let _ = self.0.lock().unwrap().send("exit");
+        }
+
+        fn resume_fallback(&self, _session_id: &str) {
+            let _ = self.0.lock().unwrap().send("resume_fallback");
+        }
+    }
+
+    #[test]
+    fn resume_fallback_is_reported_while_the_pty_runs() {
+        let (sender, receiver) = mpsc::channel();
+        let manager = PtyManager::new(Arc::new(FallbackEvents(Mutex::new(sender))));
+        let working_dir = std::env::temp_dir();
+        let config = PtyLaunchConfig::plain().with_shell(Some("sh".to_string()), Vec::new());
+        manager
+            .create("fallback", working_dir.to_str().unwrap(), config)
+            .unwrap();
+
+        // Typed as escapes, so the echoed command line does not contain the marker itself.
+        manager
+            .write(
+                "fallback",
+                "printf '\\033]7777;ccsm-resume-fallback\\007'\n",
+            )
+            .unwrap();
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(10)).unwrap(),
+            "resume_fallback"
+        );
+
+        manager
+            .close("fallback", &PtyCloseOptions::default())
+            .unwrap();
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(10)).unwrap(),
+            "exit"
+        );
+    }
 }
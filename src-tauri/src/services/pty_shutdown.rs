@@ -0,0 +1,315 @@
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::app_settings::TerminalSettings;
+use crate::services::pty_service::PtyExitInfo;
+
+pub const DEFAULT_CLOSE_GRACE_PERIOD_MS: u64 = 3000;
+/// Keeps a daemon close request well inside the client's request timeout.
+pub const MAX_CLOSE_GRACE_PERIOD_MS: u64 = 10_000;
+const TERMINATE_GRACE_PERIOD: Duration = Duration::from_secs(1);
+const KILL_WAIT: Duration = Duration::from_secs(1);
+
+/// First signal sent to a PTY's process groups when it is closed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PtyCloseSignal {
+    #[default]
+    Hangup,
+    Interrupt,
+}
+
+impl PtyCloseSignal {
+    /// Accepts `hangup` / `interrupt` as well as `HUP`, `SIGINT` and similar spellings.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase();
+        match value.strip_prefix("sig").unwrap_or(&value) {
+            "hangup" | "hup" => Some(Self::Hangup),
+            "interrupt" | "int" => Some(Self::Interrupt),
+            _ => None,
+        }
+    }
+
+    /// The spelling stored in the terminal settings.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hangup => "hangup",
+            Self::Interrupt => "interrupt",
+        }
+    }
+
+    #[cfg(unix)]
+    fn number(self) -> libc::c_int {
+        match self {
+            Self::Hangup => libc::SIGHUP,
+            Self::Interrupt => libc::SIGINT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PtyCloseOptions {
+    pub signal: PtyCloseSignal,
+    /// How long to wait after `signal` before escalating to SIGTERM
+    pub grace_period_ms: u64,
+}
+
+impl Default for PtyCloseOptions {
+    fn default() -> Self {
+        Self {
+            signal: PtyCloseSignal::default(),
+            grace_period_ms: DEFAULT_CLOSE_GRACE_PERIOD_MS,
+        }
+    }
+}
+
+impl PtyCloseOptions {
+    pub fn from_settings(settings: &TerminalSettings) -> Self {
+        let signal = PtyCloseSignal::parse(&settings.close_signal).unwrap_or_else(|| {
+            log::warn!(
+                "Unsupported terminal close signal {:?}, using hangup",
+                settings.close_signal
+            );
+            PtyCloseSignal::default()
+        });
+        Self {
+            signal,
+            grace_period_ms: settings.close_grace_period_ms,
+        }
+    }
+
+    fn grace_period(&self) -> Duration {
+        Duration::from_millis(self.grace_period_ms.min(MAX_CLOSE_GRACE_PERIOD_MS))
+    }
+}
+
+/// How far the close had to escalate before the child was reaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PtyCloseStage {
+    /// The child had exited before the close was requested
+    AlreadyExited,
+    /// Exited within the grace period after the first signal
+    Graceful,
+    Terminated,
+    Killed,
+    /// Still not reaped after SIGKILL; the PTY was dropped anyway
+    Unresponsive,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PtyCloseOutcome {
+    pub session_id: String,
+    pub stage: PtyCloseStage,
+    pub elapsed_ms: u64,
+    pub exit: Option<PtyExitInfo>,
+}
+
+/// Filled in by the reader thread once the child has been reaped.
+#[derive(Default)]
+pub(crate) struct PtyExitWatch {
+    exit: Mutex<Option<PtyExitInfo>>,
+    reaped: Condvar,
+}
+
+impl PtyExitWatch {
+    pub(crate) fn set(&self, exit: PtyExitInfo) {
+        let mut slot = match self.exit.lock() {
+            Ok(slot) => slot,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *slot = Some(exit);
+        self.reaped.notify_all();
+    }
+
+    pub(crate) fn wait(&self, timeout: Duration) -> Option<PtyExitInfo> {
+        let slot = match self.exit.lock() {
+            Ok(slot) => slot,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let (slot, _) = match self
+            .reaped
+            .wait_timeout_while(slot, timeout, |exit| exit.is_none())
+        {
+            Ok(result) => result,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        slot.clone()
+    }
+}
+
+/// Process groups to signal for a PTY: the shell's own group and the terminal's
+/// foreground job, which is where `claude` runs under job control.
+pub(crate) struct PtyProcessGroups<'a> {
+    pub shell_pid: Option<u32>,
+    pub foreground: &'a dyn Fn() -> Option<i32>,
+}
+
+/// Signals the PTY's processes, escalating to SIGTERM and SIGKILL until the reader
+/// thread reports the child as reaped. The caller drops the PTY afterwards.
+pub(crate) fn terminate(
+    session_id: &str,
+    groups: &PtyProcessGroups,
+    watch: &PtyExitWatch,
+    options: &PtyCloseOptions,
+) -> PtyCloseOutcome {
+    let started = Instant::now();
+    let outcome = |stage: PtyCloseStage, exit: Option<PtyExitInfo>| PtyCloseOutcome {
+        session_id: session_id.to_string(),
+        stage,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        exit,
+    };
+
+    if let Some(exit) = watch.wait(Duration::ZERO) {
+        return outcome(PtyCloseStage::AlreadyExited, Some(exit));
+    }
+
+    #[cfg(unix)]
+    let steps = [
+        (
+            options.signal.number(),
+            options.grace_period(),
+            PtyCloseStage::Graceful,
+        ),
+        (
+            libc::SIGTERM,
+            TERMINATE_GRACE_PERIOD,
+            PtyCloseStage::Terminated,
+        ),
+        (libc::SIGKILL, KILL_WAIT, PtyCloseStage::Killed),
+    ];
+    #[cfg(unix)]
+    for (signal, wait, stage) in steps {
+        signal_groups(session_id, groups, signal);
+        if let Some(exit) = watch.wait(wait) {
+            log::info!(
+                "PTY closed sid={} stage={:?} elapsed_ms={}",
+                session_id,
+                stage,
+                started.elapsed().as_millis()
+            );
+            return outcome(stage, Some(exit));
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = (groups, options, TERMINATE_GRACE_PERIOD, KILL_WAIT);
+
+    log::warn!("PTY child did not exit after close sid={}", session_id);
+    outcome(PtyCloseStage::Unresponsive, None)
+}
+
+#[cfg(unix)]
+fn signal_groups(session_id: &str, groups: &PtyProcessGroups, signal: libc::c_int) {
+    let shell_group = groups.shell_pid.and_then(|pid| i32::try_from(pid).ok());
+    let foreground = (groups.foreground)().filter(|pgid| Some(*pgid) != shell_group);
+
+    for pgid in [foreground, shell_group].into_iter().flatten() {
+        if pgid <= 1 {
+            continue;
+        }
+        // SAFETY: plain syscall; a group that is already gone just returns ESRCH.
+        if unsafe { libc::killpg(pgid, signal) } != 0 {
+            log::debug!(
+                "killpg({}, {}) failed for sid={}: {}",
+                pgid,
+                signal,
+                session_id,
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::PtyProcessGroups;
+    use super::{terminate, PtyCloseOptions, PtyCloseSignal, PtyCloseStage, PtyExitWatch};
+    use crate::services::pty_service::PtyExitInfo;
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn spawn_group(script: &str) -> (u32, Arc<PtyExitWatch>) {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdout(Stdio::null())
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        let watch = Arc::new(PtyExitWatch::default());
+        let reaper = Arc::clone(&watch);
+        std::thread::spawn(move || {
+            let status = child.wait().unwrap();
+            reaper.set(PtyExitInfo {
+                status: format!("{:?}", status),
+                ..PtyExitInfo::default()
+            });
+        });
+        // Let the shell install its traps.
+        std::thread::sleep(Duration::from_millis(200));
+        (pid, watch)
+    }
+
+    #[test]
+    fn parse_accepts_common_signal_spellings() {
+        assert_eq!(
+            PtyCloseSignal::parse("SIGHUP"),
+            Some(PtyCloseSignal::Hangup)
+        );
+        assert_eq!(
+            PtyCloseSignal::parse(" int "),
+            Some(PtyCloseSignal::Interrupt)
+        );
+        assert_eq!(
+            PtyCloseSignal::parse("interrupt"),
+            Some(PtyCloseSignal::Interrupt)
+        );
+        assert_eq!(PtyCloseSignal::parse("kill"), None);
+    }
+
+    #[test]
+    fn terminate_escalates_until_the_child_exits() {
+        let no_foreground = || None;
+        let options = PtyCloseOptions {
+            signal: PtyCloseSignal::Hangup,
+            grace_period_ms: 200,
+        };
+
+        let (pid, watch) = spawn_group("sleep 30");
+        let groups = PtyProcessGroups {
+            shell_pid: Some(pid),
+            foreground: &no_foreground,
+        };
+        let outcome = terminate("graceful", &groups, &watch, &options);
+        assert_eq!(outcome.stage, PtyCloseStage::Graceful);
+        assert!(outcome.exit.is_some());
+
+        let (pid, watch) = spawn_group("trap '' HUP; sleep 30");
+        let groups = PtyProcessGroups {
+            shell_pid: Some(pid),
+            foreground: &no_foreground,
+        };
+        let outcome = terminate("terminated", &groups, &watch, &options);
+        assert_eq!(outcome.stage, PtyCloseStage::Terminated);
+        assert!(outcome.elapsed_ms >= 200, "{:?}", outcome);
+
+        let (pid, watch) = spawn_group("trap '' HUP TERM; while :; do sleep 1; done");
+        let groups = PtyProcessGroups {
+            shell_pid: Some(pid),
+            foreground: &no_foreground,
+        };
+        let outcome = terminate("killed", &groups, &watch, &options);
+        assert_eq!(outcome.stage, PtyCloseStage::Killed);
+
+        let outcome = terminate("again", &groups, &watch, &options);
+        assert_eq!(outcome.stage, PtyCloseStage::AlreadyExited);
+    }
+}
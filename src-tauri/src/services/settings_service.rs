@@ -1,11 +1,16 @@
 use std::collections::HashSet;
 use std::sync::Mutex;
 
-use crate::models::app_settings::{AppSettings, LastOpenedSession, ThemePalette};
+use crate::models::app_settings::{AppSettings, LastOpenedSession, ModelPrice, ThemePalette};
+use crate::services::pty_shutdown::{PtyCloseSignal, MAX_CLOSE_GRACE_PERIOD_MS};
 use crate::services::storage_service::StorageService;
 
 const SETTINGS_VERSION: u32 = 11;
 const DEFAULT_CUSTOM_STARTUP_ARGS: &str = "--dangerously-skip-permissions";
+const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+const MAX_TRASH_RETENTION_DAYS: u32 = 3650;
+const MAX_JOB_CONCURRENCY: u32 = 16;
+const MAX_JOB_RETRIES: u32 = 10;
 
 pub struct SettingsService {
     settings: Mutex<AppSettings>,
@@ -62,6 +67,14 @@ impl SettingsService {
         }
     }
 
+    fn normalize_price(value: f64) -> f64 {
+        if value.is_finite() && value > 0.0 {
+            value
+        } else {
+            0.0
+        }
+    }
+
     fn normalize_palette(palette: &mut ThemePalette, defaults: &ThemePalette) {
         palette.app_bg = Self::normalize_non_empty(&palette.app_bg, &defaults.app_bg);
         palette.panel_bg = Self::normalize_non_empty(&palette.panel_bg, &defaults.panel_bg);
@@ -365,11 +378,54 @@ impl SettingsService {
             settings.claude.custom_startup_args = DEFAULT_CUSTOM_STARTUP_ARGS.to_string();
         }
 
+        if settings.claude.trash_retention_days == 0 {
+            settings.claude.trash_retention_days = DEFAULT_TRASH_RETENTION_DAYS;
+        }
+        settings.claude.trash_retention_days = settings
+            .claude
+            .trash_retention_days
+            .min(MAX_TRASH_RETENTION_DAYS);
+        settings.claude.job_concurrency = settings
+            .claude
+            .job_concurrency
+            .clamp(1, MAX_JOB_CONCURRENCY);
+        settings.claude.job_max_retries = settings.claude.job_max_retries.min(MAX_JOB_RETRIES);
+
+        let mut seen_model_patterns = HashSet::new();
+        settings.claude.model_prices = settings
+            .claude
+            .model_prices
+            .into_iter()
+            .filter_map(|price| {
+                let pattern = price.model_pattern.trim().to_lowercase();
+                if pattern.is_empty() || !seen_model_patterns.insert(pattern.clone()) {
+                    return None;
+                }
+
+                Some(ModelPrice {
+                    model_pattern: pattern,
+                    input_per_mtok: Self::normalize_price(price.input_per_mtok),
+                    output_per_mtok: Self::normalize_price(price.output_per_mtok),
+                    cache_write_per_mtok: Self::normalize_price(price.cache_write_per_mtok),
+                    cache_read_per_mtok: Self::normalize_price(price.cache_read_per_mtok),
+                })
+            })
+            .collect();
+
         settings.integrations.default_external_terminal =
             Self::normalize_non_empty(&settings.integrations.default_external_terminal, "Terminal");
         settings.integrations.default_external_editor =
             Self::normalize_non_empty(&settings.integrations.default_external_editor, "VSCode");
 
+        settings.terminal.close_signal = PtyCloseSignal::parse(&settings.terminal.close_signal)
+            .unwrap_or_default()
+            .as_str()
+            .to_string();
+        settings.terminal.close_grace_period_ms = settings
+            .terminal
+            .close_grace_period_ms
+            .min(MAX_CLOSE_GRACE_PERIOD_MS);
+
         settings.ui.layout.sidebar_width = settings.ui.layout.sidebar_width.clamp(200, 600);
         settings.ui.layout.main_width = settings.ui.layout.main_width.clamp(300, 1000);
         settings.ui.layout.terminal_height = settings.ui.layout.terminal_height.clamp(100, 800);
@@ -512,4 +568,66 @@ mod tests {
 
         assert_eq!(serialized["appearance"]["theme_preset"], "everforest");
     }
+
+    #[test]
+    fn normalize_cleans_model_price_table() {
+        let settings: AppSettings = serde_json::from_value(serde_json::json!({
+            "version": 11,
+            "claude": {
+                "model_prices": [
+                    { "model_pattern": " Claude-Sonnet-4 ", "input_per_mtok": 3.0, "output_per_mtok": -1.0 },
+                    { "model_pattern": "claude-sonnet-4", "input_per_mtok": 9.0 },
+                    { "model_pattern": "   ", "input_per_mtok": 1.0 }
+                ]
+            }
+        }))
+        .expect("settings should deserialize");
+
+        let normalized = SettingsService::normalize(settings);
+        let prices = &normalized.claude.model_prices;
+
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices[0].model_pattern, "claude-sonnet-4");
+        assert_eq!(prices[0].input_per_mtok, 3.0);
+        assert_eq!(prices[0].output_per_mtok, 0.0);
+    }
+
+    #[test]
+    fn normalize_clamps_terminal_close_settings() {
+        let settings: AppSettings = serde_json::from_value(serde_json::json!({
+            "version": 11,
+            "terminal": { "close_signal": " SIGINT ", "close_grace_period_ms": 60000 }
+        }))
+        .expect("settings should deserialize");
+        let invalid: AppSettings = serde_json::from_value(serde_json::json!({
+            "version": 11,
+            "terminal": { "close_signal": "sigkill" }
+        }))
+        .expect("settings should deserialize");
+
+        let normalized = SettingsService::normalize(settings);
+        let normalized_invalid = SettingsService::normalize(invalid);
+
+        assert_eq!(normalized.terminal.close_signal, "interrupt");
+        assert_eq!(normalized.terminal.close_grace_period_ms, 10_000);
+        assert_eq!(normalized_invalid.terminal.close_signal, "hangup");
+        assert_eq!(normalized_invalid.terminal.close_grace_period_ms, 3000);
+    }
+
+    #[test]
+    fn legacy_settings_receive_default_model_prices() {
+        let legacy_settings: AppSettings = serde_json::from_value(serde_json::json!({
+            "version": 11,
+            "claude": { "use_custom_startup_args": true }
+        }))
+        .expect("legacy settings should deserialize");
+
+        let normalized = SettingsService::normalize(legacy_settings);
+
+        assert!(normalized
+            .claude
+            .model_prices
+            .iter()
+            .any(|price| price.model_pattern == "claude-sonnet-4"));
+    }
 }
@@ -49,6 +49,27 @@ impl StorageService {
         Self::app_data_dir().join("sessions.json")
     }
 
+    pub fn claude_session_cache_file() -> PathBuf {
+        Self::app_data_dir().join("claude-session-cache.json")
+    }
+
+    pub fn claude_jobs_file() -> PathBuf {
+        Self::app_data_dir().join("claude-jobs.json")
+    }
+
+    pub fn claude_trash_dir() -> PathBuf {
+        Self::app_data_dir().join("claude-trash")
+    }
+
+    pub fn pty_recordings_dir() -> PathBuf {
+        Self::app_data_dir().join("pty-recordings")
+    }
+
+    /// One socket per daemon protocol, so an upgraded app never talks to an older daemon.
+    pub fn pty_daemon_socket_file(protocol: u32) -> PathBuf {
+        Self::app_data_dir().join(format!("pty-daemon-v{}.sock", protocol))
+    }
+
     #[allow(dead_code)]
     pub fn preferences_file() -> PathBuf {
         Self::app_data_dir().join("preferences.json")
@@ -108,6 +129,33 @@ pub(crate) fn storage_test_env_lock() -> &'static std::sync::Mutex<()> {
     LOCK.get_or_init(|| std::sync::Mutex::new(()))
 }
 
+/// Sets an environment variable and restores its previous value on drop, including when
+/// the test panics. Hold `storage_test_env_lock` for as long as the guard lives.
+#[cfg(test)]
+pub(crate) struct EnvVarGuard {
+    key: &'static str,
+    previous: Option<std::ffi::OsString>,
+}
+
+#[cfg(test)]
+impl EnvVarGuard {
+    pub(crate) fn set(key: &'static str, value: impl AsRef<std::ffi::OsStr>) -> Self {
+        let previous = std::env::var_os(key);
+        std::env::set_var(key, value);
+        Self { key, previous }
+    }
+}
+
+#[cfg(test)]
+impl Drop for EnvVarGuard {
+    fn drop(&mut self) {
+        match self.previous.take() {
+            Some(value) => std::env::set_var(self.key, value),
+            None => std::env::remove_var(self.key),
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) fn unique_test_data_dir(name: &str) -> PathBuf {
     std::env::temp_dir().join(format!(
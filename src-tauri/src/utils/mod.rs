@@ -2,4 +2,5 @@ pub mod terminal;
 
 pub use terminal::{
     detect_available_terminals, open_terminal_with_command, open_terminal_with_path,
+    resolve_shell_path,
 };
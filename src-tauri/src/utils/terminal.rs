@@ -250,6 +250,34 @@ fn create_temp_script(path: &str, command: &str, file_prefix: &str) -> Result<St
     Ok(script_path)
 }
 
+/// Resolves a shell name (`zsh`) or path to an executable: `/bin/<name>` first, then `$PATH`.
+pub fn resolve_shell_path(shell: &str) -> Result<String, String> {
+    let normalized = shell.trim();
+
+    if normalized.is_empty() {
+        return Err("Shell cannot be empty".to_string());
+    }
+
+    if normalized.contains('/') {
+        if Path::new(normalized).exists() {
+            return Ok(normalized.to_string());
+        }
+
+        return Err(format!("Shell executable not found: {}", normalized));
+    }
+
+    let bundled_path = format!("/bin/{}", normalized);
+    if Path::new(&bundled_path).exists() {
+        return Ok(bundled_path);
+    }
+
+    if command_exists(normalized) {
+        return Ok(normalized.to_string());
+    }
+
+    Err(format!("Unsupported or missing shell: {}", normalized))
+}
+
 fn command_exists(command: &str) -> bool {
     let normalized = command.trim();
     if normalized.is_empty() || normalized.contains(char::is_whitespace) {
@@ -26,6 +26,13 @@ interface EmbeddedTerminalProps {
   isDark: boolean;
   themePalette?: EmbeddedTerminalThemePalette;
   claudeArgs?: string[];
+  /** Shell name or path; the backend falls back to $SHELL */
+  shell?: string;
+  shellArgs?: string[];
+  /** App project id; its path is used for `.env` loading */
+  projectId?: string;
+  /** Stored app session whose environment variables the PTY gets */
+  appSessionId?: string;
   onOutput?: (sessionId: string, data: string) => void;
 }
 
@@ -187,6 +194,10 @@ export default function EmbeddedTerminal({
   isDark,
   themePalette,
   claudeArgs = [],
+  shell,
+  shellArgs,
+  projectId,
+  appSessionId,
   onOutput,
 }: EmbeddedTerminalProps) {
   const containerRef = useRef<HTMLDivElement>(null);
@@ -209,6 +220,9 @@ export default function EmbeddedTerminal({
   const listenerReadyRef = useRef(false);
   const hasReceivedPtyOutputRef = useRef(false);
   const ptyCreateInFlightRef = useRef<Promise<void> | null>(null);
+  // Stream offset covered by the attach_pty snapshot; null until the snapshot is applied
+  const attachedOffsetRef = useRef<number | null>(null);
+  const preAttachChunksRef = useRef<Array<{ data: string; offset?: number }>>([]);
 
   useEffect(() => {
     visibleRef.current = visible;
@@ -279,8 +293,12 @@ export default function EmbeddedTerminal({
       claude_args: shouldResumeClaude ? normalizedClaudeArgs : null,
       allow_dangerously_skip_permissions:
         shouldResumeClaude && allowDangerouslySkipPermissions,
+      shell: shell?.trim() || null,
+      shell_args: shellArgs ?? null,
+      app_session_id: appSessionId ?? null,
+      project_id: projectId ?? null,
     };
-  }, [claudeArgs, sessionId, workingDir]);
+  }, [appSessionId, claudeArgs, projectId, sessionId, shell, shellArgs, workingDir]);
 
   const ensurePtySession = useCallback(
     async (reason: string) => {
@@ -376,6 +394,8 @@ export default function EmbeddedTerminal({
     listenerReadyRef.current = false;
     hasReceivedPtyOutputRef.current = false;
     ptyCreateInFlightRef.current = null;
+    attachedOffsetRef.current = null;
+    preAttachChunksRef.current = [];
 
     terminalDebugLog(sessionId, "terminal mounted", {
       workingDir,
@@ -561,18 +581,32 @@ export default function EmbeddedTerminal({
       schedulePtyRenderFlush();
     };
 
+    const handlePtyChunk = (data: string) => {
+      if (!hasReceivedPtyOutputRef.current) {
+        hasReceivedPtyOutputRef.current = true;
+        terminalDebugLog(sessionId, "received first PTY chunk", {
+          bytes: data.length,
+          visible: visibleRef.current,
+        });
+      }
+
+      onOutputRef.current?.(sessionId, data);
+      queuePtyRender(data);
+    };
+
     const setupListener = async () => {
-      const unlisten = await subscribePtyOutput(sessionId, (data) => {
-        if (!hasReceivedPtyOutputRef.current) {
-          hasReceivedPtyOutputRef.current = true;
-          terminalDebugLog(sessionId, "received first PTY chunk", {
-            bytes: data.length,
-            visible: visibleRef.current,
-          });
+      const unlisten = await subscribePtyOutput(sessionId, (data, offset) => {
+        const attachedOffset = attachedOffsetRef.current;
+        if (attachedOffset === null) {
+          preAttachChunksRef.current.push({ data, offset });
+          return;
         }
 
-        onOutputRef.current?.(sessionId, data);
-        queuePtyRender(data);
+        if (offset !== undefined && offset <= attachedOffset) {
+          return;
+        }
+
+        handlePtyChunk(data);
       });
       unlistenRef.current = unlisten;
       listenerReadyRef.current = true;
@@ -680,6 +714,36 @@ export default function EmbeddedTerminal({
       fitTerminal();
     }, 140);
 
+    // Restore output printed while no terminal was mounted (e.g. after a webview reload),
+    // then release chunks that arrived during the round trip and are not in the snapshot.
+    const attachPty = async () => {
+      let snapshotOffset = 0;
+      try {
+        const attachment = await invoke<{ data: string; offset: number }>("attach_pty", {
+          session_id: sessionId,
+        });
+        snapshotOffset = attachment.offset;
+        if (attachment.data) {
+          handlePtyChunk(attachment.data);
+        }
+        terminalDebugLog(sessionId, "attached to PTY", {
+          bytes: attachment.data.length,
+          offset: attachment.offset,
+        });
+      } catch (error) {
+        terminalDebugLog(sessionId, "attach_pty failed", { error: String(error) });
+      }
+
+      attachedOffsetRef.current = snapshotOffset;
+      const queued = preAttachChunksRef.current;
+      preAttachChunksRef.current = [];
+      for (const chunk of queued) {
+        if (chunk.offset === undefined || chunk.offset > snapshotOffset) {
+          handlePtyChunk(chunk.data);
+        }
+      }
+    };
+
     let disposed = false;
 
     void (async () => {
@@ -699,6 +763,12 @@ export default function EmbeddedTerminal({
 
       await ensurePtySession("mount");
 
+      if (disposed) {
+        return;
+      }
+
+      await attachPty();
+
       if (!disposed) {
         window.setTimeout(() => {
           if (!hasReceivedPtyOutputRef.current) {
@@ -791,6 +861,8 @@ export default function EmbeddedTerminal({
       ptyCreateInFlightRef.current = null;
       listenerReadyRef.current = false;
       hasReceivedPtyOutputRef.current = false;
+      attachedOffsetRef.current = null;
+      preAttachChunksRef.current = [];
 
       terminal.dispose();
       terminalRef.current = null;
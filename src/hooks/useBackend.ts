@@ -6,7 +6,7 @@ import {
   useContext,
   useState,
 } from "react";
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
 
 // Type definitions
 export interface Project {
@@ -68,6 +68,19 @@ export interface ClaudeSession {
   is_sidechain: boolean;
 }
 
+export interface ClaudeSessionFileChange {
+  session_id: string;
+  kind: "created" | "updated" | "deleted";
+}
+
+/** Payload of the `claude-sessions-changed` event */
+export interface ClaudeProjectChange {
+  project_dir_name: string;
+  project_path: string;
+  sessions: ClaudeSessionFileChange[];
+  index_changed: boolean;
+}
+
 export interface UpdateCheckResult {
   current_version: string;
   latest_version: string;
@@ -87,6 +100,173 @@ export interface DownloadedUpdateResult {
   downloaded_path: string;
 }
 
+export interface PtyExitInfo {
+  status: string;
+  exit_code: number | null;
+  signal: string | null;
+  success: boolean;
+  killed_by_close: boolean;
+  duration_ms: number;
+  resume_fallback: boolean;
+}
+
+export interface PtyByteSubscription {
+  session_id: string;
+  subscription_id: number;
+  offset: number;
+  cols: number;
+  rows: number;
+}
+
+export type PtyLaunchMode =
+  | "plain"
+  | "claude_resume"
+  | "claude_new"
+  | "claude_continue"
+  | "claude_prompt";
+
+export interface ClaudeLaunchOptions {
+  model?: string;
+  permission_mode?: "default" | "accept_edits" | "plan" | "bypass_permissions";
+  allowed_tools?: string[];
+  disallowed_tools?: string[];
+  mcp_config?: string;
+  append_system_prompt?: string;
+}
+
+// Payload of the `pty-claude-session` event
+export interface PtyClaudeSession {
+  session_id: string;
+  claude_session_id: string;
+  transcript_path: string | null;
+  ready: boolean;
+}
+
+export type PtyCloseSignal = "hangup" | "interrupt";
+
+export interface PtyCloseOutcome {
+  session_id: string;
+  stage: "already_exited" | "graceful" | "terminated" | "killed" | "unresponsive";
+  elapsed_ms: number;
+  exit: PtyExitInfo | null;
+}
+
+export interface PtyProcessInfo {
+  pid: number;
+  parent_pid: number | null;
+  process_group: number | null;
+  name: string;
+  command_line: string;
+  cpu_percent: number | null;
+  memory_bytes: number | null;
+}
+
+export interface PtyProcessReport {
+  session_id: string;
+  shell_pid: number | null;
+  foreground_process_group: number | null;
+  foreground: PtyProcessInfo | null;
+  processes: PtyProcessInfo[];
+  idle: boolean;
+  running_claude: boolean;
+  cpu_percent: number | null;
+  memory_bytes: number | null;
+}
+
+export type ClaudeJobStatus =
+  | "pending"
+  | "running"
+  | "succeeded"
+  | "failed"
+  | "cancelled";
+
+export interface ClaudeJobUsage {
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_input_tokens: number;
+  cache_read_input_tokens: number;
+  total_cost_usd: number | null;
+}
+
+// Payload of the `claude-job-updated` event
+export interface ClaudeJob {
+  id: string;
+  project_path: string;
+  prompt: string;
+  options: ClaudeLaunchOptions;
+  status: ClaudeJobStatus;
+  attempts: number;
+  max_retries: number;
+  created_at: string;
+  started_at: string | null;
+  finished_at: string | null;
+  claude_session_id: string | null;
+  result: string | null;
+  error: string | null;
+  usage: ClaudeJobUsage | null;
+  num_turns: number | null;
+  duration_ms: number | null;
+}
+
+// Pending and running jobs in queue order
+export interface ClaudeJobQueue {
+  paused: boolean;
+  max_concurrent: number;
+  jobs: ClaudeJob[];
+}
+
+export interface ClaudeJobOutcome {
+  is_error: boolean;
+  subtype: string;
+  result: string | null;
+  claude_session_id: string | null;
+  usage: ClaudeJobUsage;
+  num_turns: number | null;
+  duration_ms: number | null;
+}
+
+// Payload of the `claude-job-progress` event
+export type ClaudeJobProgressEvent = { job_id: string } & (
+  | { kind: "started"; claude_session_id: string; model: string | null }
+  | { kind: "assistant_text"; text: string }
+  | { kind: "tool_use"; tool_use_id: string; tool: string; input: unknown }
+  | {
+      kind: "tool_result";
+      tool_use_id: string;
+      is_error: boolean;
+      content: string;
+    }
+  | ({ kind: "completed" } & ClaudeJobOutcome)
+);
+
+export interface ClaudeDiagnosticIssue {
+  code: string;
+  severity: "warning" | "error";
+  message: string;
+}
+
+export interface ClaudeDiagnostics {
+  ok: boolean;
+  claude_path: string | null;
+  claude_real_path: string | null;
+  claude_version: string | null;
+  claude_requires_node: boolean;
+  node_path: string | null;
+  node_version: string | null;
+  effective_path: string[];
+  claude_dir: { path: string; exists: boolean; writable: boolean };
+  login_shell: {
+    shell: string;
+    exists: boolean;
+    ok: boolean;
+    exit_code: number | null;
+    timed_out: boolean;
+    elapsed_ms: number;
+    claude_path: string | null;
+  };
+  issues: ClaudeDiagnosticIssue[];
+}
+
 export interface CreateProjectInput {
   name: string;
   path: string;
@@ -549,19 +729,37 @@ function useCreateBackend() {
       workingDir: string,
       options?: {
         enableClaudeResume?: boolean;
+        launchMode?: PtyLaunchMode;
         resumeSessionId?: string;
+        prompt?: string;
+        claudeSessionId?: string;
+        claudeOptions?: ClaudeLaunchOptions;
         claudeArgs?: string[];
         allowDangerouslySkipPermissions?: boolean;
+        shell?: string;
+        shellArgs?: string[];
+        appSessionId?: string;
+        projectId?: string;
+        loadDotenv?: boolean;
       }
     ): Promise<boolean> => {
       return invoke<boolean>("create_pty", {
         session_id: sessionId,
         working_dir: workingDir,
         enable_claude_resume: options?.enableClaudeResume ?? false,
+        launch_mode: options?.launchMode ?? null,
         resume_session_id: options?.resumeSessionId ?? null,
+        prompt: options?.prompt ?? null,
+        claude_session_id: options?.claudeSessionId ?? null,
+        claude_options: options?.claudeOptions ?? null,
         claude_args: options?.claudeArgs ?? null,
         allow_dangerously_skip_permissions:
           options?.allowDangerouslySkipPermissions ?? false,
+        shell: options?.shell ?? null,
+        shell_args: options?.shellArgs ?? null,
+        app_session_id: options?.appSessionId ?? null,
+        project_id: options?.projectId ?? null,
+        load_dotenv: options?.loadDotenv ?? null,
       });
     },
     []
@@ -574,6 +772,47 @@ function useCreateBackend() {
     []
   );
 
+  // Binary-safe input, e.g. pastes that are not valid UTF-8
+  const writePtyBytes = useCallback(
+    async (sessionId: string, data: Uint8Array): Promise<void> => {
+      let binary = "";
+      for (let index = 0; index < data.length; index += 0x8000) {
+        binary += String.fromCharCode(...data.subarray(index, index + 0x8000));
+      }
+      return invoke<void>("write_pty_bytes", {
+        session_id: sessionId,
+        data: btoa(binary),
+      });
+    },
+    []
+  );
+
+  // Raw output: the scrollback first, then every chunk as read from the PTY.
+  const subscribePtyBytes = useCallback(
+    async (
+      sessionId: string,
+      onData: (data: Uint8Array) => void
+    ): Promise<{ subscription: PtyByteSubscription; unsubscribe: () => Promise<void> }> => {
+      const channel = new Channel<ArrayBuffer>();
+      channel.onmessage = (data) => onData(new Uint8Array(data));
+
+      const subscription = await invoke<PtyByteSubscription>("subscribe_pty_bytes", {
+        session_id: sessionId,
+        on_output: channel,
+      });
+      const unsubscribe = async () => {
+        channel.onmessage = () => {};
+        await invoke<boolean>("unsubscribe_pty_bytes", {
+          session_id: sessionId,
+          subscription_id: subscription.subscription_id,
+        });
+      };
+
+      return { subscription, unsubscribe };
+    },
+    []
+  );
+
   const resizePty = useCallback(
     async (sessionId: string, cols: number, rows: number): Promise<void> => {
       return invoke<void>("resize_pty", { session_id: sessionId, cols, rows });
@@ -582,15 +821,112 @@ function useCreateBackend() {
   );
 
   const closePty = useCallback(
-    async (sessionId: string): Promise<void> => {
-      return invoke<void>("close_pty", { session_id: sessionId });
+    async (
+      sessionId: string,
+      options?: { signal?: PtyCloseSignal; gracePeriodMs?: number }
+    ): Promise<PtyCloseOutcome> => {
+      return invoke<PtyCloseOutcome>("close_pty", {
+        session_id: sessionId,
+        signal: options?.signal,
+        grace_period_ms: options?.gracePeriodMs,
+      });
     },
     []
   );
 
   const closeAllPtys = useCallback(
-    async (): Promise<void> => {
-      return invoke<void>("close_all_ptys");
+    async (options?: {
+      signal?: PtyCloseSignal;
+      gracePeriodMs?: number;
+    }): Promise<PtyCloseOutcome[]> => {
+      return invoke<PtyCloseOutcome[]>("close_all_ptys", {
+        signal: options?.signal,
+        grace_period_ms: options?.gracePeriodMs,
+      });
+    },
+    []
+  );
+
+  const listPtyClaudeSessions = useCallback(async (): Promise<
+    PtyClaudeSession[]
+  > => {
+    return invoke<PtyClaudeSession[]>("list_pty_claude_sessions");
+  }, []);
+
+  const getPtyProcesses = useCallback(
+    async (sessionIds?: string[]): Promise<PtyProcessReport[]> => {
+      return invoke<PtyProcessReport[]>("get_pty_processes", {
+        session_ids: sessionIds,
+      });
+    },
+    []
+  );
+
+  // Headless Claude jobs
+  const startClaudeJob = useCallback(
+    async (
+      projectPath: string,
+      prompt: string,
+      claudeOptions?: ClaudeLaunchOptions
+    ): Promise<ClaudeJob> => {
+      return invoke<ClaudeJob>("start_claude_job", {
+        project_path: projectPath,
+        prompt,
+        claude_options: claudeOptions,
+      });
+    },
+    []
+  );
+
+  const enqueueClaudeJob = useCallback(
+    async (
+      projectPath: string,
+      prompt: string,
+      options?: { claudeOptions?: ClaudeLaunchOptions; maxRetries?: number }
+    ): Promise<ClaudeJob> => {
+      return invoke<ClaudeJob>("enqueue_claude_job", {
+        project_path: projectPath,
+        prompt,
+        claude_options: options?.claudeOptions,
+        max_retries: options?.maxRetries,
+      });
+    },
+    []
+  );
+
+  const reorderClaudeJobs = useCallback(
+    async (jobIds: string[]): Promise<ClaudeJobQueue> => {
+      return invoke<ClaudeJobQueue>("reorder_claude_jobs", { job_ids: jobIds });
+    },
+    []
+  );
+
+  const setClaudeJobQueuePaused = useCallback(
+    async (paused: boolean): Promise<ClaudeJobQueue> => {
+      return invoke<ClaudeJobQueue>("set_claude_job_queue_paused", { paused });
+    },
+    []
+  );
+
+  const getClaudeJobQueue = useCallback(async (): Promise<ClaudeJobQueue> => {
+    return invoke<ClaudeJobQueue>("get_claude_job_queue");
+  }, []);
+
+  const cancelClaudeJob = useCallback(
+    async (jobId: string): Promise<boolean> => {
+      return invoke<boolean>("cancel_claude_job", { job_id: jobId });
+    },
+    []
+  );
+
+  const listClaudeJobs = useCallback(async (): Promise<ClaudeJob[]> => {
+    const result = await invoke<ClaudeJob[]>("list_claude_jobs");
+    return result || [];
+  }, []);
+
+  const getClaudeJob = useCallback(
+    async (jobId: string): Promise<ClaudeJob> => {
+      return invoke<ClaudeJob>("get_claude_job", { job_id: jobId });
     },
     []
   );
@@ -684,6 +1020,10 @@ function useCreateBackend() {
     [runWithState]
   );
 
+  const getClaudeDiagnostics = useCallback(async (): Promise<ClaudeDiagnostics> => {
+    return invoke<ClaudeDiagnostics>("get_claude_diagnostics");
+  }, []);
+
   return {
     // State
     loading: state.pendingRequests > 0,
@@ -719,9 +1059,23 @@ function useCreateBackend() {
     // PTY commands
     createPty,
     writePty,
+    writePtyBytes,
+    subscribePtyBytes,
     resizePty,
     closePty,
     closeAllPtys,
+    getPtyProcesses,
+    listPtyClaudeSessions,
+
+    // Headless Claude jobs
+    startClaudeJob,
+    enqueueClaudeJob,
+    reorderClaudeJobs,
+    setClaudeJobQueuePaused,
+    getClaudeJobQueue,
+    cancelClaudeJob,
+    listClaudeJobs,
+    getClaudeJob,
 
     // Claude Code native session commands
     listClaudeSessions,
@@ -731,6 +1085,7 @@ function useCreateBackend() {
     checkForUpdates,
     downloadAndOpenUpdate,
     openExternalUrl,
+    getClaudeDiagnostics,
   };
 }
 
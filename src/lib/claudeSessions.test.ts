@@ -2,7 +2,9 @@ import test from "node:test";
 import assert from "node:assert/strict";
 import {
   buildSessionAliasKey,
+  encodeClaudeProjectPath,
   getDefaultSessionLabel,
+  getProjectsForClaudeChange,
   getProjectsWithOpenPlainTerminals,
   getSessionAliasForItem,
   getVisibleClaudeSessions,
@@ -116,6 +118,28 @@ test("getProjectsWithOpenPlainTerminals returns projects with plain terminals",
   );
 });
 
+test("getProjectsForClaudeChange matches projects by Claude dir name or path", () => {
+  const dotted = { ...project("dotted"), path: "/work/my.app_v2" };
+  const change = {
+    project_dir_name: encodeClaudeProjectPath(dotted.path),
+    // Decoding a dir name cannot restore the dot and underscore.
+    project_path: "/work/my/app/v2",
+  };
+
+  assert.equal(change.project_dir_name, "-work-my-app-v2");
+  assert.deepEqual(
+    getProjectsForClaudeChange([project("one"), dotted], change).map((item) => item.id),
+    ["dotted"]
+  );
+  assert.deepEqual(
+    getProjectsForClaudeChange([project("one"), dotted], {
+      project_dir_name: "-renamed",
+      project_path: "/tmp/one",
+    }).map((item) => item.id),
+    ["one"]
+  );
+});
+
 test("haveClaudeSessionsChanged detects newly discovered sessions", () => {
   const previous = [session({ session_id: "existing" })];
   const next = [session({ session_id: "existing" }), session({ session_id: "new-session" })];
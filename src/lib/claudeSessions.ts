@@ -1,4 +1,4 @@
-import type { ClaudeSession, Project } from "../hooks/useBackend";
+import type { ClaudeProjectChange, ClaudeSession, Project } from "../hooks/useBackend";
 
 type SessionAliasTarget = Pick<ClaudeSession, "project_path" | "session_id">;
 type SessionLabelSource = Pick<ClaudeSession, "summary" | "first_prompt" | "session_id">;
@@ -299,6 +299,22 @@ export function getProjectsWithOpenPlainTerminals(
   return projects.filter((project) => openTerminalSessionIds.has(`__plain__${project.id}`));
 }
 
+// Claude names a project dir after its path with every non-alphanumeric character as "-".
+export function encodeClaudeProjectPath(projectPath: string): string {
+  return projectPath.replace(/[^a-zA-Z0-9]/g, "-");
+}
+
+export function getProjectsForClaudeChange(
+  projects: Project[],
+  change: Pick<ClaudeProjectChange, "project_dir_name" | "project_path">
+): Project[] {
+  return projects.filter(
+    (project) =>
+      project.path === change.project_path ||
+      encodeClaudeProjectPath(project.path) === change.project_dir_name
+  );
+}
+
 export function haveClaudeSessionsChanged(
   previous: ClaudeSession[],
   next: ClaudeSession[]
@@ -6,7 +6,7 @@ export const DEFAULT_APP_LANGUAGE: AppLanguage = "zh-CN";
 
 const dictionaries = {
   "zh-CN": {
-    confirm_delete_session: "删除此会话？此操作不可撤销。",
+    confirm_delete_session: "删除此会话？会话将移入回收站，可在过期前恢复。",
     confirm_delete_project: "从侧边栏删除项目“{name}”？",
     status_ready: "已就绪。",
     status_load_failed: "加载项目/会话失败。",
@@ -36,6 +36,7 @@ const dictionaries = {
     status_open_release_failed: "打开发布页失败：{message}",
     status_terminal_write_failed: "终端写入失败，请重试或重开会话。",
     status_terminal_start_failed: "终端启动失败：{message}",
+    status_resume_fallback: "恢复会话失败，已启动新会话。",
     status_unexpected_error: "发生未处理错误，请查看日志。",
     title_show_sidebar: "展开侧边栏  ⌘B",
     title_hide_sidebar: "收起侧边栏  ⌘B",
@@ -131,7 +132,7 @@ const dictionaries = {
     fallback_new_project: "新项目",
   },
   "en-US": {
-    confirm_delete_session: "Delete this session? This cannot be undone.",
+    confirm_delete_session: "Delete this session? It will be moved to the trash and can be restored until it expires.",
     confirm_delete_project: "Remove project \"{name}\" from sidebar?",
     status_ready: "Ready.",
     status_load_failed: "Failed to load projects/sessions.",
@@ -161,6 +162,7 @@ const dictionaries = {
     status_open_release_failed: "Failed to open release page: {message}",
     status_terminal_write_failed: "Terminal write failed. Please retry or reopen the session.",
     status_terminal_start_failed: "Terminal failed to start: {message}",
+    status_resume_fallback: "Resume failed, started a new session.",
     status_unexpected_error: "An unexpected error occurred. Check logs for details.",
     title_show_sidebar: "Show sidebar  ⌘B",
     title_hide_sidebar: "Hide sidebar  ⌘B",
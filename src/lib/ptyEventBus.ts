@@ -1,6 +1,7 @@
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
-type SessionHandler = (data: string) => void;
+// `offset` is the PTY stream position just past this chunk (see `attach_pty`).
+type SessionHandler = (data: string, offset?: number) => void;
 
 
 const PTY_EVENT_DEBUG = (() => {
@@ -40,7 +41,7 @@ async function ensureGlobalListener(): Promise<void> {
   if (!listenerReady) {
     listenerReady = (async () => {
       try {
-        const unlisten = await listen<{ session_id: string; data: string }>(
+        const unlisten = await listen<{ session_id: string; data: string; offset?: number }>(
           "pty-output",
           (event) => {
             const sessionId = event.payload.session_id;
@@ -50,7 +51,7 @@ async function ensureGlobalListener(): Promise<void> {
             }
 
             for (const handler of handlers) {
-              handler(event.payload.data);
+              handler(event.payload.data, event.payload.offset);
             }
           }
         );
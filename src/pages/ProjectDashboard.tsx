@@ -4,8 +4,10 @@ import { listen } from "@tauri-apps/api/event";
 import { LogicalSize, getCurrentWindow } from "@tauri-apps/api/window";
 import {
   Project,
+  ClaudeProjectChange,
   ClaudeSession,
   DownloadedUpdateResult,
+  Session,
   useBackend,
 } from "../hooks/useBackend";
 import { LayoutState, useWindowManager } from "../hooks/useWindowManager";
@@ -20,6 +22,7 @@ import {
 import {
   buildSessionAliasKey,
   getDefaultSessionLabel,
+  getProjectsForClaudeChange,
   getProjectsWithOpenPlainTerminals,
   getSessionAliasForItem,
   getVisibleClaudeSessions,
@@ -110,6 +113,14 @@ type WindowSettings = {
   height: number;
 };
 
+type ModelPrice = {
+  model_pattern: string;
+  input_per_mtok: number;
+  output_per_mtok: number;
+  cache_write_per_mtok: number;
+  cache_read_per_mtok: number;
+};
+
 type LastOpenedSessionRef = {
   project_path: string;
   session_id: string;
@@ -126,6 +137,10 @@ type AppSettingsFile = {
   claude: {
     use_custom_startup_args: boolean;
     custom_startup_args: string;
+    model_prices?: ModelPrice[];
+    trash_retention_days?: number;
+    job_concurrency?: number;
+    job_max_retries?: number;
   };
   integrations: IntegrationSettings;
   ui: {
@@ -148,11 +163,28 @@ type AppSettingsFile = {
     restore_last_opened_session: boolean;
     last_opened: LastOpenedSessionRef | null;
   };
+  terminal?: {
+    use_pty_daemon?: boolean;
+    record_sessions?: boolean;
+    load_dotenv?: boolean;
+    close_signal?: string;
+    close_grace_period_ms?: number;
+  };
 };
 
 type PtyExitPayload = {
   session_id: string;
   status?: string;
+  exit_code?: number | null;
+  signal?: string | null;
+  success?: boolean;
+  killed_by_close?: boolean;
+  duration_ms?: number;
+  resume_fallback?: boolean;
+};
+
+type PtyResumeFallbackPayload = {
+  session_id: string;
 };
 
 type PtyCreateFailedPayload = {
@@ -507,6 +539,19 @@ const normalizeWindowSettings = (
   };
 };
 
+// A project's terminals use its most recently updated stored session for shell and env vars.
+const latestSessionByProject = (sessions: Session[]): Record<string, Session> => {
+  const latest: Record<string, Session> = {};
+  for (const session of sessions) {
+    const current = latest[session.project_id];
+    if (!current || session.updated_at > current.updated_at) {
+      latest[session.project_id] = session;
+    }
+  }
+
+  return latest;
+};
+
 const applyProjectOrder = (items: Project[], order: string[]): Project[] => {
   if (items.length <= 1 || order.length === 0) {
     return items;
@@ -536,6 +581,7 @@ export function ProjectDashboard() {
   const {
     listProjects,
     listClaudeSessions,
+    listSessions,
     createProject,
     deleteProject,
     closePty,
@@ -558,6 +604,7 @@ export function ProjectDashboard() {
   const [claudeSessionsByProject, setClaudeSessionsByProject] = useState<
     Record<string, ClaudeSession[]>
   >({});
+  const [appSessionsByProject, setAppSessionsByProject] = useState<Record<string, Session>>({});
   const [projectsReady, setProjectsReady] = useState(false);
   const [expandedProjects, setExpandedProjects] = useState<Record<string, boolean>>({});
   const [showAllSessions, setShowAllSessions] = useState<Record<string, boolean>>({});
@@ -609,6 +656,18 @@ export function ProjectDashboard() {
   });
 
   const lastPersistedSettingsRef = useRef<string>("");
+  // Claude settings without UI yet; kept so saving doesn't reset them to defaults
+  const claudeExtraSettingsRef = useRef<
+    Pick<
+      AppSettingsFile["claude"],
+      | "model_prices"
+      | "trash_retention_days"
+      | "job_concurrency"
+      | "job_max_retries"
+    >
+  >({});
+  // Terminal settings are edited in the config file only; kept so saving doesn't drop them
+  const terminalSettingsRef = useRef<NonNullable<AppSettingsFile["terminal"]>>({});
   const themeTransitionTimerRef = useRef<number | null>(null);
   const hasThemeModeMountedRef = useRef(false);
   const sidebarResizeRafRef = useRef<number | null>(null);
@@ -811,6 +870,7 @@ export function ProjectDashboard() {
   useEffect(() => {
     let unlistenPtyExit: (() => void) | null = null;
     let unlistenPtyCreateFailed: (() => void) | null = null;
+    let unlistenPtyResumeFallback: (() => void) | null = null;
 
     const cleanupTerminalSessionState = (sessionId: string) => {
       const normalizedSessionId = sessionId.trim();
@@ -843,6 +903,13 @@ export function ProjectDashboard() {
           cleanupTerminalSessionState(event.payload?.session_id ?? "");
         });
 
+        unlistenPtyResumeFallback = await listen<PtyResumeFallbackPayload>(
+          "pty-resume-fallback",
+          () => {
+            setStatusMessage(t("status_resume_fallback"));
+          }
+        );
+
         unlistenPtyCreateFailed = await listen<PtyCreateFailedPayload>(
           "pty-create-failed",
           (event) => {
@@ -870,6 +937,10 @@ export function ProjectDashboard() {
       if (unlistenPtyCreateFailed) {
         unlistenPtyCreateFailed();
       }
+
+      if (unlistenPtyResumeFallback) {
+        unlistenPtyResumeFallback();
+      }
     };
   }, [t]);
 
@@ -1084,6 +1155,25 @@ export function ProjectDashboard() {
     [projectById, selectedProject?.path, sessionLookupMap]
   );
 
+  const getProjectId = useCallback(
+    (terminalId: string): string | undefined => {
+      if (terminalId.startsWith("__plain__")) {
+        return terminalId.replace("__plain__", "");
+      }
+
+      return sessionLookupMap.get(terminalId)?.project.id || selectedProject?.id;
+    },
+    [selectedProject?.id, sessionLookupMap]
+  );
+
+  const getAppSession = useCallback(
+    (terminalId: string): Session | undefined => {
+      const projectId = getProjectId(terminalId);
+      return projectId ? appSessionsByProject[projectId] : undefined;
+    },
+    [appSessionsByProject, getProjectId]
+  );
+
   const refreshClaudeSessionsForProject = useCallback(
     async (projectId: string, projectPath: string) => {
       const sessions = await listClaudeSessions(projectPath);
@@ -1112,6 +1202,12 @@ export function ProjectDashboard() {
       const orderedProjects = applyProjectOrder(loadedProjects, projectOrder);
       setProjects(orderedProjects);
 
+      try {
+        setAppSessionsByProject(latestSessionByProject(await listSessions()));
+      } catch (sessionsError) {
+        console.error("Failed to load stored sessions", sessionsError);
+      }
+
       const entries: Array<readonly [string, ClaudeSession[]]> = [];
       const concurrency = 4;
 
@@ -1200,6 +1296,44 @@ export function ProjectDashboard() {
     };
   }, [openTerminalSessionIds, projects, projectsReady, refreshClaudeSessionsForProject]);
 
+  useEffect(() => {
+    if (!projectsReady) {
+      return;
+    }
+
+    let disposed = false;
+    let unlisten: (() => void) | null = null;
+
+    const subscribe = async () => {
+      try {
+        const stop = await listen<ClaudeProjectChange>("claude-sessions-changed", (event) => {
+          for (const project of getProjectsForClaudeChange(projects, event.payload)) {
+            void refreshClaudeSessionsForProject(project.id, project.path).catch((refreshError) => {
+              console.warn("Failed to refresh Claude sessions", refreshError);
+            });
+          }
+        });
+
+        if (disposed) {
+          stop();
+        } else {
+          unlisten = stop;
+        }
+      } catch (listenError) {
+        console.warn("Failed to subscribe to Claude session changes", listenError);
+      }
+    };
+
+    void subscribe();
+
+    return () => {
+      disposed = true;
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, [projects, projectsReady, refreshClaudeSessionsForProject]);
+
   useEffect(() => {
     if (projects.length === 0) {
       return;
@@ -1254,6 +1388,13 @@ export function ProjectDashboard() {
               ? settings.claude.custom_startup_args
               : DEFAULT_CLAUDE_CUSTOM_ARGS,
         });
+        claudeExtraSettingsRef.current = {
+          model_prices: settings.claude?.model_prices,
+          trash_retention_days: settings.claude?.trash_retention_days,
+          job_concurrency: settings.claude?.job_concurrency,
+          job_max_retries: settings.claude?.job_max_retries,
+        };
+        terminalSettingsRef.current = settings.terminal ?? {};
 
         const normalizedWindow = normalizeWindowSettings(settings.ui?.window);
 
@@ -1465,6 +1606,7 @@ export function ProjectDashboard() {
         theme_palettes: themePalettes,
       },
       claude: {
+        ...claudeExtraSettingsRef.current,
         use_custom_startup_args: claudeStartupSettings.enabled,
         custom_startup_args:
           claudeStartupSettings.customArgs.trim() || DEFAULT_CLAUDE_CUSTOM_ARGS,
@@ -1496,6 +1638,7 @@ export function ProjectDashboard() {
         restore_last_opened_session: restoreLastOpenedSession,
         last_opened: lastOpenedSession,
       },
+      terminal: terminalSettingsRef.current,
     };
 
     const serializedPayload = JSON.stringify(settingsPayload);
@@ -3004,18 +3147,24 @@ export function ProjectDashboard() {
 
         <div className="terminal-area">
           <div className="terminal-wrapper">
-            {openTerminalIds.map((id) => (
-              <EmbeddedTerminal
-                key={id}
-                sessionId={id}
-                workingDir={getWorkingDir(id)}
-                visible={id === activeTerminalId}
-                isDark={themeMode === "dark"}
-                themePalette={terminalThemePalette}
-                claudeArgs={customClaudeArgs}
-                onOutput={handleTerminalOutput}
-              />
-            ))}
+            {openTerminalIds.map((id) => {
+              const appSession = getAppSession(id);
+              return (
+                <EmbeddedTerminal
+                  key={id}
+                  sessionId={id}
+                  workingDir={getWorkingDir(id)}
+                  projectId={getProjectId(id)}
+                  appSessionId={appSession?.id}
+                  shell={appSession?.shell}
+                  visible={id === activeTerminalId}
+                  isDark={themeMode === "dark"}
+                  themePalette={terminalThemePalette}
+                  claudeArgs={customClaudeArgs}
+                  onOutput={handleTerminalOutput}
+                />
+              );
+            })}
             {openTerminalSessionIds.size === 0 && (
               <div className="workspace-placeholder">
                 <p>{t("workspace_placeholder")}</p>